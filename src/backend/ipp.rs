@@ -0,0 +1,223 @@
+//! IPP printjob without libcups.
+//!
+//! All data is collected in memory and sent with Print-Job on close(),
+//! so an abort before that never reaches the printer. Dropping the job
+//! without close() is an abort.
+//!
+//! A job with several documents is sent with Create-Job and one
+//! Send-Document per document.
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::ipp::template::{document_format, job_template, supported_format};
+use crate::ipp::{Attribute, IppClient};
+use crate::{wait_for_job, Backend, Format, JobParam, JobStatus, Lifecycle, PrintError};
+
+/// Printjob for an IPP printer or a cupsd queue.
+#[derive(Debug)]
+pub struct IppJob {
+    client: IppClient,
+    job_name: String,
+    job_attrs: Vec<Attribute>,
+    /// document-format-supported of the printer.
+    formats: Vec<String>,
+    job_id: i32,
+    /// All documents, the last one is written to.
+    documents: Vec<Document>,
+    /// Documents already sent with Send-Document.
+    sent: usize,
+    lifecycle: Lifecycle,
+}
+
+#[derive(Debug)]
+struct Document {
+    name: String,
+    format: &'static str,
+    data: Vec<u8>,
+}
+
+impl Write for IppJob {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.lifecycle.is_done() {
+            return Err(PrintError::io_error(PrintError::Closed));
+        }
+        self.lifecycle = Lifecycle::Writing;
+        if let Some(doc) = self.documents.last_mut() {
+            doc.data.extend_from_slice(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl IppJob {
+    /// Starts a printjob for the printer at an ipp:// or http:// uri.
+    pub fn new(uri: &str, doc_name: &str) -> io::Result<Self> {
+        Self::new_with(uri, doc_name, &JobParam::default())
+    }
+
+    /// Starts a printjob. The parameters are sent as job-template
+    /// attributes.
+    ///
+    /// Checks that the printer exists.
+    pub fn new_with(uri: &str, doc_name: &str, param: &JobParam) -> io::Result<Self> {
+        let client = IppClient::new(uri)?;
+        Self::with_client(client, doc_name, param)
+    }
+
+    /// Starts a printjob with a configured client.
+    pub fn with_client(client: IppClient, doc_name: &str, param: &JobParam) -> io::Result<Self> {
+        let attr =
+            client.get_printer_attributes(&["printer-state", "document-format-supported"])?;
+        let formats = attr
+            .iter()
+            .filter(|v| v.name == "document-format-supported")
+            .flat_map(|v| v.values.iter())
+            .filter_map(|v| v.as_str())
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>();
+        let param = param.prepare(|| client.capabilities())?;
+
+        Ok(Self {
+            client,
+            job_name: doc_name.to_string(),
+            job_attrs: job_template(&param),
+            job_id: 0,
+            documents: vec![Document {
+                name: doc_name.to_string(),
+                format: supported_format(document_format(&param.data_format), &formats),
+                data: Vec::new(),
+            }],
+            formats,
+            sent: 0,
+            lifecycle: Lifecycle::Created,
+        })
+    }
+
+    /// Timeout for connect, read and write. Defaults to 30s.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.client.set_timeout(timeout);
+    }
+
+    /// The client used for this job.
+    pub fn client(&self) -> &IppClient {
+        &self.client
+    }
+
+    /// The job-id. Only known after close().
+    pub fn job_id(&self) -> u32 {
+        self.job_id as u32
+    }
+
+    /// Status of the job at the printer.
+    pub fn status(&self) -> io::Result<JobStatus> {
+        if self.job_id == 0 {
+            return Err(PrintError::io_error(PrintError::NotFound));
+        }
+        self.client.job_status(self.job_id)
+    }
+
+    /// Waits until the printer has finished the job.
+    ///
+    /// Fails with PrintError::JobFailed if the job was canceled or aborted,
+    /// and with PrintError::Timeout if it takes too long.
+    pub fn wait_for_completion(&self, timeout: Duration) -> io::Result<()> {
+        wait_for_job(timeout, || self.status())
+    }
+
+    /// Closes the job and waits until the printer has finished it.
+    pub fn close_and_wait(&mut self, timeout: Duration) -> io::Result<()> {
+        self.close()?;
+        self.wait_for_completion(timeout)
+    }
+
+    /// State of the printjob.
+    pub fn lifecycle(&self) -> Lifecycle {
+        self.lifecycle
+    }
+
+    /// Ends the current document and starts the next one in the same
+    /// job. Everything written from now on goes to the new document.
+    pub fn next_document(&mut self, doc_name: &str, format: Format) -> io::Result<()> {
+        if self.lifecycle.is_done() {
+            return Err(PrintError::io_error(PrintError::Closed));
+        }
+        self.documents.push(Document {
+            name: doc_name.to_string(),
+            format: supported_format(document_format(&format), &self.formats),
+            data: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Sends the job to the printer.
+    ///
+    /// If this fails the data is kept and close() can be tried again.
+    /// With several documents the ones already sent are not sent again,
+    /// and the error is a PrintError::Document for the failed one.
+    pub fn close(&mut self) -> io::Result<()> {
+        match self.lifecycle {
+            Lifecycle::Closed => return Ok(()),
+            Lifecycle::Aborted => return Err(PrintError::io_error(PrintError::Closed)),
+            _ => {}
+        }
+
+        if let [doc] = self.documents.as_slice() {
+            self.job_id =
+                self.client
+                    .print_job(&self.job_name, doc.format, &self.job_attrs, &doc.data)?;
+        } else {
+            if self.job_id == 0 {
+                self.job_id = self.client.create_job(&self.job_name, &self.job_attrs)?;
+            }
+            let last = self.documents.len() - 1;
+            for (index, doc) in self.documents.iter_mut().enumerate().skip(self.sent) {
+                self.client
+                    .send_document(self.job_id, &doc.name, doc.format, index == last, &doc.data)
+                    .map_err(|e| PrintError::document_error(index, &doc.name, e))?;
+                doc.data = Vec::new();
+                self.sent = index + 1;
+            }
+        }
+        self.documents.iter_mut().for_each(|v| v.data = Vec::new());
+        self.lifecycle = Lifecycle::Closed;
+
+        Ok(())
+    }
+
+    /// Discards the data. Nothing has been sent to the printer yet,
+    /// except for a job with several documents where close() failed
+    /// halfway. That job is canceled.
+    pub fn abort(&mut self) -> io::Result<()> {
+        if self.lifecycle != Lifecycle::Closed {
+            self.lifecycle = Lifecycle::Aborted;
+            self.documents.iter_mut().for_each(|v| v.data = Vec::new());
+            if self.job_id != 0 {
+                self.client.cancel_job(self.job_id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Backend for IppJob {
+    fn start_page(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        IppJob::close(self)
+    }
+
+    fn abort(&mut self) -> io::Result<()> {
+        IppJob::abort(self)
+    }
+}
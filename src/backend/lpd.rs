@@ -0,0 +1,215 @@
+//! LPD client backend. See RFC 1179.
+//!
+//! The protocol needs the size of the data file up front, so all data is
+//! collected in memory and sent to the server on close().
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use crate::backend::{connect, net_error};
+use crate::{Backend, JobParam, Lifecycle, PrintError};
+
+/// Default port for LPD.
+pub const DEFAULT_PORT: u16 = 515;
+
+static JOB_NUMBER: AtomicU32 = AtomicU32::new(0);
+
+/// Printjob for a LPD server.
+#[derive(Debug)]
+pub struct LpdJob {
+    addr: String,
+    queue: String,
+    doc_name: String,
+    copies: u32,
+    timeout: Option<Duration>,
+    data: Vec<u8>,
+    lifecycle: Lifecycle,
+}
+
+impl Write for LpdJob {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.lifecycle.is_done() {
+            return Err(PrintError::io_error(PrintError::Closed));
+        }
+        self.lifecycle = Lifecycle::Writing;
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LpdJob {
+    /// Starts a printjob. The address is host or host:port, the port
+    /// defaults to 515.
+    pub fn new(addr: &str, queue: &str, doc_name: &str) -> io::Result<Self> {
+        Self::new_with(addr, queue, doc_name, &JobParam::default())
+    }
+
+    /// Starts a printjob. Of the parameters only the copies are used,
+    /// the data is always sent as format 'l' (raw).
+    pub fn new_with(addr: &str, queue: &str, doc_name: &str, param: &JobParam) -> io::Result<Self> {
+        for v in [addr, queue, doc_name] {
+            if v.contains(['\n', '\0']) {
+                return Err(PrintError::io_error(PrintError::Print(format!(
+                    "Invalid character in {:?}",
+                    v
+                ))));
+            }
+        }
+
+        Ok(Self {
+            addr: addr.to_string(),
+            queue: queue.to_string(),
+            doc_name: doc_name.to_string(),
+            copies: param.copies.unwrap_or(1),
+            timeout: Some(Duration::from_secs(30)),
+            data: Vec::new(),
+            lifecycle: Lifecycle::Created,
+        })
+    }
+
+    /// Timeout for connecting and for each answer of the server.
+    /// Defaults to 30s.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// State of the printjob.
+    pub fn lifecycle(&self) -> Lifecycle {
+        self.lifecycle
+    }
+
+    /// Sends the job to the server.
+    ///
+    /// If this fails the data is kept and close() can be tried again.
+    pub fn close(&mut self) -> io::Result<()> {
+        match self.lifecycle {
+            Lifecycle::Closed => return Ok(()),
+            Lifecycle::Aborted => return Err(PrintError::io_error(PrintError::Closed)),
+            _ => {}
+        }
+
+        let mut stream = connect(&self.addr, DEFAULT_PORT, self.timeout)?;
+        if let Err(e) = stream
+            .set_read_timeout(self.timeout)
+            .and_then(|_| stream.set_write_timeout(self.timeout))
+        {
+            return Err(net_error(&self.addr, e));
+        }
+
+        let job_no =
+            std::process::id().wrapping_add(JOB_NUMBER.fetch_add(1, Ordering::Relaxed)) % 1000;
+        let host = host_name();
+        let df_name = format!("dfA{:03}{}", job_no, host);
+        let cf_name = format!("cfA{:03}{}", job_no, host);
+
+        let mut control = String::new();
+        control.push_str(&format!("H{}\n", host));
+        control.push_str(&format!("P{}\n", user_name()));
+        control.push_str(&format!("J{}\n", self.doc_name));
+        control.push_str(&format!("N{}\n", self.doc_name));
+        for _ in 0..self.copies {
+            control.push_str(&format!("l{}\n", df_name));
+        }
+        control.push_str(&format!("U{}\n", df_name));
+
+        // receive a printer job
+        self.command(&mut stream, format!("\x02{}\n", self.queue).as_bytes())?;
+        // receive data file
+        self.command(
+            &mut stream,
+            format!("\x03{} {}\n", self.data.len(), df_name).as_bytes(),
+        )?;
+        self.send_file(&mut stream, &self.data)?;
+        // receive control file
+        self.command(
+            &mut stream,
+            format!("\x02{} {}\n", control.len(), cf_name).as_bytes(),
+        )?;
+        self.send_file(&mut stream, control.as_bytes())?;
+
+        // the job is complete with the control file.
+        self.lifecycle = Lifecycle::Closed;
+        self.data = Vec::new();
+        Ok(())
+    }
+
+    /// Discards the data. Nothing has been sent to the server yet.
+    pub fn abort(&mut self) -> io::Result<()> {
+        self.lifecycle = Lifecycle::Aborted;
+        self.data = Vec::new();
+        Ok(())
+    }
+
+    /// Sends a command and waits for the acknowledgement.
+    fn command(&self, stream: &mut TcpStream, cmd: &[u8]) -> io::Result<()> {
+        if let Err(e) = stream.write_all(cmd) {
+            return Err(net_error(&self.addr, e));
+        }
+        self.ack(stream)
+    }
+
+    /// Sends file content plus the terminating 0 and waits for the
+    /// acknowledgement.
+    fn send_file(&self, stream: &mut TcpStream, data: &[u8]) -> io::Result<()> {
+        if let Err(e) = stream.write_all(data).and_then(|_| stream.write_all(&[0])) {
+            return Err(net_error(&self.addr, e));
+        }
+        self.ack(stream)
+    }
+
+    fn ack(&self, stream: &mut TcpStream) -> io::Result<()> {
+        let mut ack = [0u8; 1];
+        match stream.read_exact(&mut ack) {
+            Ok(()) if ack[0] == 0 => Ok(()),
+            Ok(()) => Err(PrintError::io_error(PrintError::Print(format!(
+                "{}: LPD queue {} refused the job",
+                self.addr, self.queue
+            )))),
+            Err(e) => Err(net_error(&self.addr, e)),
+        }
+    }
+}
+
+impl Backend for LpdJob {
+    fn start_page(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        LpdJob::close(self)
+    }
+
+    fn abort(&mut self) -> io::Result<()> {
+        LpdJob::abort(self)
+    }
+}
+
+/// Host name for the control file. At most 31 characters.
+fn host_name() -> String {
+    let mut host = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "localhost".to_string());
+    host.retain(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    host.truncate(31);
+    host
+}
+
+/// User name for the control file. At most 31 characters.
+fn user_name() -> String {
+    let mut user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "uniprint".to_string());
+    user.retain(|c| !c.is_whitespace());
+    user.truncate(31);
+    user
+}
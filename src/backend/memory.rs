@@ -0,0 +1,111 @@
+//! Backend that captures everything in memory.
+//!
+//! Useful for testing drivers without a printer.
+
+use std::io::Write;
+
+use crate::Backend;
+
+/// Calls to the job lifecycle, recorded by a MemoryJob.
+///
+/// Each event carries the number of bytes written up to that point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    StartPage(usize),
+    EndPage(usize),
+    Close(usize),
+    Abort(usize),
+}
+
+/// Records all bytes written and all the calls to the job lifecycle.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryJob {
+    data: Vec<u8>,
+    events: Vec<Event>,
+}
+
+impl Write for MemoryJob {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Backend for MemoryJob {
+    fn start_page(&mut self) -> std::io::Result<()> {
+        self.events.push(Event::StartPage(self.data.len()));
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> std::io::Result<()> {
+        self.events.push(Event::EndPage(self.data.len()));
+        Ok(())
+    }
+
+    fn close(&mut self) -> std::io::Result<()> {
+        self.events.push(Event::Close(self.data.len()));
+        Ok(())
+    }
+
+    fn abort(&mut self) -> std::io::Result<()> {
+        self.events.push(Event::Abort(self.data.len()));
+        Ok(())
+    }
+}
+
+impl MemoryJob {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// All bytes written.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Take the written bytes.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Lifecycle calls in order.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Bytes written between each start_page() and the following end_page().
+    /// A page that is still open at the end extends to the end of the data.
+    pub fn pages(&self) -> Vec<&[u8]> {
+        let mut pages = Vec::new();
+        let mut start = None;
+        for event in &self.events {
+            match *event {
+                Event::StartPage(pos) => start = Some(pos),
+                Event::EndPage(pos) => {
+                    if let Some(start) = start.take() {
+                        pages.push(&self.data[start..pos]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = start {
+            pages.push(&self.data[start..]);
+        }
+        pages
+    }
+
+    /// Has close() been called.
+    pub fn is_closed(&self) -> bool {
+        self.events.iter().any(|v| matches!(v, Event::Close(_)))
+    }
+
+    /// Has abort() been called.
+    pub fn is_aborted(&self) -> bool {
+        self.events.iter().any(|v| matches!(v, Event::Abort(_)))
+    }
+}
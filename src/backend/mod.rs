@@ -0,0 +1,73 @@
+//!
+//! Backends beside the system printjob.
+//!
+
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::PrintError;
+
+pub mod ipp;
+pub mod lpd;
+pub mod memory;
+pub mod socket;
+
+/// Connect to host or host:port. Uses the default port if none is given.
+pub(crate) fn connect(
+    addr: &str,
+    default_port: u16,
+    timeout: Option<Duration>,
+) -> io::Result<TcpStream> {
+    let addrs = match with_port(addr, default_port).to_socket_addrs() {
+        Ok(v) => v,
+        Err(e) => return Err(net_error(addr, e)),
+    };
+
+    let mut last_err = None;
+    for sock_addr in addrs {
+        let res = match timeout {
+            Some(timeout) => TcpStream::connect_timeout(&sock_addr, timeout),
+            None => TcpStream::connect(sock_addr),
+        };
+        match res {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_err = Some(e),
+        }
+    }
+
+    Err(match last_err {
+        Some(e) => net_error(addr, e),
+        None => PrintError::io_error(PrintError::Network(format!("{}: no address", addr))),
+    })
+}
+
+/// Append the default port if there is none.
+fn with_port(addr: &str, default_port: u16) -> String {
+    if let Some(rest) = addr.strip_prefix('[') {
+        // [ipv6] or [ipv6]:port
+        if rest.ends_with(']') {
+            format!("{}:{}", addr, default_port)
+        } else {
+            addr.to_string()
+        }
+    } else {
+        match addr.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') && port.parse::<u16>().is_ok() => {
+                addr.to_string()
+            }
+            Some(_) => format!("[{}]:{}", addr, default_port),
+            None => format!("{}:{}", addr, default_port),
+        }
+    }
+}
+
+/// Maps network errors to PrintError.
+pub(crate) fn net_error(addr: &str, e: io::Error) -> io::Error {
+    match e.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => {
+            PrintError::io_error(PrintError::Timeout)
+        }
+        _ => PrintError::io_error(PrintError::Network(format!("{}: {}", addr, e))),
+    }
+}
@@ -0,0 +1,109 @@
+//! Raw TCP backend. Port 9100, also known as JetDirect or AppSocket.
+//!
+//! The data is sent as is, there is no feedback from the printer.
+
+use std::io::{self, Write};
+use std::net::{Shutdown, TcpStream};
+use std::time::Duration;
+
+use crate::backend::{connect, net_error};
+use crate::{Backend, PrintError};
+
+/// Default port for raw printing.
+pub const DEFAULT_PORT: u16 = 9100;
+
+/// Printjob via a raw socket.
+#[derive(Debug)]
+pub struct SocketJob {
+    addr: String,
+    stream: Option<TcpStream>,
+}
+
+impl Write for SocketJob {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let stream = self.stream()?;
+        match stream.write(buf) {
+            Ok(n) => Ok(n),
+            Err(e) => Err(net_error(&self.addr, e)),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let stream = self.stream()?;
+        match stream.flush() {
+            Ok(()) => Ok(()),
+            Err(e) => Err(net_error(&self.addr, e)),
+        }
+    }
+}
+
+impl SocketJob {
+    /// Connect to the printer. The address is host or host:port,
+    /// the port defaults to 9100.
+    pub fn new(addr: &str) -> io::Result<Self> {
+        Self::new_with(addr, None, None)
+    }
+
+    /// Connect to the printer with timeouts for connect and write.
+    pub fn new_with(
+        addr: &str,
+        connect_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> io::Result<Self> {
+        let stream = connect(addr, DEFAULT_PORT, connect_timeout)?;
+        if let Err(e) = stream.set_write_timeout(write_timeout) {
+            return Err(net_error(addr, e));
+        }
+        Ok(Self {
+            addr: addr.to_string(),
+            stream: Some(stream),
+        })
+    }
+
+    fn stream(&mut self) -> io::Result<&mut TcpStream> {
+        match &mut self.stream {
+            Some(stream) => Ok(stream),
+            None => Err(PrintError::io_error(PrintError::Closed)),
+        }
+    }
+
+    /// Flush the data and close the connection.
+    pub fn close(&mut self) -> io::Result<()> {
+        if let Some(mut stream) = self.stream.take() {
+            if let Err(e) = stream.flush() {
+                return Err(net_error(&self.addr, e));
+            }
+            if let Err(e) = stream.shutdown(Shutdown::Write) {
+                return Err(net_error(&self.addr, e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the connection. Whatever has been sent until now can't be
+    /// recalled and will probably be printed.
+    pub fn abort(&mut self) -> io::Result<()> {
+        if let Some(stream) = self.stream.take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        Ok(())
+    }
+}
+
+impl Backend for SocketJob {
+    fn start_page(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        SocketJob::close(self)
+    }
+
+    fn abort(&mut self) -> io::Result<()> {
+        SocketJob::abort(self)
+    }
+}
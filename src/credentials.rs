@@ -0,0 +1,75 @@
+//!
+//! Credentials for printers and servers that require authentication.
+//!
+
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, RwLock};
+
+/// User and password for one server.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub user: String,
+    pub password: String,
+}
+
+impl Credentials {
+    pub fn new(user: &str, password: &str) -> Self {
+        Self {
+            user: user.to_string(),
+            password: password.to_string(),
+        }
+    }
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("user", &self.user)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// How often the credentials are sent for one request before giving up.
+/// The same for libcups and the IPP client.
+pub(crate) const PASSWORD_TRIES: u32 = 1;
+
+type Provider = Arc<dyn Fn(&str) -> Option<Credentials> + Send + Sync>;
+
+static PROVIDER: RwLock<Option<Provider>> = RwLock::new(None);
+
+/// Sets the function that supplies the credentials when a server asks
+/// for authentication. It gets the host name of the server and returns
+/// None if it has nothing for this server.
+///
+/// Without a provider, or if it returns None or the server rejects the
+/// credentials, the request fails with PrintError::AuthenticationRequired.
+/// The credentials are sent once per request, a wrong password is not
+/// tried again.
+///
+/// The IPP client has no TLS, so it answers Digest challenges but sends
+/// Basic, which has the password in cleartext, only to the local host.
+/// A remote server that offers only Basic fails with
+/// AuthenticationRequired. libcups decides this on its own, use
+/// Encryption::Required to keep the password off the wire there.
+///
+/// libcups calls the provider from its password callback, a panic there
+/// is caught and counts as None.
+pub fn set_credentials_provider(
+    provider: impl Fn(&str) -> Option<Credentials> + Send + Sync + 'static,
+) {
+    let mut v = PROVIDER.write().unwrap_or_else(|e| e.into_inner());
+    *v = Some(Arc::new(provider));
+}
+
+/// Removes the credentials provider.
+pub fn clear_credentials_provider() {
+    let mut v = PROVIDER.write().unwrap_or_else(|e| e.into_inner());
+    *v = None;
+}
+
+/// Asks the provider for the credentials of this host.
+pub(crate) fn credentials(host: &str) -> Option<Credentials> {
+    let provider = PROVIDER.read().unwrap_or_else(|e| e.into_inner()).clone();
+    provider.and_then(|v| v(host))
+}
@@ -2,15 +2,15 @@
 
 use std::io::Write;
 
-use crate::{Driver, JobParam, PrintJob};
+use crate::{Backend, Driver, Length, PrintJob};
 
 const STX: char = '\x02';
 const CR: char = '\x0D';
 
 /// Datamax driver.
 #[derive(Debug)]
-pub struct Datamax {
-    pub print: PrintJob,
+pub struct Datamax<B = PrintJob> {
+    pub print: B,
     metric: bool,
 }
 
@@ -162,35 +162,22 @@ impl TextSys {
     }
 }
 
-impl Driver for Datamax {
-    fn new(pr_name: &str, doc_name: &str) -> std::io::Result<Self> {
-        Ok(Self {
-            print: PrintJob::new(pr_name, doc_name)?,
-            metric: false,
-        })
-    }
+impl<B: Backend> Driver for Datamax<B> {
+    type Backend = B;
 
-    fn new_with(pr_name: &str, doc_name: &str, param: &JobParam) -> std::io::Result<Self> {
-        Ok(Self {
-            print: PrintJob::new_with(pr_name, doc_name, param)?,
+    fn with_backend(backend: B) -> Self {
+        Self {
+            print: backend,
             metric: false,
-        })
-    }
-
-    fn start_page(&mut self) -> std::io::Result<()> {
-        self.print.start_page()
-    }
-
-    fn end_page(&mut self) -> std::io::Result<()> {
-        self.print.end_page()
+        }
     }
 
-    fn close(&mut self) -> std::io::Result<()> {
-        self.print.close()
+    fn backend(&mut self) -> &mut B {
+        &mut self.print
     }
 }
 
-impl Write for Datamax {
+impl<B: Backend> Write for Datamax<B> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         self.print.write(buf)
     }
@@ -200,7 +187,7 @@ impl Write for Datamax {
     }
 }
 
-impl Datamax {
+impl<B: Backend> Datamax<B> {
     /// Label starten.
     pub fn start_label(&mut self) -> Result<(), std::io::Error> {
         write!(self.print, "{}", STX)?;
@@ -288,7 +275,8 @@ impl Datamax {
     }
 
     /// Horizontal offset
-    pub fn offset_x(&mut self, dist: u32) -> Result<(), std::io::Error> {
+    pub fn offset_x(&mut self, dist: Length) -> Result<(), std::io::Error> {
+        let dist = self.units(dist)?;
         write!(self.print, "C{:04}", dist)?;
         write!(self.print, "{}", CR)?;
         Ok(())
@@ -297,15 +285,17 @@ impl Datamax {
     /// Text output
     /// font (0..8)
     /// expand (1..24)
-    /// row (0..9999) in 0.01" or 0.1 mm if metric()
-    /// col (0.410) in 0.01" or 0.1 mm if metric()
+    /// row (0..9999) sent in 0.01" or 0.1 mm if metric()
+    /// col (0.410) sent in 0.01" or 0.1 mm if metric()
     pub fn text_sys(
         &mut self,
         param: TextSys,
-        row_pos: u16,
-        col_pos: u16,
+        row_pos: Length,
+        col_pos: Length,
         data: &str,
     ) -> Result<(), std::io::Error> {
+        let row_pos = self.units(row_pos)?;
+        let col_pos = self.units(col_pos)?;
         let data_enc = yore::code_pages::CP850.encode_lossy(data, b'_');
 
         write!(
@@ -332,8 +322,8 @@ impl Datamax {
     /// Text output, scaling font.
     /// expand (1..24)
     /// size (4..72pt)
-    /// row (0..9999) in 0.01" or 0.1 mm if metric()
-    /// col (0.410) in 0.01" or 0.1 mm if metric()
+    /// row (0..9999) sent in 0.01" or 0.1 mm if metric()
+    /// col (0.410) sent in 0.01" or 0.1 mm if metric()
     pub fn text_scale(
         &mut self,
         param: TextScale,
@@ -342,10 +332,12 @@ impl Datamax {
         // vert_expand: u8,
         // bold: bool,
         // size: ScaleSize,
-        row_pos: u16,
-        col_pos: u16,
+        row_pos: Length,
+        col_pos: Length,
         data: &str,
     ) -> Result<(), std::io::Error> {
+        let row_pos = self.units(row_pos)?;
+        let col_pos = self.units(col_pos)?;
         let data_enc = yore::code_pages::CP850.encode_lossy(data, b'_');
 
         write!(
@@ -370,6 +362,9 @@ impl Datamax {
         Ok(())
     }
 
+    /// Converts mm to the units of the printer, 0.1 mm if metric(),
+    /// 0.01" otherwise.
+    #[deprecated(note = "the coordinates take a Length now")]
     pub fn mm(&self, width: f32) -> u16 {
         if self.metric {
             (width * 10f32) as u16
@@ -377,6 +372,23 @@ impl Datamax {
             (width / 25.4f32 * 100f32) as u16
         }
     }
+
+    /// Coordinates in 0.1 mm if metric(), 0.01" otherwise.
+    /// DPL has 4 digits for them, anything outside 0..=9999 is an error.
+    fn units(&self, v: Length) -> Result<u16, std::io::Error> {
+        let units = if self.metric {
+            v.count(Length::mm(0.1))
+        } else {
+            v.count(Length::inch(0.01))
+        };
+        match units.and_then(|v| u16::try_from(v).ok()) {
+            Some(units) if units <= 9999 => Ok(units),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is out of range for the printer", v),
+            )),
+        }
+    }
 }
 
 fn feed_speed(speed: FeedSpeed) -> u8 {
@@ -0,0 +1,202 @@
+//!
+//! Printing a file or a reader in one call.
+//!
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+#[cfg(target_os = "linux")]
+use crate::Format;
+use crate::{JobParam, PrintJob};
+
+/// Size of the chunks read and written.
+const CHUNK: usize = 64 * 1024;
+
+/// Bytes checked for plain text.
+const TEXT_SAMPLE: usize = 1024;
+
+/// Document type from the first bytes of the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentType {
+    Pdf,
+    Postscript,
+    Jpeg,
+    Png,
+    /// PCL or a PJL job.
+    Pcl,
+    /// Datamax DPL.
+    Dpl,
+    /// Zebra ZPL.
+    Zpl,
+    Text,
+}
+
+impl DocumentType {
+    /// Detects the type from the magic bytes. None if unknown.
+    pub fn sniff(data: &[u8]) -> Option<DocumentType> {
+        if data.starts_with(b"%PDF-") {
+            Some(DocumentType::Pdf)
+        } else if data.starts_with(b"%!") || data.starts_with(b"\x04%!") {
+            Some(DocumentType::Postscript)
+        } else if data.starts_with(b"\xff\xd8\xff") {
+            Some(DocumentType::Jpeg)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(DocumentType::Png)
+        } else if data.starts_with(b"\x1bE") || data.starts_with(b"\x1b%-12345X") {
+            Some(DocumentType::Pcl)
+        } else if matches!(data, [1 | 2, c, ..] if c.is_ascii_alphabetic()) {
+            Some(DocumentType::Dpl)
+        } else if is_zpl(data) {
+            Some(DocumentType::Zpl)
+        } else if is_text(data) {
+            Some(DocumentType::Text)
+        } else {
+            None
+        }
+    }
+
+    /// MIME type. The printer languages are application/octet-stream.
+    pub fn mime(&self) -> &'static str {
+        match self {
+            DocumentType::Pdf => "application/pdf",
+            DocumentType::Postscript => "application/postscript",
+            DocumentType::Jpeg => "image/jpeg",
+            DocumentType::Png => "image/png",
+            DocumentType::Pcl => "application/vnd.hp-PCL",
+            DocumentType::Dpl | DocumentType::Zpl => "application/octet-stream",
+            DocumentType::Text => "text/plain",
+        }
+    }
+
+    /// The Format to send it with. Printer languages go raw.
+    #[cfg(target_os = "linux")]
+    pub fn format(&self) -> Format {
+        match self {
+            DocumentType::Pdf => Format::Pdf,
+            DocumentType::Postscript => Format::Postscript,
+            DocumentType::Jpeg => Format::Jpeg,
+            DocumentType::Png => Format::Png,
+            DocumentType::Pcl | DocumentType::Dpl | DocumentType::Zpl => Format::Raw,
+            DocumentType::Text => Format::Text,
+        }
+    }
+}
+
+/// ^XA starts a label, ~ the host commands like ~DG.
+fn is_zpl(data: &[u8]) -> bool {
+    let start = data.iter().position(|v| !v.is_ascii_whitespace());
+    let data = &data[start.unwrap_or(data.len())..];
+    data[..data.len().min(3)].eq_ignore_ascii_case(b"^XA")
+        || matches!(data, [b'~', a, b, ..] if a.is_ascii_uppercase() && b.is_ascii_uppercase())
+}
+
+/// No control characters except whitespace and form feed.
+fn is_text(data: &[u8]) -> bool {
+    !data.is_empty()
+        && data[..data.len().min(TEXT_SAMPLE)]
+            .iter()
+            .all(|v| matches!(v, b'\t' | b'\n' | b'\r' | b'\x0c' | 0x20..=0x7e | 0x80..))
+}
+
+/// Prints a file. See [print_reader].
+pub fn print_file(pr_name: &str, path: impl AsRef<Path>, param: &JobParam) -> io::Result<u32> {
+    print_file_progress(pr_name, path, param, |_, _| {})
+}
+
+/// Prints a file. The progress gets the bytes written so far and the
+/// file size.
+pub fn print_file_progress(
+    pr_name: &str,
+    path: impl AsRef<Path>,
+    param: &JobParam,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> io::Result<u32> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    let total = file.metadata()?.len();
+    let doc_name = path
+        .file_name()
+        .map(|v| v.to_string_lossy())
+        .unwrap_or_default();
+    print_reader_progress(pr_name, &doc_name, file, param, |written, _| {
+        progress(written, Some(total))
+    })
+}
+
+/// Prints everything from the reader as one job and returns the job-id.
+///
+/// The data is read in chunks of 64KB. With Format::Auto the format
+/// is detected from the first chunk. Any error aborts the job.
+///
+/// With libcups and on windows each chunk goes to the spooler right
+/// away. Without the `cups` feature the job is sent via IPP, which
+/// collects the whole document in memory and sends it on close.
+pub fn print_reader(
+    pr_name: &str,
+    doc_name: &str,
+    reader: impl Read,
+    param: &JobParam,
+) -> io::Result<u32> {
+    print_reader_progress(pr_name, doc_name, reader, param, |_, _| {})
+}
+
+/// Prints everything from the reader. The progress gets the bytes
+/// written so far, the total is not known.
+pub fn print_reader_progress(
+    pr_name: &str,
+    doc_name: &str,
+    mut reader: impl Read,
+    param: &JobParam,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> io::Result<u32> {
+    let mut buf = vec![0u8; CHUNK];
+    let mut len = fill(&mut reader, &mut buf)?;
+
+    let param = detect_format(param, &buf[..len]);
+    let mut job = PrintJob::new_with(pr_name, doc_name, &param)?;
+
+    let mut written = 0u64;
+    while len > 0 {
+        job.write_all(&buf[..len])?;
+        written += len as u64;
+        progress(written, None);
+        len = fill(&mut reader, &mut buf)?;
+    }
+    job.close()?;
+
+    Ok(job.job_id())
+}
+
+/// Replaces Format::Auto with the detected format. Unknown data stays
+/// with Auto and is left to the cupsd.
+#[cfg(target_os = "linux")]
+fn detect_format(param: &JobParam, data: &[u8]) -> JobParam {
+    let mut param = param.clone();
+    if param.data_format == Format::Auto {
+        if let Some(v) = DocumentType::sniff(data) {
+            param.data_format = v.format();
+        }
+    }
+    param
+}
+
+/// Windows has no auto format, the datatype is used as is.
+#[cfg(target_os = "windows")]
+fn detect_format(param: &JobParam, _data: &[u8]) -> JobParam {
+    param.clone()
+}
+
+/// Reads until the buffer is full or at the end.
+fn fill(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
@@ -0,0 +1,298 @@
+//!
+//! HTTP Basic and Digest authentication.
+//!
+
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::credentials::Credentials;
+
+/// Authorization header for the WWW-Authenticate challenges.
+/// Digest is preferred, Basic only if allowed. None if no challenge
+/// can be answered.
+pub(crate) fn authorization(
+    challenges: &[&str],
+    cred: &Credentials,
+    method: &str,
+    path: &str,
+    allow_basic: bool,
+) -> Option<String> {
+    let schemes = challenges
+        .iter()
+        .map(|v| match v.trim().split_once(' ') {
+            Some((scheme, params)) => (scheme, params),
+            None => (v.trim(), ""),
+        })
+        .collect::<Vec<_>>();
+
+    let digest = schemes
+        .iter()
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Digest"))
+        .find_map(|(_, params)| digest(&parse_params(params), cred, method, path));
+    if digest.is_some() {
+        return digest;
+    }
+    if allow_basic
+        && schemes
+            .iter()
+            .any(|(scheme, _)| scheme.eq_ignore_ascii_case("Basic"))
+    {
+        let userpass = format!("{}:{}", cred.user, cred.password);
+        return Some(format!("Basic {}", base64(userpass.as_bytes())));
+    }
+    None
+}
+
+/// Digest with MD5. Only qop=auth or no qop at all.
+fn digest(
+    params: &[(String, String)],
+    cred: &Credentials,
+    method: &str,
+    path: &str,
+) -> Option<String> {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    };
+
+    let realm = param("realm").unwrap_or_default();
+    let nonce = param("nonce")?;
+    match param("algorithm") {
+        None => {}
+        Some(v) if v.eq_ignore_ascii_case("MD5") => {}
+        Some(_) => return None,
+    }
+    let qop = match param("qop") {
+        None => None,
+        Some(v) if v.split(',').any(|v| v.trim() == "auth") => Some("auth"),
+        Some(_) => return None,
+    };
+
+    let ha1 = md5_hex(format!("{}:{}:{}", cred.user, realm, cred.password).as_bytes());
+    let ha2 = md5_hex(format!("{}:{}", method, path).as_bytes());
+
+    let mut header = format!(
+        "Digest username={}, realm={}, nonce={}, uri={}",
+        quote(&cred.user)?,
+        quote(realm)?,
+        quote(nonce)?,
+        quote(path)?
+    );
+    if let Some(qop) = qop {
+        let nc = "00000001";
+        let cnonce = cnonce();
+        let response = md5_hex(format!("{ha1}:{nonce}:{nc}:{cnonce}:{qop}:{ha2}").as_bytes());
+        _ = write!(
+            header,
+            ", qop={}, nc={}, cnonce=\"{}\", response=\"{}\"",
+            qop, nc, cnonce, response
+        );
+    } else {
+        let response = md5_hex(format!("{ha1}:{nonce}:{ha2}").as_bytes());
+        _ = write!(header, ", response=\"{}\"", response);
+    }
+    if param("algorithm").is_some() {
+        header.push_str(", algorithm=MD5");
+    }
+    if let Some(opaque) = param("opaque") {
+        _ = write!(header, ", opaque={}", quote(opaque)?);
+    }
+    Some(header)
+}
+
+/// Quoted string with \ and " escaped. None for control characters,
+/// they can't be quoted and would end the header.
+fn quote(v: &str) -> Option<String> {
+    if v.chars().any(|c| c.is_control()) {
+        return None;
+    }
+    let mut res = String::with_capacity(v.len() + 2);
+    res.push('"');
+    for c in v.chars() {
+        if c == '"' || c == '\\' {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res.push('"');
+    Some(res)
+}
+
+/// Splits name=value and name="value" pairs.
+fn parse_params(params: &str) -> Vec<(String, String)> {
+    let mut res = Vec::new();
+    let mut rest = params.trim();
+    while let Some((name, tail)) = rest.split_once('=') {
+        let name = name.trim().trim_start_matches(',').trim().to_string();
+        let tail = tail.trim_start();
+        let (value, tail) = if let Some(tail) = tail.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = tail.char_indices();
+            let mut end = tail.len();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some((_, c)) = chars.next() {
+                            value.push(c);
+                        }
+                    }
+                    '"' => {
+                        end = i + 1;
+                        break;
+                    }
+                    c => value.push(c),
+                }
+            }
+            (value, &tail[end..])
+        } else {
+            let end = tail.find(',').unwrap_or(tail.len());
+            (tail[..end].trim().to_string(), &tail[end..])
+        };
+        res.push((name, value));
+        rest = tail.trim_start().trim_start_matches(',');
+    }
+    res
+}
+
+/// Client nonce from the clock, only has to differ between requests.
+fn cnonce() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    md5_hex(format!("{}:{}", now.as_nanos(), std::process::id()).as_bytes())[..16].to_string()
+}
+
+fn base64(data: &[u8]) -> String {
+    const CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut res = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let v = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(CHARS[(v >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+fn md5_hex(data: &[u8]) -> String {
+    md5(data)
+        .iter()
+        .fold(String::with_capacity(32), |mut s, v| {
+            _ = write!(s, "{:02x}", v);
+            s
+        })
+}
+
+/// RFC 1321. Digest auth needs it, no point in a dependency for that.
+fn md5(data: &[u8]) -> [u8; 16] {
+    const S: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, //
+        5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, //
+        4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, //
+        6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    let k: [u32; 64] =
+        std::array::from_fn(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32);
+
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    let mut h: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in msg.chunks(64) {
+        let m: [u32; 16] = std::array::from_fn(|i| {
+            u32::from_le_bytes([
+                block[i * 4],
+                block[i * 4 + 1],
+                block[i * 4 + 2],
+                block[i * 4 + 3],
+            ])
+        });
+        let [mut a, mut b, mut c, mut d] = h;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let f = f.wrapping_add(a).wrapping_add(k[i]).wrapping_add(m[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(S[i]));
+        }
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+    }
+
+    let mut res = [0u8; 16];
+    for (i, v) in h.iter().enumerate() {
+        res[i * 4..i * 4 + 4].copy_from_slice(&v.to_le_bytes());
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{base64, md5_hex};
+
+    /// RFC 1321, A.5.
+    #[test]
+    fn test_md5() {
+        let suite = [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("a", "0cc175b9c0f1b6a831c399e269772661"),
+            ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+            ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+        for (data, hash) in suite {
+            assert_eq!(md5_hex(data.as_bytes()), hash, "{:?}", data);
+        }
+    }
+
+    /// RFC 4648, 10.
+    #[test]
+    fn test_base64() {
+        let suite = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (data, encoded) in suite {
+            assert_eq!(base64(data.as_bytes()), encoded, "{:?}", data);
+        }
+    }
+}
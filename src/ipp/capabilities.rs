@@ -0,0 +1,256 @@
+//!
+//! Maps the printer description attributes to Capabilities.
+//!
+
+use crate::ipp::{Attribute, Value, RESOLUTION_DPCM};
+#[cfg(target_os = "linux")]
+use crate::linux::options::{FINISHINGS, MEDIA, PRINT_COLOR_MODE, PRINT_QUALITY, SIDES};
+use crate::media;
+use crate::{Capabilities, ColorMode, Duplex, Length, Margins, Media, PaperSize, Quality};
+
+/// Printer attributes needed for Capabilities.
+pub(crate) const CAPABILITIES_ATTRIBUTES: &[&str] = &[
+    "media-supported",
+    "media-col-database",
+    "media-bottom-margin-supported",
+    "media-left-margin-supported",
+    "media-right-margin-supported",
+    "media-top-margin-supported",
+    "sides-supported",
+    "print-color-mode-supported",
+    "print-quality-supported",
+    "printer-resolution-supported",
+    "finishings-supported",
+    "document-format-supported",
+    "copies-supported",
+];
+
+/// Capabilities from the printer attributes.
+pub(crate) fn capabilities(attr: &[Attribute]) -> Capabilities {
+    let find = |name: &str| {
+        attr.iter()
+            .find(|v| v.name == name)
+            .map(|v| v.values.as_slice())
+            .unwrap_or_default()
+    };
+    let keywords = |name: &str| find(name).iter().filter_map(|v| v.as_str());
+    let numbers = |name: &str| find(name).iter().filter_map(|v| v.as_i32());
+
+    let mut caps = Capabilities::default();
+
+    let database = find("media-col-database")
+        .iter()
+        .filter_map(|v| v.as_collection())
+        .collect::<Vec<_>>();
+    let min_margin = |name: &str| numbers(name).min().map(hundredth_mm);
+    let margins = match (
+        min_margin("media-bottom-margin-supported"),
+        min_margin("media-left-margin-supported"),
+        min_margin("media-right-margin-supported"),
+        min_margin("media-top-margin-supported"),
+    ) {
+        (None, None, None, None) => None,
+        (bottom, left, right, top) => Some(Margins {
+            bottom: bottom.unwrap_or_default(),
+            left: left.unwrap_or_default(),
+            right: right.unwrap_or_default(),
+            top: top.unwrap_or_default(),
+        }),
+    };
+    let mut custom_min = None;
+    let mut custom_max = None;
+    for name in keywords("media-supported") {
+        if name.starts_with("custom_min_") {
+            custom_min = pwg_dimensions(name);
+            continue;
+        } else if name.starts_with("custom_max_") {
+            custom_max = pwg_dimensions(name);
+            continue;
+        }
+        let col = database
+            .iter()
+            .find(|col| col_str(col, "media-size-name") == Some(name));
+        let (width, length) = match col.and_then(|col| col_size(col)) {
+            Some(v) => v,
+            None => pwg_dimensions(name)
+                .or_else(|| media::by_alias(name).map(|v| (v.width, v.length)))
+                .unwrap_or_default(),
+        };
+        caps.media.push(Media {
+            name: name.to_string(),
+            paper_size: paper_size(name),
+            width,
+            length,
+            margins: col.and_then(|col| col_margins(col)).or(margins),
+        });
+    }
+
+    if let (Some(min), Some(max)) = (custom_min, custom_max) {
+        caps.custom_size = Some((min, max));
+    }
+
+    caps.sides = keywords("sides-supported").filter_map(sides).collect();
+    caps.color_modes = keywords("print-color-mode-supported")
+        .filter_map(color_mode)
+        .collect();
+    caps.qualities = numbers("print-quality-supported")
+        .filter_map(quality)
+        .collect();
+    caps.resolutions = find("printer-resolution-supported")
+        .iter()
+        .filter_map(|v| match v {
+            Value::Resolution { x, y, units } if *units == RESOLUTION_DPCM => {
+                Some((dpcm_to_dpi(*x), dpcm_to_dpi(*y)))
+            }
+            Value::Resolution { x, y, .. } => Some(((*x).max(0) as u32, (*y).max(0) as u32)),
+            _ => None,
+        })
+        .collect();
+    #[cfg(target_os = "linux")]
+    {
+        caps.finishings = find("finishings-supported")
+            .iter()
+            .filter_map(|v| FINISHINGS.parse_value(v))
+            .collect();
+    }
+    caps.document_formats = keywords("document-format-supported")
+        .map(|v| v.to_string())
+        .collect();
+    caps.copies = find("copies-supported").iter().find_map(|v| match v {
+        Value::Range(lower, upper) => Some(((*lower).max(0) as u32, (*upper).max(0) as u32)),
+        _ => None,
+    });
+
+    caps
+}
+
+/// Values from the network can be anything, so no overflow here.
+fn dpcm_to_dpi(v: i32) -> u32 {
+    ((v.max(0) as u64 * 254 + 50) / 100).min(u32::MAX as u64) as u32
+}
+
+fn col_str<'a>(col: &'a [Attribute], name: &str) -> Option<&'a str> {
+    col.iter()
+        .find(|v| v.name == name)
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_str())
+}
+
+/// IPP dimensions are in 1/100mm.
+fn hundredth_mm(v: i32) -> Length {
+    Length::mm(0.01) * v.max(0) as i64
+}
+
+fn col_num(col: &[Attribute], name: &str) -> Option<Length> {
+    col.iter()
+        .find(|v| v.name == name)
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_i32())
+        .map(hundredth_mm)
+}
+
+/// x-dimension and y-dimension of the media-size.
+fn col_size(col: &[Attribute]) -> Option<(Length, Length)> {
+    let size = col
+        .iter()
+        .find(|v| v.name == "media-size")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_collection())?;
+    Some((col_num(size, "x-dimension")?, col_num(size, "y-dimension")?))
+}
+
+fn col_margins(col: &[Attribute]) -> Option<Margins> {
+    Some(Margins {
+        bottom: col_num(col, "media-bottom-margin")?,
+        left: col_num(col, "media-left-margin")?,
+        right: col_num(col, "media-right-margin")?,
+        top: col_num(col, "media-top-margin")?,
+    })
+}
+
+/// Width and length from a PWG self describing media name,
+/// eg. iso_a4_210x297mm or na_letter_8.5x11in.
+pub(crate) fn pwg_dimensions(name: &str) -> Option<(Length, Length)> {
+    let size = name.rsplit('_').next()?;
+    let (size, unit): (_, fn(f64) -> Length) = if let Some(v) = size.strip_suffix("mm") {
+        (v, Length::mm)
+    } else if let Some(v) = size.strip_suffix("in") {
+        (v, Length::inch)
+    } else {
+        return None;
+    };
+    let (width, length) = size.split_once('x')?;
+    let width = width.parse::<f64>().ok()?;
+    let length = length.parse::<f64>().ok()?;
+    Some((unit(width), unit(length)))
+}
+
+/// PWG name for a custom size, eg. custom_100x50.8mm.
+pub(crate) fn pwg_custom_name(width: Length, height: Length) -> String {
+    fn mm(v: Length) -> String {
+        let v = v.count(Length::mm(0.01)).unwrap_or_default().max(0);
+        let s = format!("{}.{:02}", v / 100, v % 100);
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+    format!("custom_{}x{}mm", mm(width), mm(height))
+}
+
+#[cfg(target_os = "linux")]
+fn paper_size(name: &str) -> Option<PaperSize> {
+    Some(MEDIA.parse(name))
+}
+
+#[cfg(target_os = "linux")]
+fn sides(v: &str) -> Option<Duplex> {
+    Some(SIDES.parse(v))
+}
+
+#[cfg(target_os = "linux")]
+fn color_mode(v: &str) -> Option<ColorMode> {
+    Some(PRINT_COLOR_MODE.parse(v))
+}
+
+#[cfg(target_os = "linux")]
+fn quality(v: i32) -> Option<Quality> {
+    Some(PRINT_QUALITY.parse(&v.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn paper_size(name: &str) -> Option<PaperSize> {
+    match media::by_name(name) {
+        Some(media) => Some(PaperSize::from(media)),
+        None => name
+            .strip_prefix("custom_")
+            .and_then(pwg_dimensions)
+            .map(|(width, height)| PaperSize::Custom { width, height }),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn sides(v: &str) -> Option<Duplex> {
+    match v {
+        "one-sided" => Some(Duplex::Simplex),
+        "two-sided-long-edge" => Some(Duplex::TwoSidedPortrait),
+        "two-sided-short-edge" => Some(Duplex::TwoSidedLandscape),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn color_mode(v: &str) -> Option<ColorMode> {
+    match v {
+        "monochrome" => Some(ColorMode::Monochrome),
+        "color" => Some(ColorMode::Color),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn quality(v: i32) -> Option<Quality> {
+    match v {
+        3 => Some(Quality::Draft),
+        4 => Some(Quality::Normal),
+        5 => Some(Quality::High),
+        _ => None,
+    }
+}
@@ -0,0 +1,611 @@
+//!
+//! IPP client.
+//!
+
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::credentials::{credentials, PASSWORD_TRIES};
+use crate::ipp::auth::authorization;
+use crate::ipp::capabilities::{capabilities, CAPABILITIES_ATTRIBUTES};
+use crate::ipp::http::{post, Response, Uri};
+use crate::ipp::{Attribute, GroupTag, Message, Operation, StatusCode, Value};
+use crate::{
+    Capabilities, Conflict, JobInfo, JobState, JobStatus, PrintError, PrinterState, PrinterStatus,
+    StateReason, WhichJobs,
+};
+
+static REQUEST_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Talks to one IPP printer, or to one queue of a cupsd.
+///
+/// Only plain HTTP is supported, ipps:// uris are rejected.
+/// Each request uses a new connection.
+#[derive(Debug, Clone)]
+pub struct IppClient {
+    uri: Uri,
+    printer_uri: String,
+    timeout: Option<Duration>,
+    user: String,
+}
+
+impl IppClient {
+    /// Client for the printer at an ipp:// or http:// uri.
+    ///
+    /// For cupsd this is ipp://host:631/printers/queue.
+    pub fn new(uri: &str) -> io::Result<Self> {
+        let uri = Uri::parse(uri)?;
+        let printer_uri = uri.ipp_uri();
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "uniprint".to_string());
+
+        Ok(Self {
+            uri,
+            printer_uri,
+            timeout: Some(Duration::from_secs(30)),
+            user,
+        })
+    }
+
+    /// The printer-uri.
+    pub fn uri(&self) -> &str {
+        &self.printer_uri
+    }
+
+    /// Timeout for connect, read and write. Defaults to 30s.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// The requesting-user-name. Defaults to $USER.
+    pub fn set_user(&mut self, user: &str) {
+        self.user = user.to_string();
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// New request with printer-uri and requesting-user-name.
+    pub fn request(&self, operation: Operation) -> Message {
+        let mut req = Message::request(operation, REQUEST_ID.fetch_add(1, Ordering::Relaxed));
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("printer-uri", Value::Uri(self.printer_uri.clone())),
+        );
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("requesting-user-name", Value::Name(self.user.clone())),
+        );
+        req
+    }
+
+    /// Sends the request followed by the document data.
+    /// Returns the response whatever its status.
+    pub fn send_raw(&self, req: &Message, data: &[u8]) -> io::Result<Message> {
+        let mut body = req.encode()?;
+        body.extend_from_slice(data);
+
+        let mut res = post(&self.uri, self.timeout, "application/ipp", &[], &body)?;
+        for _ in 0..PASSWORD_TRIES {
+            if res.status != 401 {
+                break;
+            }
+            match self.authenticate(&res, &body)? {
+                Some(v) => res = v,
+                None => break,
+            }
+        }
+        match res.status {
+            200 => {}
+            401 => return Err(PrintError::io_error(PrintError::AuthenticationRequired)),
+            404 => return Err(PrintError::io_error(PrintError::NotFound)),
+            _ => {
+                return Err(PrintError::io_error(PrintError::Network(format!(
+                    "{}: HTTP {} {}",
+                    self.uri.authority(),
+                    res.status,
+                    res.reason
+                ))))
+            }
+        }
+
+        let (msg, _) = Message::decode(&res.body)?;
+        Ok(msg)
+    }
+
+    /// Answers the challenge of a 401 with the credentials from the
+    /// provider and sends the request once more. None if there are no
+    /// credentials or no usable challenge.
+    ///
+    /// Basic would send the password in cleartext, it's only used for
+    /// the local host.
+    fn authenticate(&self, res: &Response, body: &[u8]) -> io::Result<Option<Response>> {
+        let Some(cred) = credentials(&self.uri.host) else {
+            return Ok(None);
+        };
+        let challenges = res
+            .headers
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case("WWW-Authenticate"))
+            .map(|(_, v)| v.as_str())
+            .collect::<Vec<_>>();
+        let header = authorization(
+            &challenges,
+            &cred,
+            "POST",
+            &self.uri.path,
+            self.uri.is_loopback(),
+        );
+        match header {
+            Some(header) => post(
+                &self.uri,
+                self.timeout,
+                "application/ipp",
+                &[("Authorization", header)],
+                body,
+            )
+            .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Sends the request followed by the document data.
+    /// Fails if the response status is not successful.
+    pub fn send(&self, req: &Message, data: &[u8]) -> io::Result<Message> {
+        let res = self.send_raw(req, data)?;
+        if res.status().is_success() {
+            Ok(res)
+        } else {
+            Err(status_error(&res))
+        }
+    }
+
+    /// Get-Printer-Attributes. Returns the printer group.
+    ///
+    /// An empty list requests the default set of the printer.
+    pub fn get_printer_attributes(&self, requested: &[&str]) -> io::Result<Vec<Attribute>> {
+        let mut req = self.request(Operation::GetPrinterAttributes);
+        if !requested.is_empty() {
+            req.add(GroupTag::Operation, requested_attributes(requested));
+        }
+        let mut res = self.send(&req, &[])?;
+
+        let idx = res.groups.iter().position(|v| v.tag == GroupTag::Printer);
+        Ok(match idx {
+            Some(idx) => res.groups.swap_remove(idx).attributes,
+            None => Vec::new(),
+        })
+    }
+
+    /// Get-Printer-Attributes with the attributes for PrinterStatus.
+    pub fn printer_status(&self) -> io::Result<PrinterStatus> {
+        let attr = self.get_printer_attributes(PRINTER_STATUS_ATTRIBUTES)?;
+        Ok(printer_status(&attr))
+    }
+
+    /// Get-Printer-Attributes with the supported values.
+    pub fn capabilities(&self) -> io::Result<Capabilities> {
+        let attr = self.get_printer_attributes(CAPABILITIES_ATTRIBUTES)?;
+        Ok(capabilities(&attr))
+    }
+
+    /// Print-Job. Sends the complete document in one request.
+    /// Returns the job-id.
+    pub fn print_job(
+        &self,
+        job_name: &str,
+        format: &str,
+        job_attrs: &[Attribute],
+        data: &[u8],
+    ) -> io::Result<i32> {
+        let mut req = self.request(Operation::PrintJob);
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("job-name", Value::Name(job_name.to_string())),
+        );
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("document-format", Value::MimeMediaType(format.to_string())),
+        );
+        for attr in job_attrs {
+            req.add(GroupTag::Job, attr.clone());
+        }
+        let res = self.send(&req, data)?;
+        job_id(&res)
+    }
+
+    /// Validate-Job. Returns the unsupported or conflicting attributes,
+    /// empty if the printer would take the job as is.
+    pub fn validate_job(
+        &self,
+        job_name: &str,
+        format: &str,
+        job_attrs: &[Attribute],
+    ) -> io::Result<Vec<Conflict>> {
+        let mut req = self.request(Operation::ValidateJob);
+        validate_job_request(&mut req, job_name, format, job_attrs);
+        match self.send(&req, &[]) {
+            Ok(res) => Ok(conflicts(&res)),
+            Err(e) => invalid_param(e),
+        }
+    }
+
+    /// Create-Job. Documents follow with send_document.
+    /// Returns the job-id.
+    pub fn create_job(&self, job_name: &str, job_attrs: &[Attribute]) -> io::Result<i32> {
+        let mut req = self.request(Operation::CreateJob);
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("job-name", Value::Name(job_name.to_string())),
+        );
+        for attr in job_attrs {
+            req.add(GroupTag::Job, attr.clone());
+        }
+        let res = self.send(&req, &[])?;
+        job_id(&res)
+    }
+
+    /// Send-Document for a job from create_job.
+    pub fn send_document(
+        &self,
+        job_id: i32,
+        doc_name: &str,
+        format: &str,
+        last_document: bool,
+        data: &[u8],
+    ) -> io::Result<()> {
+        let mut req = self.request(Operation::SendDocument);
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("job-id", Value::Integer(job_id)),
+        );
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("document-name", Value::Name(doc_name.to_string())),
+        );
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("document-format", Value::MimeMediaType(format.to_string())),
+        );
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("last-document", Value::Boolean(last_document)),
+        );
+        self.send(&req, data)?;
+        Ok(())
+    }
+
+    /// Get-Job-Attributes. Returns the job group.
+    ///
+    /// An empty list requests the default set of the printer.
+    pub fn get_job_attributes(
+        &self,
+        job_id: i32,
+        requested: &[&str],
+    ) -> io::Result<Vec<Attribute>> {
+        let mut req = self.request(Operation::GetJobAttributes);
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("job-id", Value::Integer(job_id)),
+        );
+        if !requested.is_empty() {
+            req.add(GroupTag::Operation, requested_attributes(requested));
+        }
+        let mut res = self.send(&req, &[])?;
+
+        let idx = res.groups.iter().position(|v| v.tag == GroupTag::Job);
+        Ok(match idx {
+            Some(idx) => res.groups.swap_remove(idx).attributes,
+            None => Vec::new(),
+        })
+    }
+
+    /// Status of a job.
+    pub fn job_status(&self, job_id: i32) -> io::Result<JobStatus> {
+        let attr = self.get_job_attributes(job_id, JOB_STATUS_ATTRIBUTES)?;
+        Ok(job_status(job_id, &attr))
+    }
+
+    /// Get-Jobs.
+    pub fn get_jobs(&self, which: WhichJobs) -> io::Result<Vec<JobInfo>> {
+        let mut req = self.request(Operation::GetJobs);
+        get_jobs_request(&mut req, which);
+        let res = self.send(&req, &[])?;
+        Ok(job_infos(&res))
+    }
+
+    /// Cancel-Job.
+    pub fn cancel_job(&self, job_id: i32) -> io::Result<()> {
+        self.job_operation(Operation::CancelJob, job_id)
+    }
+
+    /// Cancel-Jobs. Cancels all jobs of the printer, this usually needs
+    /// an operator.
+    pub fn cancel_jobs(&self) -> io::Result<()> {
+        self.send(&self.request(Operation::CancelJobs), &[])?;
+        Ok(())
+    }
+
+    /// Hold-Job.
+    pub fn hold_job(&self, job_id: i32) -> io::Result<()> {
+        self.job_operation(Operation::HoldJob, job_id)
+    }
+
+    /// Release-Job.
+    pub fn release_job(&self, job_id: i32) -> io::Result<()> {
+        self.job_operation(Operation::ReleaseJob, job_id)
+    }
+
+    fn job_operation(&self, operation: Operation, job_id: i32) -> io::Result<()> {
+        let mut req = self.request(operation);
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("job-id", Value::Integer(job_id)),
+        );
+        self.send(&req, &[])?;
+        Ok(())
+    }
+}
+
+/// requested-attributes for the list of names.
+pub(crate) fn requested_attributes(requested: &[&str]) -> Attribute {
+    Attribute::with_values(
+        "requested-attributes",
+        requested
+            .iter()
+            .map(|v| Value::Keyword(v.to_string()))
+            .collect(),
+    )
+}
+
+/// Operation and job attributes for Validate-Job.
+pub(crate) fn validate_job_request(
+    req: &mut Message,
+    job_name: &str,
+    format: &str,
+    job_attrs: &[Attribute],
+) {
+    req.add(
+        GroupTag::Operation,
+        Attribute::new("job-name", Value::Name(job_name.to_string())),
+    );
+    req.add(
+        GroupTag::Operation,
+        Attribute::new("document-format", Value::MimeMediaType(format.to_string())),
+    );
+    for attr in job_attrs {
+        req.add(GroupTag::Job, attr.clone());
+    }
+}
+
+/// The attributes of the unsupported group.
+pub(crate) fn conflicts(res: &Message) -> Vec<Conflict> {
+    res.groups(GroupTag::Unsupported)
+        .flat_map(|v| v.attributes.iter())
+        .map(|v| Conflict {
+            name: v.name.clone(),
+            value: v
+                .values
+                .iter()
+                .map(|v| match (v.as_str(), v.as_i32()) {
+                    (Some(v), _) => v.to_string(),
+                    (_, Some(v)) => v.to_string(),
+                    _ => format!("{:?}", v),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+        })
+        .collect()
+}
+
+/// The conflicts of a PrintError::InvalidParam, other errors stay errors.
+pub(crate) fn invalid_param(e: io::Error) -> io::Result<Vec<Conflict>> {
+    match e.get_ref().and_then(|v| v.downcast_ref::<PrintError>()) {
+        Some(PrintError::InvalidParam(v)) => Ok(v.clone()),
+        _ => Err(e),
+    }
+}
+
+/// Printer attributes needed for PrinterStatus.
+pub(crate) const PRINTER_STATUS_ATTRIBUTES: &[&str] = &[
+    "printer-state",
+    "printer-state-reasons",
+    "printer-state-message",
+    "printer-state-change-time",
+    "printer-is-accepting-jobs",
+];
+
+/// PrinterStatus from the printer attributes.
+pub(crate) fn printer_status(attr: &[Attribute]) -> PrinterStatus {
+    let find = |name: &str| attr.iter().find(|v| v.name == name);
+
+    let state = match find("printer-state")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_i32())
+    {
+        Some(4) => PrinterState::Printing,
+        Some(5) => PrinterState::Stopped,
+        _ => PrinterState::Idle,
+    };
+    let reasons = find("printer-state-reasons")
+        .map(|v| {
+            v.values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .flat_map(StateReason::parse_list)
+                .collect()
+        })
+        .unwrap_or_default();
+    let accepting_jobs = find("printer-is-accepting-jobs")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    // same as time-at-creation
+    let changed = match find("printer-state-change-time")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_i32())
+    {
+        Some(v) if v > 0 => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(v as u64)),
+        _ => None,
+    };
+    let message = find("printer-state-message")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    PrinterStatus {
+        state,
+        reasons,
+        accepting_jobs,
+        changed,
+        message,
+    }
+}
+
+/// Job attributes needed for JobStatus.
+pub(crate) const JOB_STATUS_ATTRIBUTES: &[&str] = &[
+    "job-id",
+    "job-state",
+    "job-state-reasons",
+    "job-impressions-completed",
+];
+
+/// JobStatus from the job attributes.
+pub(crate) fn job_status(job_id: i32, attr: &[Attribute]) -> JobStatus {
+    let find = |name: &str| attr.iter().find(|v| v.name == name);
+
+    let state = job_state(find("job-state"));
+    let reasons = find("job-state-reasons")
+        .map(|v| {
+            v.values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .filter(|v| *v != "none")
+                .map(|v| v.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    let impressions_completed = find("job-impressions-completed")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_i32())
+        .unwrap_or_default();
+
+    JobStatus {
+        job_id: job_id as u32,
+        state,
+        reasons,
+        impressions_completed: impressions_completed.max(0) as u32,
+    }
+}
+
+/// JobState from the job-state.
+fn job_state(attr: Option<&Attribute>) -> JobState {
+    match attr.and_then(|v| v.value()).and_then(|v| v.as_i32()) {
+        Some(4) => JobState::Held,
+        Some(5) => JobState::Processing,
+        Some(6) => JobState::Stopped,
+        Some(7) => JobState::Canceled,
+        Some(8) => JobState::Aborted,
+        Some(9) => JobState::Completed,
+        _ => JobState::Pending,
+    }
+}
+
+/// Adds which-jobs and the attributes for JobInfo to a Get-Jobs.
+pub(crate) fn get_jobs_request(req: &mut Message, which: WhichJobs) {
+    let which = match which {
+        WhichJobs::Active => "not-completed",
+        WhichJobs::Completed => "completed",
+        WhichJobs::All => "all",
+    };
+    req.add(
+        GroupTag::Operation,
+        Attribute::new("which-jobs", Value::Keyword(which.to_string())),
+    );
+    req.add(
+        GroupTag::Operation,
+        requested_attributes(&[
+            "job-id",
+            "job-name",
+            "job-originating-user-name",
+            "job-k-octets",
+            "job-state",
+            "time-at-creation",
+        ]),
+    );
+}
+
+/// JobInfo for each job group of a Get-Jobs response.
+pub(crate) fn job_infos(res: &Message) -> Vec<JobInfo> {
+    res.groups(GroupTag::Job)
+        .map(|group| {
+            let str_attr = |name: &str| {
+                group
+                    .attribute(name)
+                    .and_then(|v| v.value())
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let num_attr = |name: &str| {
+                group
+                    .attribute(name)
+                    .and_then(|v| v.value())
+                    .and_then(|v| v.as_i32())
+                    .unwrap_or_default()
+                    .max(0) as u32
+            };
+
+            // cupsd sends seconds since the epoch, printers often their uptime.
+            let created = match num_attr("time-at-creation") {
+                0 => None,
+                v => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(v as u64)),
+            };
+
+            JobInfo {
+                job_id: num_attr("job-id"),
+                name: str_attr("job-name"),
+                user: str_attr("job-originating-user-name"),
+                size: num_attr("job-k-octets") as u64 * 1024,
+                state: job_state(group.attribute("job-state")),
+                created,
+            }
+        })
+        .collect()
+}
+
+fn job_id(res: &Message) -> io::Result<i32> {
+    res.attribute(GroupTag::Job, "job-id")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_i32())
+        .ok_or_else(|| PrintError::io_error(PrintError::Protocol("No job-id.".to_string())))
+}
+
+/// Maps an unsuccessful status to PrintError.
+pub(crate) fn status_error(res: &Message) -> io::Error {
+    let status = res.status();
+    if status == StatusCode::CLIENT_ERROR_NOT_FOUND {
+        return PrintError::io_error(PrintError::NotFound);
+    }
+    if status == StatusCode::CLIENT_ERROR_NOT_AUTHENTICATED {
+        return PrintError::io_error(PrintError::AuthenticationRequired);
+    }
+    if status == StatusCode::CLIENT_ERROR_ATTRIBUTES_OR_VALUES_NOT_SUPPORTED
+        || status == StatusCode::CLIENT_ERROR_CONFLICTING_ATTRIBUTES
+        || status == StatusCode::CLIENT_ERROR_DOCUMENT_FORMAT_NOT_SUPPORTED
+    {
+        let conflicts = conflicts(res);
+        if !conflicts.is_empty() {
+            return PrintError::io_error(PrintError::InvalidParam(conflicts));
+        }
+    }
+    let msg = match res.status_message() {
+        "" => format!("IPP status 0x{:04x}", status.0),
+        v => format!("{} (IPP status 0x{:04x})", v, status.0),
+    };
+    PrintError::io_error(PrintError::Print(msg))
+}
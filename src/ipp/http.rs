@@ -0,0 +1,284 @@
+//!
+//! Just enough HTTP/1.1 to POST an IPP request.
+//!
+
+use std::fmt::{Display, Formatter, Write as _};
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::backend::{connect, net_error};
+use crate::ipp::protocol_error;
+use crate::PrintError;
+
+/// Parsed ipp:// or http:// uri.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Uri {
+    pub(crate) scheme: String,
+    /// Host without brackets.
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) path: String,
+}
+
+impl Uri {
+    /// Uri from the parts. The host may have brackets.
+    pub(crate) fn new(scheme: &str, host: &str, port: u16, path: &str) -> Uri {
+        let host = host
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .unwrap_or(host);
+        Uri {
+            scheme: scheme.to_string(),
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        }
+    }
+
+    pub(crate) fn parse(uri: &str) -> io::Result<Uri> {
+        let invalid = || PrintError::io_error(PrintError::Network(format!("{}: invalid uri", uri)));
+
+        let (scheme, rest) = uri.split_once("://").ok_or_else(invalid)?;
+        let scheme = scheme.to_ascii_lowercase();
+        let default_port = match scheme.as_str() {
+            "ipp" => 631,
+            "http" => 80,
+            "ipps" | "https" => {
+                return Err(PrintError::io_error(PrintError::Network(format!(
+                    "{}: TLS is not supported",
+                    uri
+                ))))
+            }
+            _ => return Err(invalid()),
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, "/"),
+        };
+        // drop any userinfo
+        let authority = match authority.rsplit_once('@') {
+            Some((_, v)) => v,
+            None => authority,
+        };
+
+        let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+            let (host, rest) = rest.split_once(']').ok_or_else(invalid)?;
+            match rest.strip_prefix(':') {
+                Some(port) => (host, port.parse::<u16>().map_err(|_| invalid())?),
+                None if rest.is_empty() => (host, default_port),
+                None => return Err(invalid()),
+            }
+        } else {
+            match authority.split_once(':') {
+                Some((host, port)) => (host, port.parse::<u16>().map_err(|_| invalid())?),
+                None => (authority, default_port),
+            }
+        };
+        if host.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Uri {
+            scheme,
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// host:port, with brackets for IPv6.
+    pub(crate) fn authority(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    /// The host is this machine.
+    pub(crate) fn is_loopback(&self) -> bool {
+        self.host.eq_ignore_ascii_case("localhost")
+            || self.host.parse::<IpAddr>().is_ok_and(|v| v.is_loopback())
+    }
+
+    /// The same location as ipp:// uri.
+    pub(crate) fn ipp_uri(&self) -> String {
+        format!("ipp://{}{}", self.authority(), self.path)
+    }
+}
+
+impl Display for Uri {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}{}", self.scheme, self.authority(), self.path)
+    }
+}
+
+/// Path of a cups queue, /printers/name with the name percent-encoded.
+pub(crate) fn printer_path(pr_name: &str) -> String {
+    let mut path = "/printers/".to_string();
+    for b in pr_name.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            path.push(b as char);
+        } else {
+            _ = write!(path, "%{:02X}", b);
+        }
+    }
+    path
+}
+
+/// HTTP response.
+#[derive(Debug)]
+pub(crate) struct Response {
+    pub(crate) status: u16,
+    pub(crate) reason: String,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Response {
+    /// Header value. Names are compared case-insensitive.
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Sends a POST request with the body and reads the complete response.
+/// Uses one connection per request.
+pub(crate) fn post(
+    uri: &Uri,
+    timeout: Option<Duration>,
+    content_type: &str,
+    headers: &[(&str, String)],
+    body: &[u8],
+) -> io::Result<Response> {
+    let addr = uri.authority();
+    let stream = connect(&addr, uri.port, timeout)?;
+    stream.set_read_timeout(timeout)?;
+    stream.set_write_timeout(timeout)?;
+
+    let mut req = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\nUser-Agent: uniprint/{}\r\n",
+        uri.path,
+        addr,
+        content_type,
+        body.len(),
+        env!("CARGO_PKG_VERSION")
+    );
+    for (name, value) in headers {
+        req.push_str(name);
+        req.push_str(": ");
+        req.push_str(value);
+        req.push_str("\r\n");
+    }
+    req.push_str("\r\n");
+
+    let mut wr = &stream;
+    wr.write_all(req.as_bytes())
+        .and_then(|_| wr.write_all(body))
+        .and_then(|_| wr.flush())
+        .map_err(|e| net_error(&addr, e))?;
+
+    read_response(&mut BufReader::new(&stream)).map_err(|e| match e.get_ref() {
+        Some(v) if v.is::<PrintError>() => e,
+        _ => net_error(&addr, e),
+    })
+}
+
+fn read_response(rd: &mut impl BufRead) -> io::Result<Response> {
+    loop {
+        let line = read_line(rd)?;
+        let mut parts = line.splitn(3, ' ');
+        let version = parts.next().unwrap_or_default();
+        let status = parts.next().and_then(|v| v.parse::<u16>().ok());
+        let reason = parts.next().unwrap_or_default().to_string();
+        let status = match status {
+            Some(v) if version.starts_with("HTTP/") => v,
+            _ => return Err(protocol_error("Invalid HTTP status line.")),
+        };
+
+        let mut headers = Vec::new();
+        loop {
+            let line = read_line(rd)?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((k, v)) = line.split_once(':') {
+                headers.push((k.trim().to_string(), v.trim().to_string()));
+            }
+        }
+
+        // skip 100 Continue and friends
+        if status < 200 {
+            continue;
+        }
+
+        let mut res = Response {
+            status,
+            reason,
+            headers,
+            body: Vec::new(),
+        };
+
+        if res
+            .header("Transfer-Encoding")
+            .map(|v| v.eq_ignore_ascii_case("chunked"))
+            .unwrap_or_default()
+        {
+            read_chunked(rd, &mut res.body)?;
+        } else if let Some(len) = res.header("Content-Length") {
+            let len = len
+                .parse::<usize>()
+                .map_err(|_| protocol_error("Invalid Content-Length."))?;
+            read_exactly(rd, len, &mut res.body)?;
+        } else {
+            rd.read_to_end(&mut res.body)?;
+        }
+
+        return Ok(res);
+    }
+}
+
+fn read_chunked(rd: &mut impl BufRead, body: &mut Vec<u8>) -> io::Result<()> {
+    loop {
+        let line = read_line(rd)?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size =
+            usize::from_str_radix(size, 16).map_err(|_| protocol_error("Invalid chunk size."))?;
+        if size == 0 {
+            // trailers
+            while !read_line(rd)?.is_empty() {}
+            return Ok(());
+        }
+        read_exactly(rd, size, body)?;
+        read_line(rd)?;
+    }
+}
+
+/// Reads len bytes. The buffer grows with the data that actually
+/// arrives, not with what the other side announced.
+fn read_exactly(rd: &mut impl BufRead, len: usize, body: &mut Vec<u8>) -> io::Result<()> {
+    let n = rd.by_ref().take(len as u64).read_to_end(body)?;
+    if n < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    Ok(())
+}
+
+/// Reads one line without the line end.
+fn read_line(rd: &mut impl BufRead) -> io::Result<String> {
+    let mut buf = Vec::new();
+    rd.read_until(b'\n', &mut buf)?;
+    if buf.is_empty() {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    while matches!(buf.last(), Some(b'\n' | b'\r')) {
+        buf.pop();
+    }
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
@@ -0,0 +1,767 @@
+//!
+//! IPP/1.1 and IPP/2.0 without libcups.
+//!
+//! Encoding and decoding of IPP messages as in RFC 8010, and a small
+//! client that talks to IPP printers or to cupsd over plain HTTP.
+//!
+
+use std::io;
+
+use crate::PrintError;
+
+pub use client::IppClient;
+
+pub(crate) mod auth;
+pub(crate) mod capabilities;
+pub(crate) mod client;
+pub(crate) mod http;
+pub(crate) mod template;
+
+/// IPP version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Version(pub u8, pub u8);
+
+impl Version {
+    pub const V1_1: Version = Version(1, 1);
+    pub const V2_0: Version = Version(2, 0);
+}
+
+/// Operation ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    PrintJob,
+    ValidateJob,
+    CreateJob,
+    SendDocument,
+    CancelJob,
+    GetJobAttributes,
+    GetJobs,
+    GetPrinterAttributes,
+    HoldJob,
+    ReleaseJob,
+    CancelJobs,
+    CancelMyJobs,
+    CloseJob,
+    CupsGetDefault,
+    CupsGetPrinters,
+    Other(u16),
+}
+
+impl Operation {
+    pub fn code(self) -> u16 {
+        match self {
+            Operation::PrintJob => 0x0002,
+            Operation::ValidateJob => 0x0004,
+            Operation::CreateJob => 0x0005,
+            Operation::SendDocument => 0x0006,
+            Operation::CancelJob => 0x0008,
+            Operation::GetJobAttributes => 0x0009,
+            Operation::GetJobs => 0x000A,
+            Operation::GetPrinterAttributes => 0x000B,
+            Operation::HoldJob => 0x000C,
+            Operation::ReleaseJob => 0x000D,
+            Operation::CancelJobs => 0x0038,
+            Operation::CancelMyJobs => 0x0039,
+            Operation::CloseJob => 0x003B,
+            Operation::CupsGetDefault => 0x4001,
+            Operation::CupsGetPrinters => 0x4002,
+            Operation::Other(v) => v,
+        }
+    }
+
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            0x0002 => Operation::PrintJob,
+            0x0004 => Operation::ValidateJob,
+            0x0005 => Operation::CreateJob,
+            0x0006 => Operation::SendDocument,
+            0x0008 => Operation::CancelJob,
+            0x0009 => Operation::GetJobAttributes,
+            0x000A => Operation::GetJobs,
+            0x000B => Operation::GetPrinterAttributes,
+            0x000C => Operation::HoldJob,
+            0x000D => Operation::ReleaseJob,
+            0x0038 => Operation::CancelJobs,
+            0x0039 => Operation::CancelMyJobs,
+            0x003B => Operation::CloseJob,
+            0x4001 => Operation::CupsGetDefault,
+            0x4002 => Operation::CupsGetPrinters,
+            v => Operation::Other(v),
+        }
+    }
+}
+
+/// Status code of a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusCode(pub u16);
+
+impl StatusCode {
+    pub const OK: StatusCode = StatusCode(0x0000);
+    pub const OK_IGNORED_OR_SUBSTITUTED: StatusCode = StatusCode(0x0001);
+    pub const OK_CONFLICTING: StatusCode = StatusCode(0x0002);
+    pub const CLIENT_ERROR_BAD_REQUEST: StatusCode = StatusCode(0x0400);
+    pub const CLIENT_ERROR_FORBIDDEN: StatusCode = StatusCode(0x0401);
+    pub const CLIENT_ERROR_NOT_AUTHENTICATED: StatusCode = StatusCode(0x0402);
+    pub const CLIENT_ERROR_NOT_AUTHORIZED: StatusCode = StatusCode(0x0403);
+    pub const CLIENT_ERROR_NOT_POSSIBLE: StatusCode = StatusCode(0x0404);
+    pub const CLIENT_ERROR_NOT_FOUND: StatusCode = StatusCode(0x0406);
+    pub const CLIENT_ERROR_DOCUMENT_FORMAT_NOT_SUPPORTED: StatusCode = StatusCode(0x040A);
+    pub const CLIENT_ERROR_ATTRIBUTES_OR_VALUES_NOT_SUPPORTED: StatusCode = StatusCode(0x040B);
+    pub const CLIENT_ERROR_CONFLICTING_ATTRIBUTES: StatusCode = StatusCode(0x040D);
+    pub const SERVER_ERROR_INTERNAL_ERROR: StatusCode = StatusCode(0x0500);
+    pub const SERVER_ERROR_OPERATION_NOT_SUPPORTED: StatusCode = StatusCode(0x0501);
+    pub const SERVER_ERROR_NOT_ACCEPTING_JOBS: StatusCode = StatusCode(0x0506);
+
+    /// All successful-ok-* codes.
+    pub fn is_success(self) -> bool {
+        self.0 < 0x0100
+    }
+}
+
+/// Delimiter tags for the attribute groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupTag {
+    Operation,
+    Job,
+    Printer,
+    Unsupported,
+    Subscription,
+    EventNotification,
+    Document,
+    Other(u8),
+}
+
+impl GroupTag {
+    pub fn code(self) -> u8 {
+        match self {
+            GroupTag::Operation => 0x01,
+            GroupTag::Job => 0x02,
+            GroupTag::Printer => 0x04,
+            GroupTag::Unsupported => 0x05,
+            GroupTag::Subscription => 0x06,
+            GroupTag::EventNotification => 0x07,
+            GroupTag::Document => 0x09,
+            GroupTag::Other(v) => v,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0x01 => GroupTag::Operation,
+            0x02 => GroupTag::Job,
+            0x04 => GroupTag::Printer,
+            0x05 => GroupTag::Unsupported,
+            0x06 => GroupTag::Subscription,
+            0x07 => GroupTag::EventNotification,
+            0x09 => GroupTag::Document,
+            v => GroupTag::Other(v),
+        }
+    }
+}
+
+const TAG_END: u8 = 0x03;
+
+const TAG_UNSUPPORTED: u8 = 0x10;
+const TAG_UNKNOWN: u8 = 0x12;
+const TAG_NO_VALUE: u8 = 0x13;
+const TAG_INTEGER: u8 = 0x21;
+const TAG_BOOLEAN: u8 = 0x22;
+const TAG_ENUM: u8 = 0x23;
+const TAG_OCTET_STRING: u8 = 0x30;
+const TAG_DATE_TIME: u8 = 0x31;
+const TAG_RESOLUTION: u8 = 0x32;
+const TAG_RANGE: u8 = 0x33;
+const TAG_BEG_COLLECTION: u8 = 0x34;
+const TAG_TEXT_WITH_LANGUAGE: u8 = 0x35;
+const TAG_NAME_WITH_LANGUAGE: u8 = 0x36;
+const TAG_END_COLLECTION: u8 = 0x37;
+const TAG_TEXT: u8 = 0x41;
+const TAG_NAME: u8 = 0x42;
+const TAG_KEYWORD: u8 = 0x44;
+const TAG_URI: u8 = 0x45;
+const TAG_URI_SCHEME: u8 = 0x46;
+const TAG_CHARSET: u8 = 0x47;
+const TAG_NATURAL_LANGUAGE: u8 = 0x48;
+const TAG_MIME_MEDIA_TYPE: u8 = 0x49;
+const TAG_MEMBER_NAME: u8 = 0x4A;
+
+/// Units for a resolution.
+pub const RESOLUTION_DPI: i8 = 3;
+/// Units for a resolution.
+pub const RESOLUTION_DPCM: i8 = 4;
+
+/// Attribute value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unsupported,
+    Unknown,
+    NoValue,
+    Integer(i32),
+    Boolean(bool),
+    Enum(i32),
+    OctetString(Vec<u8>),
+    /// RFC 2579 DateAndTime, 11 bytes.
+    DateTime([u8; 11]),
+    Resolution {
+        x: i32,
+        y: i32,
+        units: i8,
+    },
+    Range(i32, i32),
+    TextWithLanguage {
+        language: String,
+        text: String,
+    },
+    NameWithLanguage {
+        language: String,
+        name: String,
+    },
+    Text(String),
+    Name(String),
+    Keyword(String),
+    Uri(String),
+    UriScheme(String),
+    Charset(String),
+    NaturalLanguage(String),
+    MimeMediaType(String),
+    Collection(Vec<Attribute>),
+    /// Any other value tag.
+    Other {
+        tag: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl Value {
+    /// Any string-like value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::TextWithLanguage { text: v, .. }
+            | Value::NameWithLanguage { name: v, .. }
+            | Value::Text(v)
+            | Value::Name(v)
+            | Value::Keyword(v)
+            | Value::Uri(v)
+            | Value::UriScheme(v)
+            | Value::Charset(v)
+            | Value::NaturalLanguage(v)
+            | Value::MimeMediaType(v) => Some(v.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Integer or enum value.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Value::Integer(v) | Value::Enum(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Members of a collection.
+    pub fn as_collection(&self) -> Option<&[Attribute]> {
+        match self {
+            Value::Collection(v) => Some(v.as_slice()),
+            _ => None,
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            Value::Unsupported => TAG_UNSUPPORTED,
+            Value::Unknown => TAG_UNKNOWN,
+            Value::NoValue => TAG_NO_VALUE,
+            Value::Integer(_) => TAG_INTEGER,
+            Value::Boolean(_) => TAG_BOOLEAN,
+            Value::Enum(_) => TAG_ENUM,
+            Value::OctetString(_) => TAG_OCTET_STRING,
+            Value::DateTime(_) => TAG_DATE_TIME,
+            Value::Resolution { .. } => TAG_RESOLUTION,
+            Value::Range(_, _) => TAG_RANGE,
+            Value::TextWithLanguage { .. } => TAG_TEXT_WITH_LANGUAGE,
+            Value::NameWithLanguage { .. } => TAG_NAME_WITH_LANGUAGE,
+            Value::Text(_) => TAG_TEXT,
+            Value::Name(_) => TAG_NAME,
+            Value::Keyword(_) => TAG_KEYWORD,
+            Value::Uri(_) => TAG_URI,
+            Value::UriScheme(_) => TAG_URI_SCHEME,
+            Value::Charset(_) => TAG_CHARSET,
+            Value::NaturalLanguage(_) => TAG_NATURAL_LANGUAGE,
+            Value::MimeMediaType(_) => TAG_MIME_MEDIA_TYPE,
+            Value::Collection(_) => TAG_BEG_COLLECTION,
+            Value::Other { tag, .. } => *tag,
+        }
+    }
+
+    /// Value bytes. Collections are written separately.
+    fn data(&self) -> Vec<u8> {
+        match self {
+            Value::Unsupported | Value::Unknown | Value::NoValue | Value::Collection(_) => {
+                Vec::new()
+            }
+            Value::Integer(v) | Value::Enum(v) => v.to_be_bytes().to_vec(),
+            Value::Boolean(v) => vec![*v as u8],
+            Value::OctetString(v) => v.clone(),
+            Value::DateTime(v) => v.to_vec(),
+            Value::Resolution { x, y, units } => {
+                let mut buf = Vec::with_capacity(9);
+                buf.extend_from_slice(&x.to_be_bytes());
+                buf.extend_from_slice(&y.to_be_bytes());
+                buf.push(*units as u8);
+                buf
+            }
+            Value::Range(lower, upper) => {
+                let mut buf = Vec::with_capacity(8);
+                buf.extend_from_slice(&lower.to_be_bytes());
+                buf.extend_from_slice(&upper.to_be_bytes());
+                buf
+            }
+            Value::TextWithLanguage {
+                language: lang,
+                text: v,
+            }
+            | Value::NameWithLanguage {
+                language: lang,
+                name: v,
+            } => {
+                let mut buf = Vec::with_capacity(4 + lang.len() + v.len());
+                buf.extend_from_slice(&(lang.len() as u16).to_be_bytes());
+                buf.extend_from_slice(lang.as_bytes());
+                buf.extend_from_slice(&(v.len() as u16).to_be_bytes());
+                buf.extend_from_slice(v.as_bytes());
+                buf
+            }
+            Value::Text(v)
+            | Value::Name(v)
+            | Value::Keyword(v)
+            | Value::Uri(v)
+            | Value::UriScheme(v)
+            | Value::Charset(v)
+            | Value::NaturalLanguage(v)
+            | Value::MimeMediaType(v) => v.as_bytes().to_vec(),
+            Value::Other { data, .. } => data.clone(),
+        }
+    }
+}
+
+/// Attribute with one or more values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    pub values: Vec<Value>,
+}
+
+impl Attribute {
+    /// Attribute with a single value.
+    pub fn new(name: &str, value: Value) -> Self {
+        Self {
+            name: name.to_string(),
+            values: vec![value],
+        }
+    }
+
+    /// Attribute with a list of values.
+    pub fn with_values(name: &str, values: Vec<Value>) -> Self {
+        Self {
+            name: name.to_string(),
+            values,
+        }
+    }
+
+    /// First value.
+    pub fn value(&self) -> Option<&Value> {
+        self.values.first()
+    }
+}
+
+/// Attribute group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub tag: GroupTag,
+    pub attributes: Vec<Attribute>,
+}
+
+impl Group {
+    pub fn new(tag: GroupTag) -> Self {
+        Self {
+            tag,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Find an attribute by name.
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|v| v.name == name)
+    }
+}
+
+/// IPP request or response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub version: Version,
+    /// Operation-id for a request, status-code for a response.
+    pub code: u16,
+    pub request_id: u32,
+    pub groups: Vec<Group>,
+}
+
+impl Message {
+    /// New request. Adds attributes-charset and attributes-natural-language.
+    pub fn request(operation: Operation, request_id: u32) -> Self {
+        let mut msg = Self {
+            version: Version::V2_0,
+            code: operation.code(),
+            request_id,
+            groups: Vec::new(),
+        };
+        msg.add_std_attributes();
+        msg
+    }
+
+    /// New response. Adds attributes-charset and attributes-natural-language.
+    pub fn response(status: StatusCode, request_id: u32) -> Self {
+        let mut msg = Self {
+            version: Version::V2_0,
+            code: status.0,
+            request_id,
+            groups: Vec::new(),
+        };
+        msg.add_std_attributes();
+        msg
+    }
+
+    fn add_std_attributes(&mut self) {
+        self.add(
+            GroupTag::Operation,
+            Attribute::new("attributes-charset", Value::Charset("utf-8".into())),
+        );
+        self.add(
+            GroupTag::Operation,
+            Attribute::new(
+                "attributes-natural-language",
+                Value::NaturalLanguage("en".into()),
+            ),
+        );
+    }
+
+    /// Operation of a request.
+    pub fn operation(&self) -> Operation {
+        Operation::from_code(self.code)
+    }
+
+    /// Status of a response.
+    pub fn status(&self) -> StatusCode {
+        StatusCode(self.code)
+    }
+
+    /// Adds the attribute to the last group with this tag.
+    /// Starts a new group if the last group has a different tag.
+    pub fn add(&mut self, tag: GroupTag, attr: Attribute) {
+        match self.groups.last_mut() {
+            Some(group) if group.tag == tag => group.attributes.push(attr),
+            _ => {
+                let mut group = Group::new(tag);
+                group.attributes.push(attr);
+                self.groups.push(group);
+            }
+        }
+    }
+
+    /// First group with this tag.
+    pub fn group(&self, tag: GroupTag) -> Option<&Group> {
+        self.groups.iter().find(|v| v.tag == tag)
+    }
+
+    /// All groups with this tag. Get-Jobs returns one group per job.
+    pub fn groups(&self, tag: GroupTag) -> impl Iterator<Item = &Group> {
+        self.groups.iter().filter(move |v| v.tag == tag)
+    }
+
+    /// Find an attribute in the first group with this tag.
+    pub fn attribute(&self, tag: GroupTag, name: &str) -> Option<&Attribute> {
+        self.group(tag).and_then(|v| v.attribute(name))
+    }
+
+    /// The status-message of a response, or an empty string.
+    pub fn status_message(&self) -> &str {
+        self.attribute(GroupTag::Operation, "status-message")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Encode the message. Any document data follows directly after this.
+    pub fn encode(&self) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        buf.push(self.version.0);
+        buf.push(self.version.1);
+        buf.extend_from_slice(&self.code.to_be_bytes());
+        buf.extend_from_slice(&self.request_id.to_be_bytes());
+        for group in &self.groups {
+            buf.push(group.tag.code());
+            for attr in &group.attributes {
+                encode_attribute(&mut buf, attr)?;
+            }
+        }
+        buf.push(TAG_END);
+        Ok(buf)
+    }
+
+    /// Decode a message. Returns the message and the number of bytes used.
+    /// Any document data follows after that.
+    pub fn decode(buf: &[u8]) -> io::Result<(Message, usize)> {
+        let mut rd = Reader { buf, pos: 0 };
+
+        let version = Version(rd.u8()?, rd.u8()?);
+        let code = rd.u16()?;
+        let request_id = rd.u32()?;
+
+        let mut groups: Vec<Group> = Vec::new();
+        loop {
+            let tag = rd.u8()?;
+            if tag == TAG_END {
+                break;
+            } else if tag < 0x10 {
+                groups.push(Group::new(GroupTag::from_code(tag)));
+            } else {
+                let group = match groups.last_mut() {
+                    Some(v) => v,
+                    None => return Err(protocol_error("Attribute outside of a group.")),
+                };
+                let name = rd.string()?;
+                let value = decode_value(&mut rd, tag, 0)?;
+                if name.is_empty() {
+                    match group.attributes.last_mut() {
+                        Some(attr) => attr.values.push(value),
+                        None => return Err(protocol_error("Additional value without name.")),
+                    }
+                } else {
+                    group.attributes.push(Attribute::new(&name, value));
+                }
+            }
+        }
+
+        Ok((
+            Message {
+                version,
+                code,
+                request_id,
+                groups,
+            },
+            rd.pos,
+        ))
+    }
+}
+
+pub(crate) fn protocol_error(msg: &str) -> io::Error {
+    PrintError::io_error(PrintError::Protocol(msg.to_string()))
+}
+
+fn encode_len(buf: &mut Vec<u8>, len: usize) -> io::Result<()> {
+    if len > u16::MAX as usize {
+        return Err(protocol_error("Value too long."));
+    }
+    buf.extend_from_slice(&(len as u16).to_be_bytes());
+    Ok(())
+}
+
+fn encode_attribute(buf: &mut Vec<u8>, attr: &Attribute) -> io::Result<()> {
+    for (i, value) in attr.values.iter().enumerate() {
+        let name = if i == 0 { attr.name.as_str() } else { "" };
+        encode_value(buf, name, value)?;
+    }
+    Ok(())
+}
+
+fn encode_value(buf: &mut Vec<u8>, name: &str, value: &Value) -> io::Result<()> {
+    buf.push(value.tag());
+    encode_len(buf, name.len())?;
+    buf.extend_from_slice(name.as_bytes());
+
+    if let Value::Collection(members) = value {
+        encode_len(buf, 0)?;
+        for member in members {
+            buf.push(TAG_MEMBER_NAME);
+            encode_len(buf, 0)?;
+            encode_len(buf, member.name.len())?;
+            buf.extend_from_slice(member.name.as_bytes());
+            for value in &member.values {
+                encode_value(buf, "", value)?;
+            }
+        }
+        buf.push(TAG_END_COLLECTION);
+        encode_len(buf, 0)?;
+        encode_len(buf, 0)?;
+    } else {
+        let data = value.data();
+        encode_len(buf, data.len())?;
+        buf.extend_from_slice(&data);
+    }
+    Ok(())
+}
+
+/// Nesting of collections in a response. Real ones have 2 or 3 levels.
+const MAX_COLLECTION_DEPTH: usize = 32;
+
+/// Decodes a value, depth is the number of enclosing collections.
+fn decode_value(rd: &mut Reader<'_>, tag: u8, depth: usize) -> io::Result<Value> {
+    if tag == TAG_BEG_COLLECTION {
+        if depth >= MAX_COLLECTION_DEPTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                PrintError::Protocol("Collections nested too deep.".to_string()),
+            ));
+        }
+        // value is empty
+        rd.bytes()?;
+        return decode_collection(rd, depth + 1);
+    }
+
+    let data = rd.bytes()?;
+    let value = match tag {
+        TAG_UNSUPPORTED => Value::Unsupported,
+        TAG_UNKNOWN => Value::Unknown,
+        TAG_NO_VALUE => Value::NoValue,
+        TAG_INTEGER | TAG_ENUM => {
+            let v = match <[u8; 4]>::try_from(data) {
+                Ok(v) => i32::from_be_bytes(v),
+                Err(_) => return Err(protocol_error("Invalid integer.")),
+            };
+            if tag == TAG_INTEGER {
+                Value::Integer(v)
+            } else {
+                Value::Enum(v)
+            }
+        }
+        TAG_BOOLEAN => match data {
+            [v] => Value::Boolean(*v != 0),
+            _ => return Err(protocol_error("Invalid boolean.")),
+        },
+        TAG_DATE_TIME => match <[u8; 11]>::try_from(data) {
+            Ok(v) => Value::DateTime(v),
+            Err(_) => return Err(protocol_error("Invalid dateTime.")),
+        },
+        TAG_RESOLUTION => {
+            if data.len() != 9 {
+                return Err(protocol_error("Invalid resolution."));
+            }
+            let mut rd = Reader { buf: data, pos: 0 };
+            Value::Resolution {
+                x: rd.u32()? as i32,
+                y: rd.u32()? as i32,
+                units: rd.u8()? as i8,
+            }
+        }
+        TAG_RANGE => {
+            if data.len() != 8 {
+                return Err(protocol_error("Invalid rangeOfInteger."));
+            }
+            let mut rd = Reader { buf: data, pos: 0 };
+            Value::Range(rd.u32()? as i32, rd.u32()? as i32)
+        }
+        TAG_TEXT_WITH_LANGUAGE | TAG_NAME_WITH_LANGUAGE => {
+            let mut rd = Reader { buf: data, pos: 0 };
+            let language = rd.string()?;
+            let text = rd.string()?;
+            if tag == TAG_TEXT_WITH_LANGUAGE {
+                Value::TextWithLanguage { language, text }
+            } else {
+                Value::NameWithLanguage {
+                    language,
+                    name: text,
+                }
+            }
+        }
+        TAG_TEXT => Value::Text(string(data)),
+        TAG_NAME => Value::Name(string(data)),
+        TAG_KEYWORD => Value::Keyword(string(data)),
+        TAG_URI => Value::Uri(string(data)),
+        TAG_URI_SCHEME => Value::UriScheme(string(data)),
+        TAG_CHARSET => Value::Charset(string(data)),
+        TAG_NATURAL_LANGUAGE => Value::NaturalLanguage(string(data)),
+        TAG_MIME_MEDIA_TYPE => Value::MimeMediaType(string(data)),
+        TAG_OCTET_STRING => Value::OctetString(data.to_vec()),
+        _ => Value::Other {
+            tag,
+            data: data.to_vec(),
+        },
+    };
+    Ok(value)
+}
+
+fn decode_collection(rd: &mut Reader<'_>, depth: usize) -> io::Result<Value> {
+    let mut members: Vec<Attribute> = Vec::new();
+    loop {
+        let tag = rd.u8()?;
+        // name is always empty inside a collection
+        rd.bytes()?;
+        if tag == TAG_END_COLLECTION {
+            rd.bytes()?;
+            break;
+        } else if tag == TAG_MEMBER_NAME {
+            let name = string(rd.bytes()?);
+            members.push(Attribute::with_values(&name, Vec::new()));
+        } else if tag < 0x10 {
+            return Err(protocol_error("Unterminated collection."));
+        } else {
+            let value = decode_value(rd, tag, depth)?;
+            match members.last_mut() {
+                Some(member) => member.values.push(value),
+                None => return Err(protocol_error("Collection value without member name.")),
+            }
+        }
+    }
+    Ok(Value::Collection(members))
+}
+
+fn string(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).to_string()
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.pos + n > self.buf.len() {
+            return Err(protocol_error("Message truncated."));
+        }
+        let v = &self.buf[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(v)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        let v = self.take(2)?;
+        Ok(u16::from_be_bytes([v[0], v[1]]))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let v = self.take(4)?;
+        Ok(u32::from_be_bytes([v[0], v[1], v[2], v[3]]))
+    }
+
+    /// Length prefixed bytes.
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u16()?;
+        self.take(len as usize)
+    }
+
+    /// Length prefixed string.
+    fn string(&mut self) -> io::Result<String> {
+        Ok(string(self.bytes()?))
+    }
+}
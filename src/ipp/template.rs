@@ -0,0 +1,428 @@
+//!
+//! Maps JobParam to IPP job-template attributes.
+//!
+
+use std::borrow::Cow;
+
+#[cfg(target_os = "windows")]
+use crate::ipp::capabilities::pwg_custom_name;
+use crate::ipp::{Attribute, Value, RESOLUTION_DPCM, RESOLUTION_DPI};
+#[cfg(target_os = "linux")]
+use crate::linux::options::{
+    FINISHINGS, MEDIA, MEDIA_SOURCE, MEDIA_TYPE, ORIENTATION, PRINT_COLOR_MODE, PRINT_QUALITY,
+    SIDES,
+};
+#[cfg(target_os = "windows")]
+use crate::{ColorMode, Duplex, Orientation, PaperSource, PaperType, Quality};
+use crate::{Format, JobParam, Length, PaperSize};
+
+fn keyword(v: &str) -> Value {
+    Value::Keyword(v.to_string())
+}
+
+/// IPP dimensions are in 1/100mm.
+fn hundredth_mm(v: Length) -> Value {
+    let v = v.count(Length::mm(0.01)).unwrap_or_default();
+    Value::Integer(v.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+}
+
+/// Mime type for the document-format.
+#[cfg(target_os = "linux")]
+pub(crate) fn document_format(format: &Format) -> &'static str {
+    match format {
+        Format::Raw => "application/vnd.cups-raw",
+        Format::Auto => "application/octet-stream",
+        Format::Command => "application/vnd.cups-command",
+        Format::Jpeg => "image/jpeg",
+        Format::Png => "image/png",
+        Format::Pdf => "application/pdf",
+        Format::Postscript => "application/postscript",
+        Format::Text => "text/plain",
+    }
+}
+
+/// application/vnd.cups-raw only exists with CUPS. A printer that
+/// doesn't list it in document-format-supported gets the raw data as
+/// application/octet-stream.
+pub(crate) fn supported_format(format: &'static str, supported: &[String]) -> &'static str {
+    if format == "application/vnd.cups-raw" && !supported.iter().any(|v| v == format) {
+        "application/octet-stream"
+    } else {
+        format
+    }
+}
+
+/// Mime type for the document-format.
+#[cfg(target_os = "windows")]
+pub(crate) fn document_format(format: &Format) -> &'static str {
+    match format {
+        Format::Text => "text/plain",
+        Format::XpsPass | Format::Xps2Gdi => "application/oxps",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The media keyword for a PaperSize.
+#[cfg(target_os = "linux")]
+pub(crate) fn media_keyword(size: &PaperSize) -> Option<Cow<'_, str>> {
+    Some(MEDIA.keyword(size))
+}
+
+/// The media keyword for a PaperSize. Only the sizes with an
+/// IPP equivalent.
+#[cfg(target_os = "windows")]
+pub(crate) fn media_keyword(size: &PaperSize) -> Option<Cow<'_, str>> {
+    match (size, size.media_size()) {
+        (_, Some(media)) => Some(Cow::Borrowed(media.name)),
+        (PaperSize::Custom { width, height }, None) => {
+            Some(Cow::Owned(pwg_custom_name(*width, *height)))
+        }
+        _ => None,
+    }
+}
+
+/// Width and length.
+pub(crate) fn media_dimensions(size: &PaperSize) -> Option<(Length, Length)> {
+    match size {
+        PaperSize::Custom { width, height } => Some((*width, *height)),
+        size => size.media_size().map(|v| (v.width, v.length)),
+    }
+}
+
+/// Job-template attributes for the job group.
+#[cfg(target_os = "linux")]
+pub(crate) fn job_template(param: &JobParam) -> Vec<Attribute> {
+    let mut attr = Vec::new();
+
+    if let Some(copies) = param.copies {
+        attr.push(Attribute::new("copies", Value::Integer(copies as i32)));
+    }
+    if let Some(finishings) = &param.finishings {
+        attr.push(Attribute::new(
+            FINISHINGS.name,
+            FINISHINGS.value(finishings),
+        ));
+    }
+
+    let source = param.paper_source.as_ref().map(|v| MEDIA_SOURCE.value(v));
+    let media_type = param.paper_type.as_ref().map(|v| MEDIA_TYPE.value(v));
+    push_media(&mut attr, param.paper_size.as_ref(), source, media_type);
+
+    if let Some(number_up) = param.number_up {
+        attr.push(Attribute::new(
+            "number-up",
+            Value::Integer(number_up as i32),
+        ));
+    }
+    if let Some(orientation) = &param.orientation {
+        attr.push(Attribute::new(
+            ORIENTATION.name,
+            ORIENTATION.value(orientation),
+        ));
+    }
+    if let Some(color) = &param.color {
+        attr.push(Attribute::new(
+            PRINT_COLOR_MODE.name,
+            PRINT_COLOR_MODE.value(color),
+        ));
+    }
+    if let Some(quality) = &param.quality {
+        attr.push(Attribute::new(
+            PRINT_QUALITY.name,
+            PRINT_QUALITY.value(quality),
+        ));
+    }
+    if let Some(duplex) = &param.duplex {
+        attr.push(Attribute::new(SIDES.name, SIDES.value(duplex)));
+    }
+    push_options(&mut attr, &param.options);
+
+    attr
+}
+
+/// Job-template attributes for the job group.
+///
+/// Only values with an IPP equivalent are mapped, numeric values
+/// and the other paper sizes are left to the printer defaults.
+#[cfg(target_os = "windows")]
+pub(crate) fn job_template(param: &JobParam) -> Vec<Attribute> {
+    let mut attr = Vec::new();
+
+    if let Some(copies) = param.copies {
+        attr.push(Attribute::new("copies", Value::Integer(copies as i32)));
+    }
+
+    let source = param.paper_source.as_ref().and_then(|v| match v {
+        PaperSource::Auto => Some(keyword("auto")),
+        PaperSource::Manual => Some(keyword("manual")),
+        PaperSource::Upper => Some(keyword("top")),
+        PaperSource::Middle => Some(keyword("middle")),
+        PaperSource::Lower => Some(keyword("bottom")),
+        PaperSource::Envelope => Some(keyword("envelope")),
+        PaperSource::Largecapacity => Some(keyword("large-capacity")),
+        _ => None,
+    });
+    let media_type = param.paper_type.as_ref().and_then(|v| match v {
+        PaperType::Standard => Some(keyword("stationery")),
+        PaperType::Glossy => Some(keyword("photographic-glossy")),
+        PaperType::Transparency => Some(keyword("transparency")),
+        _ => None,
+    });
+    push_media(&mut attr, param.paper_size.as_ref(), source, media_type);
+
+    if let Some(orientation) = &param.orientation {
+        let value = match orientation {
+            Orientation::Numeric(_) => None,
+            Orientation::Portrait => Some(Value::Enum(3)),
+            Orientation::Landscape => Some(Value::Enum(4)),
+        };
+        if let Some(value) = value {
+            attr.push(Attribute::new("orientation-requested", value));
+        }
+    }
+    if let Some(color) = &param.color {
+        let value = match color {
+            ColorMode::Numeric(_) => None,
+            ColorMode::Monochrome => Some(keyword("monochrome")),
+            ColorMode::Color => Some(keyword("color")),
+        };
+        if let Some(value) = value {
+            attr.push(Attribute::new("print-color-mode", value));
+        }
+    }
+    if let Some(quality) = &param.quality {
+        let value = match quality {
+            Quality::Numeric(_) => None,
+            Quality::Draft | Quality::Low => Some(Value::Enum(3)),
+            Quality::Normal => Some(Value::Enum(4)),
+            Quality::High => Some(Value::Enum(5)),
+        };
+        if let Some(value) = value {
+            attr.push(Attribute::new("print-quality", value));
+        }
+    }
+    if let Some(duplex) = &param.duplex {
+        let value = match duplex {
+            Duplex::Numeric(_) => None,
+            Duplex::Simplex => Some(keyword("one-sided")),
+            Duplex::TwoSidedPortrait => Some(keyword("two-sided-long-edge")),
+            Duplex::TwoSidedLandscape => Some(keyword("two-sided-short-edge")),
+        };
+        if let Some(value) = value {
+            attr.push(Attribute::new("sides", value));
+        }
+    }
+    push_options(&mut attr, &param.options);
+
+    attr
+}
+
+/// A plain media keyword, or a media-col if source or type are given
+/// or the size is custom. Sending both is a conflict for the printer.
+fn push_media(
+    attr: &mut Vec<Attribute>,
+    size: Option<&PaperSize>,
+    source: Option<Value>,
+    media_type: Option<Value>,
+) {
+    let custom = match size {
+        Some(PaperSize::Custom { width, height }) => Some(Value::Collection(vec![
+            Attribute::new("x-dimension", hundredth_mm(*width)),
+            Attribute::new("y-dimension", hundredth_mm(*height)),
+        ])),
+        _ => None,
+    };
+    let media = size.and_then(media_keyword).map(|v| keyword(&v));
+
+    if source.is_none() && media_type.is_none() && custom.is_none() {
+        if let Some(media) = media {
+            attr.push(Attribute::new("media", media));
+        }
+    } else {
+        let mut col = Vec::new();
+        if let Some(custom) = custom {
+            col.push(Attribute::new("media-size", custom));
+        } else if let Some(media) = media {
+            col.push(Attribute::new("media-size-name", media));
+        }
+        if let Some(source) = source {
+            col.push(Attribute::new("media-source", source));
+        }
+        if let Some(media_type) = media_type {
+            col.push(Attribute::new("media-type", media_type));
+        }
+        attr.push(Attribute::new("media-col", Value::Collection(col)));
+    }
+}
+
+/// Syntax of the known attributes for the raw options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Integer,
+    Boolean,
+    Enum,
+    Keyword,
+    Name,
+    Range,
+    Resolution,
+    Collection,
+}
+
+/// Job-template and job-description attributes that can come as raw option.
+const OPTION_SYNTAX: &[(&str, Syntax)] = &[
+    ("copies", Syntax::Integer),
+    ("finishings", Syntax::Enum),
+    ("job-cancel-after", Syntax::Integer),
+    ("job-hold-until", Syntax::Keyword),
+    ("job-name", Syntax::Name),
+    ("job-priority", Syntax::Integer),
+    ("job-sheets", Syntax::Keyword),
+    ("media", Syntax::Keyword),
+    ("media-col", Syntax::Collection),
+    ("media-source", Syntax::Keyword),
+    ("media-type", Syntax::Keyword),
+    ("multiple-document-handling", Syntax::Keyword),
+    ("number-up", Syntax::Integer),
+    ("orientation-requested", Syntax::Enum),
+    ("output-bin", Syntax::Keyword),
+    ("page-delivery", Syntax::Keyword),
+    ("page-ranges", Syntax::Range),
+    ("print-color-mode", Syntax::Keyword),
+    ("print-content-optimize", Syntax::Keyword),
+    ("print-quality", Syntax::Enum),
+    ("print-rendering-intent", Syntax::Keyword),
+    ("print-scaling", Syntax::Keyword),
+    ("printer-resolution", Syntax::Resolution),
+    ("sides", Syntax::Keyword),
+    ("fit-to-page", Syntax::Boolean),
+    ("mirror", Syntax::Boolean),
+];
+
+/// The attribute for a raw option.
+///
+/// Values of the known attributes are checked against their syntax,
+/// anything else is sent as name like libcups does. Multiple values are
+/// separated by ',', collections are written as {name=value ...}.
+/// None if the value doesn't fit the syntax.
+pub(crate) fn option_attribute(name: &str, value: &str) -> Option<Attribute> {
+    let syntax = OPTION_SYNTAX
+        .iter()
+        .find(|v| v.0 == name)
+        .map(|v| v.1)
+        .unwrap_or(Syntax::Name);
+    let values = split_values(value, ',')
+        .into_iter()
+        .map(|v| syntax_value(syntax, v))
+        .collect::<Option<Vec<_>>>()?;
+    Some(Attribute::with_values(name, values))
+}
+
+/// Job-template attributes for the raw options. Replaces the
+/// attribute of the same name. Invalid values are left out here, the
+/// jobs refuse them before with check_options() and validate_job()
+/// returns them.
+fn push_options(attr: &mut Vec<Attribute>, options: &[(String, String)]) {
+    for (name, value) in options {
+        if let Some(option) = option_attribute(name, value) {
+            attr.retain(|v| v.name != option.name);
+            attr.push(option);
+        }
+    }
+}
+
+fn syntax_value(syntax: Syntax, v: &str) -> Option<Value> {
+    let v = unquote(v.trim());
+    match syntax {
+        Syntax::Integer => v.parse().ok().map(Value::Integer),
+        Syntax::Boolean => match v {
+            "true" | "yes" | "on" => Some(Value::Boolean(true)),
+            "false" | "no" | "off" => Some(Value::Boolean(false)),
+            _ => None,
+        },
+        Syntax::Enum => v.parse().ok().map(Value::Enum),
+        Syntax::Keyword if !v.is_empty() && !v.contains(char::is_whitespace) => {
+            Some(Value::Keyword(v.to_string()))
+        }
+        Syntax::Keyword => None,
+        Syntax::Name => Some(Value::Name(v.to_string())),
+        Syntax::Range => {
+            let (lower, upper) = v.split_once('-').unwrap_or((v, v));
+            let lower = lower.trim().parse().ok()?;
+            let upper = upper.trim().parse().ok()?;
+            (lower <= upper).then_some(Value::Range(lower, upper))
+        }
+        Syntax::Resolution => {
+            let (v, units) = if let Some(v) = v.strip_suffix("dpi") {
+                (v, RESOLUTION_DPI)
+            } else if let Some(v) = v.strip_suffix("dpcm") {
+                (v, RESOLUTION_DPCM)
+            } else {
+                return None;
+            };
+            let (x, y) = v.split_once('x').unwrap_or((v, v));
+            Some(Value::Resolution {
+                x: x.parse().ok()?,
+                y: y.parse().ok()?,
+                units,
+            })
+        }
+        Syntax::Collection => collection(v).map(Value::Collection),
+    }
+}
+
+/// Members of a collection {name=value name=value}. The member values
+/// are integers, nested collections or keywords.
+fn collection(v: &str) -> Option<Vec<Attribute>> {
+    let v = v.strip_prefix('{')?.strip_suffix('}')?;
+    split_values(v, ' ')
+        .into_iter()
+        .filter(|v| !v.is_empty())
+        .map(|member| {
+            let (name, value) = member.split_once('=')?;
+            let values = split_values(value, ',')
+                .into_iter()
+                .map(|v| {
+                    let v = unquote(v.trim());
+                    if v.starts_with('{') {
+                        collection(v).map(Value::Collection)
+                    } else if let Ok(n) = v.parse() {
+                        Some(Value::Integer(n))
+                    } else {
+                        syntax_value(Syntax::Keyword, v)
+                    }
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(Attribute::with_values(name, values))
+        })
+        .collect()
+}
+
+/// Splits at sep, but not inside braces or quotes.
+fn split_values(v: &str, sep: char) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in v.char_indices() {
+        match c {
+            '"' | '\'' if quote == Some(c) => quote = None,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            '{' if quote.is_none() => depth += 1,
+            '}' if quote.is_none() => depth -= 1,
+            c if c == sep && depth == 0 && quote.is_none() => {
+                result.push(&v[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    result.push(&v[start..]);
+    result
+}
+
+fn unquote(v: &str) -> &str {
+    v.strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| v.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(v)
+}
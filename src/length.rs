@@ -0,0 +1,184 @@
+//!
+//! Lengths for paper sizes, margins and label coordinates.
+//!
+
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Units per inch. Divisible by 254 and 72, so mm, 1/100 inch and points
+/// are exact. The same as the EMU of Office Open XML.
+const PER_INCH: i64 = 914400;
+const PER_MM: i64 = PER_INCH * 10 / 254;
+const PER_POINT: i64 = PER_INCH / 72;
+
+/// A length.
+///
+/// Stored as integer so the conversions between mm, inch and points
+/// are lossless. Dots are exact if the dpi divides 914400, eg. 72, 96,
+/// 150, 200, 300 or 600. Otherwise, eg. with 203 dpi, they are rounded
+/// to the nearest dot.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Length(i64);
+
+impl Length {
+    pub const ZERO: Length = Length(0);
+
+    /// Length in mm.
+    pub const fn mm(v: f64) -> Length {
+        Length(round(v * PER_MM as f64))
+    }
+
+    /// Length in inch.
+    pub const fn inch(v: f64) -> Length {
+        Length(round(v * PER_INCH as f64))
+    }
+
+    /// Length in points, 1/72 inch.
+    pub const fn points(v: f64) -> Length {
+        Length(round(v * PER_POINT as f64))
+    }
+
+    /// Length in dots of a printer with the given resolution.
+    ///
+    /// Panics if dpi is 0 or the length is out of range.
+    pub const fn dots(v: i64, dpi: u32) -> Length {
+        assert!(dpi > 0, "dpi must not be 0");
+        let v = div_round(v as i128 * PER_INCH as i128, dpi as i128);
+        assert!(
+            v >= i64::MIN as i128 && v <= i64::MAX as i128,
+            "length out of range"
+        );
+        Length(v as i64)
+    }
+
+    /// The length in mm.
+    pub fn as_mm(self) -> f64 {
+        self.0 as f64 / PER_MM as f64
+    }
+
+    /// The length in inch.
+    pub fn as_inch(self) -> f64 {
+        self.0 as f64 / PER_INCH as f64
+    }
+
+    /// The length in points.
+    pub fn as_points(self) -> f64 {
+        self.0 as f64 / PER_POINT as f64
+    }
+
+    /// The length in dots, rounded to the nearest dot.
+    /// None if dpi is 0 or there are too many dots for an i64.
+    pub fn to_dots(self, dpi: u32) -> Option<i64> {
+        if dpi == 0 {
+            return None;
+        }
+        i64::try_from(div_round(self.0 as i128 * dpi as i128, PER_INCH as i128)).ok()
+    }
+
+    /// The length as multiple of unit, rounded. Eg. with Length::mm(0.1)
+    /// for a value in 1/10 mm. None if unit is 0 or the result doesn't
+    /// fit.
+    pub fn count(self, unit: Length) -> Option<i64> {
+        if unit.0 == 0 {
+            return None;
+        }
+        i64::try_from(div_round(self.0 as i128, unit.0 as i128)).ok()
+    }
+
+    /// Absolute value.
+    pub fn abs(self) -> Length {
+        Length(self.0.abs())
+    }
+}
+
+const fn round(v: f64) -> i64 {
+    if v < 0.0 {
+        (v - 0.5) as i64
+    } else {
+        (v + 0.5) as i64
+    }
+}
+
+/// Rounded division. In i128 the product of two i64 can't overflow.
+const fn div_round(v: i128, d: i128) -> i128 {
+    if (v < 0) == (d < 0) {
+        (v + d / 2) / d
+    } else {
+        (v - d / 2) / d
+    }
+}
+
+/// Shows mm with up to 2 decimals.
+impl Display for Length {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // a unit > 1 always fits.
+        let v = self.count(Length::mm(0.01)).unwrap_or_default();
+        let s = format!("{}.{:02}", v.abs() / 100, v.abs() % 100);
+        let s = s.trim_end_matches('0').trim_end_matches('.');
+        if v < 0 {
+            write!(f, "-{}mm", s)
+        } else {
+            write!(f, "{}mm", s)
+        }
+    }
+}
+
+impl Add for Length {
+    type Output = Length;
+
+    fn add(self, rhs: Length) -> Length {
+        Length(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Length {
+    fn add_assign(&mut self, rhs: Length) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Length {
+    type Output = Length;
+
+    fn sub(self, rhs: Length) -> Length {
+        Length(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Length {
+    fn sub_assign(&mut self, rhs: Length) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Length {
+    type Output = Length;
+
+    fn neg(self) -> Length {
+        Length(-self.0)
+    }
+}
+
+impl Mul<i64> for Length {
+    type Output = Length;
+
+    fn mul(self, rhs: i64) -> Length {
+        Length(self.0 * rhs)
+    }
+}
+
+/// Rounded.
+impl Div<i64> for Length {
+    type Output = Length;
+
+    fn div(self, rhs: i64) -> Length {
+        Length(div_round(self.0 as i128, rhs as i128) as i64)
+    }
+}
+
+impl Sum for Length {
+    fn sum<I: Iterator<Item = Length>>(iter: I) -> Length {
+        iter.fold(Length::ZERO, |a, b| a + b)
+    }
+}
@@ -6,21 +6,33 @@
 //! For the rest: State and parameters are available, but system specific.
 //!
 use std::alloc::LayoutError;
+use std::borrow::Cow;
 use std::error::Error;
 use std::ffi::NulError;
-use std::fmt::{Display, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 use std::io::Write;
 use std::num::ParseIntError;
+use std::time::{Duration, Instant, SystemTime};
 
+pub use credentials::{clear_credentials_provider, set_credentials_provider, Credentials};
+pub use file::{
+    print_file, print_file_progress, print_reader, print_reader_progress, DocumentType,
+};
+pub use length::Length;
 #[cfg(target_os = "linux")]
 pub use linux::{
-    default_printer, list_printers, printer_attr, ColorMode, Duplex, Finishings, Format, Info,
+    cancel_all_jobs, cancel_job, capabilities, default_printer, hold_job, job_status, list_jobs,
+    list_printers, list_printers_on, printer_attr, printer_attr_on, printer_status, release_job,
+    validate_job, ColorMode, CupsConnection, Duplex, Encryption, Finishings, Format, Info,
     LinuxPrintJob as PrintJob, Orientation, PaperSize, PaperSource, PaperType, Quality,
 };
+pub use reason::{Reason, Severity, StateReason};
 #[cfg(target_os = "windows")]
 pub use windows::{
-    default_printer, list_printers, printer_attr, Collate, ColorMode, Duplex, Format, Info,
-    Orientation, PaperSize, PaperSource, PaperType, Quality, TrueType, WindowsPrintJob as PrintJob,
+    cancel_all_jobs, cancel_job, capabilities, default_printer, hold_job, job_status, list_jobs,
+    list_printers, printer_attr, printer_status, release_job, validate_job, Collate, ColorMode,
+    Duplex, Format, Info, Orientation, PaperSize, PaperSource, PaperType, Quality, TrueType,
+    WindowsPrintJob as PrintJob,
 };
 
 /// Maps the system specific states to these basic flags.
@@ -33,33 +45,265 @@ pub enum Status {
     Error,
 }
 
+/// State of a printer.
+///
+/// Maps the IPP printer-state and the windows printer status.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum PrinterState {
+    #[default]
+    Idle,
+    Printing,
+    Stopped,
+}
+
+/// Status of a printer, the same for all systems.
+///
+/// The system specific details are still available with printer_attr().
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct PrinterStatus {
+    pub state: PrinterState,
+    /// Why the printer is in this state. Empty if all is well.
+    pub reasons: Vec<StateReason>,
+    /// The printer takes new jobs.
+    pub accepting_jobs: bool,
+    /// Last change of the state, if known.
+    pub changed: Option<SystemTime>,
+    /// Message for the user. Empty if the system has none.
+    pub message: String,
+}
+
+impl PrinterStatus {
+    /// Sums it up.
+    pub fn status(&self) -> Status {
+        summary_status(self.state, &self.reasons)
+    }
+}
+
+/// A stopped printer is Error or Warn if there is a reason with
+/// this severity.
+pub(crate) fn summary_status(state: PrinterState, reasons: &[StateReason]) -> Status {
+    match state {
+        PrinterState::Stopped => match reasons.iter().map(|v| v.severity).max() {
+            Some(Severity::Error) => Status::Error,
+            Some(Severity::Warning) => Status::Warn,
+            _ => Status::Stopped,
+        },
+        PrinterState::Printing => Status::Busy,
+        PrinterState::Idle => Status::Idle,
+    }
+}
+
+/// What a printer supports.
+///
+/// The values can be used for the JobParam.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct Capabilities {
+    pub media: Vec<Media>,
+    pub sides: Vec<Duplex>,
+    pub color_modes: Vec<ColorMode>,
+    pub qualities: Vec<Quality>,
+    /// Resolutions in dpi, x and y.
+    pub resolutions: Vec<(u32, u32)>,
+    #[cfg(target_os = "linux")]
+    pub finishings: Vec<Finishings>,
+    /// Mime types on linux, the datatypes of the print processor on windows.
+    pub document_formats: Vec<String>,
+    /// Minimum and maximum copies.
+    pub copies: Option<(u32, u32)>,
+    /// Minimum and maximum width and length for PaperSize::Custom.
+    pub custom_size: Option<((Length, Length), (Length, Length))>,
+}
+
+/// A supported media.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct Media {
+    /// IPP media keyword, windows paper name.
+    pub name: String,
+    /// The value for JobParam::paper_size, if there is one.
+    pub paper_size: Option<PaperSize>,
+    /// Width, 0 if unknown.
+    pub width: Length,
+    /// Length, 0 if unknown.
+    pub length: Length,
+    /// Minimum margins, if known.
+    pub margins: Option<Margins>,
+}
+
+/// Margins.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Margins {
+    pub bottom: Length,
+    pub left: Length,
+    pub right: Length,
+    pub top: Length,
+}
+
+/// Lifecycle of a printjob.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Lifecycle {
+    /// Started, nothing written yet.
+    Created,
+    /// Some data has been written.
+    Writing,
+    /// Closed and sent to the printer.
+    Closed,
+    /// Cancelled.
+    Aborted,
+}
+
+impl Lifecycle {
+    /// Closed or aborted, the printjob takes no more data.
+    pub fn is_done(&self) -> bool {
+        matches!(self, Lifecycle::Closed | Lifecycle::Aborted)
+    }
+}
+
+/// State of a printjob in the printing system.
+///
+/// Maps the IPP job-state and the windows job status.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum JobState {
+    /// Waiting to be printed.
+    Pending,
+    /// Held, will not print until released.
+    Held,
+    /// Printing.
+    Processing,
+    /// Printing stopped, usually the printer needs attention.
+    Stopped,
+    /// Canceled by a user.
+    Canceled,
+    /// Aborted by the system.
+    Aborted,
+    /// Printed.
+    Completed,
+}
+
+impl JobState {
+    /// The job is done, successful or not.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            JobState::Canceled | JobState::Aborted | JobState::Completed
+        )
+    }
+}
+
+/// Status of a printjob.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JobStatus {
+    pub job_id: u32,
+    pub state: JobState,
+    /// Details for the state, as IPP keywords.
+    pub reasons: Vec<String>,
+    /// Impressions or pages printed so far.
+    pub impressions_completed: u32,
+}
+
+/// Which jobs list_jobs() returns.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum WhichJobs {
+    /// Jobs not yet finished.
+    #[default]
+    Active,
+    /// Finished jobs, as far as the system keeps them.
+    Completed,
+    All,
+}
+
+/// A job in the queue of a printer.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JobInfo {
+    pub job_id: u32,
+    pub name: String,
+    pub user: String,
+    /// Size in bytes. IPP only knows kilobytes.
+    pub size: u64,
+    pub state: JobState,
+    pub created: Option<SystemTime>,
+}
+
+pub mod backend;
+mod credentials;
 pub mod driver;
+mod file;
+pub mod ipp;
+mod length;
 #[cfg(target_os = "linux")]
 mod linux;
+pub mod media;
+mod reason;
 #[cfg(target_os = "windows")]
 mod windows;
 
+/// Trait for the output side of a printjob.
+///
+/// The system printjob implements this, but anything that can take
+/// the bytes can be used as a target for a [Driver].
+pub trait Backend: Write {
+    /// Start a new page. Hint to the printing system.
+    fn start_page(&mut self) -> std::io::Result<()>;
+    /// End a page. Hint to the printing system.
+    fn end_page(&mut self) -> std::io::Result<()>;
+    /// End the document and send it.
+    fn close(&mut self) -> std::io::Result<()>;
+    /// Cancel the job. As far as the backend allows nothing will be printed.
+    fn abort(&mut self) -> std::io::Result<()>;
+}
+
 /// Trait for a printer driver.
 ///
-/// Wraps around a Job. The impl provides higher level functions for
+/// Wraps around a Backend. The impl provides higher level functions for
 /// actually printing stuff.
 pub trait Driver: Write {
+    /// Output backend.
+    type Backend: Backend;
+
+    /// Create a driver writing to the given backend.
+    fn with_backend(backend: Self::Backend) -> Self
+    where
+        Self: Sized;
+
+    /// Access to the backend.
+    fn backend(&mut self) -> &mut Self::Backend;
+
     /// Create a new printjob.
     fn new(pr_name: &str, doc_name: &str) -> std::io::Result<Self>
     where
-        Self: Sized;
+        Self: Sized + Driver<Backend = PrintJob>,
+    {
+        Ok(Self::with_backend(PrintJob::new(pr_name, doc_name)?))
+    }
 
     /// Create a new printjob.
     fn new_with(pr_name: &str, doc_name: &str, param: &JobParam) -> std::io::Result<Self>
     where
-        Self: Sized;
+        Self: Sized + Driver<Backend = PrintJob>,
+    {
+        Ok(Self::with_backend(PrintJob::new_with(
+            pr_name, doc_name, param,
+        )?))
+    }
 
     /// Start a new page. Hint to the printing system.
-    fn start_page(&mut self) -> std::io::Result<()>;
+    fn start_page(&mut self) -> std::io::Result<()> {
+        self.backend().start_page()
+    }
     /// End a page. Hint to the printing system.
-    fn end_page(&mut self) -> std::io::Result<()>;
+    fn end_page(&mut self) -> std::io::Result<()> {
+        self.backend().end_page()
+    }
     /// End the document.
-    fn close(&mut self) -> std::io::Result<()>;
+    fn close(&mut self) -> std::io::Result<()> {
+        self.backend().close()
+    }
+    /// Cancel the printjob.
+    fn abort(&mut self) -> std::io::Result<()> {
+        self.backend().abort()
+    }
 }
 
 /// Job parameters.
@@ -84,12 +328,12 @@ pub struct JobParam {
     pub color: Option<ColorMode>,
     pub quality: Option<Quality>,
     pub duplex: Option<Duplex>,
-    /// Length in 1/10mm
+    /// Overrides the length of the paper size. Sent in 1/10mm.
     #[cfg(target_os = "windows")]
-    pub paper_length: Option<i16>,
-    /// Width in 1/10mm
+    pub paper_length: Option<Length>,
+    /// Overrides the width of the paper size. Sent in 1/10mm.
     #[cfg(target_os = "windows")]
-    pub paper_width: Option<i16>,
+    pub paper_width: Option<Length>,
     /// Scale in scale/100
     #[cfg(target_os = "windows")]
     pub scale: Option<i16>,
@@ -100,6 +344,251 @@ pub struct JobParam {
     pub tt_option: Option<TrueType>,
     #[cfg(target_os = "windows")]
     pub collate: Option<Collate>,
+    /// Replace unsupported values with the nearest supported one
+    /// before the job is created. This asks for the capabilities()
+    /// first. If there is nothing to replace a value with, the job
+    /// fails with PrintError::InvalidParam instead of dropping it.
+    pub auto_correct: bool,
+    /// The job gets more documents with next_document() on linux.
+    /// libcups has to know this before the first document, without it
+    /// the only document is sent as the last one. IppJob doesn't need it.
+    pub multi_document: bool,
+    /// Options not covered above, by name and in the given order, eg.
+    /// ("page-ranges", "1-4") or a PPD option ("Darkness", "20").
+    ///
+    /// They are sent as given after the values above and replace them
+    /// if the name is the same. Values of the known IPP attributes are
+    /// checked, see check_options(). The Windows spooler ignores them.
+    pub options: Vec<(String, String)>,
+}
+
+impl JobParam {
+    /// Checks the values against the capabilities of the printer.
+    ///
+    /// Only checks what the capabilities know, an empty list of supported
+    /// values is not an error.
+    pub fn validate(&self, caps: &Capabilities) -> Vec<Conflict> {
+        let mut conflicts = self.check_options();
+        conflicts.extend(self.clone().correct(caps).into_iter().map(|v| Conflict {
+            name: v.name,
+            value: v.value,
+        }));
+        conflicts
+    }
+
+    /// Checks the raw options with a known IPP attribute name against
+    /// the syntax of the attribute, eg. page-ranges=1-4,8.
+    pub fn check_options(&self) -> Vec<Conflict> {
+        self.options
+            .iter()
+            .filter(|(name, value)| ipp::template::option_attribute(name, value).is_none())
+            .map(|(name, value)| Conflict {
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+
+    /// Replaces the unsupported values with the nearest supported one.
+    /// Returns what was replaced and with what. A value without a
+    /// supported replacement is cleared, the printer default applies.
+    pub fn correct(&mut self, caps: &Capabilities) -> Vec<Correction> {
+        let mut conflicts = Vec::new();
+
+        if let (Some(copies), Some((min, max))) = (self.copies, caps.copies) {
+            if copies < min || copies > max {
+                let replacement = copies.clamp(min, max);
+                conflicts.push(Correction::new("copies", &copies, Some(&replacement)));
+                self.copies = Some(replacement);
+            }
+        }
+        if let Some(size @ PaperSize::Custom { width, height }) = &self.paper_size {
+            if !custom_supported(*width, *height, caps) {
+                let replacement = nearest_media(size, caps);
+                conflicts.push(Correction::new("media", size, replacement.as_ref()));
+                self.paper_size = replacement;
+            }
+        } else {
+            let media = caps
+                .media
+                .iter()
+                .filter_map(|v| v.paper_size.clone())
+                .collect::<Vec<_>>();
+            correct_value(&mut self.paper_size, "media", &media, &mut conflicts, |v| {
+                nearest_media(v, caps)
+            });
+        }
+        correct_value(
+            &mut self.duplex,
+            "sides",
+            &caps.sides,
+            &mut conflicts,
+            |_| preferred(&caps.sides, Duplex::Simplex),
+        );
+        correct_value(
+            &mut self.color,
+            "print-color-mode",
+            &caps.color_modes,
+            &mut conflicts,
+            |_| preferred(&caps.color_modes, ColorMode::Monochrome),
+        );
+        correct_value(
+            &mut self.quality,
+            "print-quality",
+            &caps.qualities,
+            &mut conflicts,
+            |_| preferred(&caps.qualities, Quality::Normal),
+        );
+        #[cfg(target_os = "linux")]
+        correct_value(
+            &mut self.finishings,
+            "finishings",
+            &caps.finishings,
+            &mut conflicts,
+            |_| None,
+        );
+
+        conflicts
+    }
+
+    /// Checks the raw options and returns a corrected copy if
+    /// auto_correct is set.
+    pub(crate) fn prepare(
+        &self,
+        caps: impl FnOnce() -> std::io::Result<Capabilities>,
+    ) -> std::io::Result<Cow<'_, JobParam>> {
+        let invalid = self.check_options();
+        if !invalid.is_empty() {
+            return Err(PrintError::io_error(PrintError::InvalidParam(invalid)));
+        }
+        if self.auto_correct {
+            let mut param = self.clone();
+            let cleared = param
+                .correct(&caps()?)
+                .into_iter()
+                .filter(|v| v.replacement.is_none())
+                .map(|v| Conflict {
+                    name: v.name,
+                    value: v.value,
+                })
+                .collect::<Vec<_>>();
+            if !cleared.is_empty() {
+                return Err(PrintError::io_error(PrintError::InvalidParam(cleared)));
+            }
+            Ok(Cow::Owned(param))
+        } else {
+            Ok(Cow::Borrowed(self))
+        }
+    }
+}
+
+/// Replaces an unsupported value.
+fn correct_value<T: PartialEq + Debug>(
+    value: &mut Option<T>,
+    name: &str,
+    supported: &[T],
+    conflicts: &mut Vec<Correction>,
+    replace: impl FnOnce(&T) -> Option<T>,
+) {
+    if supported.is_empty() {
+        return;
+    }
+    if let Some(v) = value {
+        if !supported.contains(v) {
+            let replacement = replace(v);
+            conflicts.push(Correction::new(name, v, replacement.as_ref()));
+            *value = replacement;
+        }
+    }
+}
+
+/// The preferred value if it's supported, otherwise the first one.
+fn preferred<T: PartialEq + Clone>(supported: &[T], preferred: T) -> Option<T> {
+    if supported.contains(&preferred) {
+        Some(preferred)
+    } else {
+        supported.first().cloned()
+    }
+}
+
+/// A custom size is supported if it is in the range for custom sizes,
+/// or if there is a media with the same dimensions. Without both
+/// nothing is known.
+fn custom_supported(width: Length, height: Length, caps: &Capabilities) -> bool {
+    let tolerance = Length::mm(1.0);
+    let same_size = caps
+        .media
+        .iter()
+        .any(|v| (v.width - width).abs() <= tolerance && (v.length - height).abs() <= tolerance);
+    match caps.custom_size {
+        Some(((min_w, min_h), (max_w, max_h))) => {
+            same_size || (min_w..=max_w).contains(&width) && (min_h..=max_h).contains(&height)
+        }
+        None => same_size || caps.media.is_empty(),
+    }
+}
+
+/// The media with the nearest dimensions. The first one if the size is
+/// unknown.
+fn nearest_media(size: &PaperSize, caps: &Capabilities) -> Option<PaperSize> {
+    let media = caps.media.iter().filter(|v| v.paper_size.is_some());
+    let nearest = match ipp::template::media_dimensions(size) {
+        Some((width, length)) => media
+            .filter(|v| v.width > Length::ZERO && v.length > Length::ZERO)
+            .min_by_key(|v| (v.width - width).abs() + (v.length - length).abs()),
+        None => media.into_iter().next(),
+    };
+    nearest.and_then(|v| v.paper_size.clone())
+}
+
+/// A job parameter the printer doesn't take.
+///
+/// Locally this is a value the capabilities don't list, or a raw option
+/// with an invalid value. Validate-Job also returns values that only
+/// conflict with another parameter, in the same way. There is no
+/// telling these apart.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Conflict {
+    /// IPP attribute name, eg. media or sides.
+    pub name: String,
+    /// The requested value.
+    pub value: String,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+/// A job parameter replaced by JobParam::correct().
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Correction {
+    /// IPP attribute name, eg. media or sides.
+    pub name: String,
+    /// The requested value.
+    pub value: String,
+    /// The value used instead. None if the parameter was cleared.
+    pub replacement: Option<String>,
+}
+
+impl Correction {
+    fn new<T: Debug>(name: &str, value: &T, replacement: Option<&T>) -> Self {
+        Self {
+            name: name.to_string(),
+            value: format!("{:?}", value),
+            replacement: replacement.map(|v| format!("{:?}", v)),
+        }
+    }
+}
+
+impl Display for Correction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.replacement {
+            Some(v) => write!(f, "{}={} replaced with {}", self.name, self.value, v),
+            None => write!(f, "{}={} cleared", self.name, self.value),
+        }
+    }
 }
 
 /// Printer errors.
@@ -119,6 +608,34 @@ pub enum PrintError {
     LayoutError,
     /// ParseIntError
     ParseIntError,
+    /// Network error.
+    Network(String),
+    /// Timeout.
+    Timeout,
+    /// The printjob has already been closed.
+    Closed,
+    /// Invalid data from the other side.
+    Protocol(String),
+    /// The printing system is not installed.
+    Unavailable(String),
+    /// The printer doesn't support some job parameters.
+    InvalidParam(Vec<Conflict>),
+    /// The printjob ended without being printed.
+    JobFailed {
+        state: JobState,
+        /// The job-state-reasons.
+        reasons: Vec<String>,
+    },
+    /// One document of a job with several documents failed.
+    Document {
+        /// Index of the document, 0 is the first one.
+        index: usize,
+        name: String,
+        /// The error message.
+        error: String,
+    },
+    /// The server wants a user and password and none were accepted.
+    AuthenticationRequired,
 }
 
 impl Error for PrintError {}
@@ -133,7 +650,74 @@ impl Display for PrintError {
             PrintError::ParseIntError => write!(f, "Parse int error."),
             PrintError::DocumentOpen => write!(f, "Document already open."),
             PrintError::LayoutError => write!(f, "Can't create memory layout."),
+            PrintError::Network(v) => write!(f, "{}", v),
+            PrintError::Timeout => write!(f, "Timeout."),
+            PrintError::Closed => write!(f, "Printjob already closed."),
+            PrintError::Protocol(v) => write!(f, "Protocol error: {}", v),
+            PrintError::Unavailable(v) => write!(f, "Printing system unavailable: {}", v),
+            PrintError::InvalidParam(v) => {
+                write!(f, "Unsupported job parameters:")?;
+                for c in v {
+                    write!(f, " {}", c)?;
+                }
+                Ok(())
+            }
+            PrintError::JobFailed { state, reasons } => {
+                write!(f, "Printjob failed: {:?} {}", state, reasons.join(", "))
+            }
+            PrintError::Document { index, name, error } => {
+                write!(f, "Document {} '{}' failed: {}", index, name, error)
+            }
+            PrintError::AuthenticationRequired => write!(f, "Authentication required."),
+        }
+    }
+}
+
+impl PrintError {
+    pub(crate) fn io_error(e: PrintError) -> std::io::Error {
+        std::io::Error::other(e)
+    }
+
+    /// Error for one document of a job with several documents.
+    pub(crate) fn document_error(index: usize, name: &str, e: std::io::Error) -> std::io::Error {
+        Self::io_error(PrintError::Document {
+            index,
+            name: name.to_string(),
+            error: e.to_string(),
+        })
+    }
+}
+
+/// Interval for polling the job status.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls the job status until the job reaches a final state.
+///
+/// Ok if the job completed, PrintError::JobFailed if it was canceled or
+/// aborted and PrintError::Timeout if it takes longer than timeout.
+pub(crate) fn wait_for_job(
+    timeout: Duration,
+    mut status: impl FnMut() -> std::io::Result<JobStatus>,
+) -> std::io::Result<()> {
+    let start = Instant::now();
+    loop {
+        let status = status()?;
+        match status.state {
+            JobState::Completed => return Ok(()),
+            JobState::Canceled | JobState::Aborted => {
+                return Err(PrintError::io_error(PrintError::JobFailed {
+                    state: status.state,
+                    reasons: status.reasons,
+                }));
+            }
+            _ => {}
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            return Err(PrintError::io_error(PrintError::Timeout));
         }
+        std::thread::sleep(POLL_INTERVAL.min(timeout - elapsed));
     }
 }
 
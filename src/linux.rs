@@ -1,92 +1,217 @@
-use std::ffi::{c_char, c_int, CStr, CString};
-use std::io::{ErrorKind, Write};
-use std::ptr::{self, slice_from_raw_parts};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
-use cups_sys::{
-    cupsAddOption, cupsCreateJob, cupsFinishDocument, cupsFreeDests, cupsGetDests,
-    cupsGetNamedDest, cupsLastErrorString, cupsStartDocument, cupsWriteRequestData, CUPS_COPIES,
-    CUPS_FINISHINGS, CUPS_FINISHINGS_BIND, CUPS_FINISHINGS_COVER, CUPS_FINISHINGS_FOLD,
-    CUPS_FINISHINGS_NONE, CUPS_FINISHINGS_PUNCH, CUPS_FINISHINGS_STAPLE, CUPS_FINISHINGS_TRIM,
-    CUPS_FORMAT_AUTO, CUPS_FORMAT_COMMAND, CUPS_FORMAT_JPEG, CUPS_FORMAT_PDF,
-    CUPS_FORMAT_POSTSCRIPT, CUPS_FORMAT_TEXT, CUPS_MEDIA_SOURCE, CUPS_MEDIA_SOURCE_AUTO,
-    CUPS_MEDIA_SOURCE_MANUAL, CUPS_MEDIA_TYPE, CUPS_MEDIA_TYPE_AUTO, CUPS_MEDIA_TYPE_ENVELOPE,
-    CUPS_MEDIA_TYPE_LABELS, CUPS_MEDIA_TYPE_LETTERHEAD, CUPS_MEDIA_TYPE_PHOTO,
-    CUPS_MEDIA_TYPE_PHOTO_GLOSSY, CUPS_MEDIA_TYPE_PHOTO_MATTE, CUPS_MEDIA_TYPE_PLAIN,
-    CUPS_MEDIA_TYPE_TRANSPARENCY, CUPS_NUMBER_UP, CUPS_ORIENTATION, CUPS_ORIENTATION_LANDSCAPE,
-    CUPS_PRINT_COLOR_MODE, CUPS_PRINT_COLOR_MODE_AUTO, CUPS_PRINT_COLOR_MODE_COLOR,
-    CUPS_PRINT_COLOR_MODE_MONOCHROME, CUPS_PRINT_QUALITY, CUPS_PRINT_QUALITY_DRAFT,
-    CUPS_PRINT_QUALITY_HIGH, CUPS_PRINT_QUALITY_NORMAL, CUPS_SIDES, CUPS_SIDES_ONE_SIDED,
-    CUPS_SIDES_TWO_SIDED_LANDSCAPE, CUPS_SIDES_TWO_SIDED_PORTRAIT,
+use crate::ipp::capabilities::{capabilities as ipp_capabilities, CAPABILITIES_ATTRIBUTES};
+use crate::ipp::client::{
+    conflicts, get_jobs_request, invalid_param, job_infos, job_status as ipp_job_status,
+    printer_status as ipp_printer_status, requested_attributes, validate_job_request,
+    JOB_STATUS_ATTRIBUTES, PRINTER_STATUS_ATTRIBUTES,
 };
-use cups_sys::{cups_dest_t, cups_option_t};
-use cups_sys::{
-    http_status_e_HTTP_STATUS_CONTINUE as HTTP_STATUS_CONTINUE, http_t,
-    ipp_status_e_IPP_STATUS_OK as IPP_STATUS_OK, CUPS_FORMAT_RAW, CUPS_MEDIA, CUPS_MEDIA_3X5,
-    CUPS_MEDIA_4X6, CUPS_MEDIA_5X7, CUPS_MEDIA_8X10, CUPS_MEDIA_A3, CUPS_MEDIA_A4, CUPS_MEDIA_A5,
-    CUPS_MEDIA_A6, CUPS_MEDIA_ENV10, CUPS_MEDIA_ENVDL, CUPS_MEDIA_LEGAL, CUPS_MEDIA_LETTER,
-    CUPS_MEDIA_PHOTO_L, CUPS_MEDIA_SUPERBA3, CUPS_MEDIA_TABLOID,
+use crate::ipp::template::{document_format, job_template};
+use crate::ipp::{Attribute, GroupTag, Operation, Value};
+use crate::linux::options::{FINISHINGS, PRINT_COLOR_MODE};
+use crate::media::{self, MediaSize};
+use crate::{
+    summary_status, Capabilities, Conflict, JobInfo, JobParam, JobStatus, Length, PrintError,
+    PrinterState, PrinterStatus, Reason, StateReason, Status, WhichJobs,
 };
 
-use crate::{JobParam, PrintError, Status};
+#[cfg(feature = "cups")]
+mod cups;
+#[cfg(not(feature = "cups"))]
+mod cupsd;
+#[cfg(feature = "cups")]
+mod ffi;
+pub(crate) mod options;
+
+#[cfg(feature = "cups")]
+use cups::ipp_request;
+#[cfg(feature = "cups")]
+pub use cups::{
+    default_printer, list_printers, list_printers_on, printer_attr, printer_attr_on, LinuxPrintJob,
+};
+#[cfg(not(feature = "cups"))]
+use cupsd::ipp_request;
+#[cfg(not(feature = "cups"))]
+pub use cupsd::{
+    default_printer, list_printers, list_printers_on, printer_attr, printer_attr_on, LinuxPrintJob,
+};
 
-impl PrintError {
-    pub(crate) fn io_error(e: PrintError) -> std::io::Error {
-        std::io::Error::new(ErrorKind::Other, e)
-    }
+/// Encryption of the connection to the cups server.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Encryption {
+    Never,
+    /// Upgrade to TLS if the server asks for it.
+    #[default]
+    IfRequested,
+    Required,
+}
 
-    pub(crate) fn last_io_error() -> std::io::Error {
-        std::io::Error::new(ErrorKind::Other, PrintError::last_error())
-    }
+/// A cups server other than the default one.
+///
+/// Without libcups there is no TLS, Encryption::Required fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CupsConnection {
+    pub host: String,
+    pub port: u16,
+    pub encryption: Encryption,
+}
 
-    /// Fetch the last error.
-    pub(crate) fn last_error() -> Self {
-        unsafe {
-            let e = CStr::from_ptr(cupsLastErrorString());
-            PrintError::Print(e.to_string_lossy().to_string())
+impl CupsConnection {
+    pub fn new(host: &str, port: u16, encryption: Encryption) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            encryption,
         }
     }
 }
 
-/// Default printer.
-pub fn default_printer() -> std::io::Result<String> {
-    unsafe {
-        let mut cups_dest: *mut cups_dest_t = ptr::null_mut::<cups_dest_t>();
-        let pcups_dest = (&mut cups_dest) as *mut *mut cups_dest_t;
+/// Status of the printer, via Get-Printer-Attributes.
+pub fn printer_status(pr_name: &str) -> std::io::Result<PrinterStatus> {
+    let res = ipp_request(None, pr_name, Operation::GetPrinterAttributes, |req| {
+        req.add(
+            GroupTag::Operation,
+            requested_attributes(PRINTER_STATUS_ATTRIBUTES),
+        );
+    })?;
 
-        let n_dests = cupsGetDests(pcups_dest);
+    let attr = res
+        .group(GroupTag::Printer)
+        .map(|v| v.attributes.as_slice())
+        .unwrap_or_default();
+    Ok(ipp_printer_status(attr))
+}
 
-        for i in 0isize..n_dests as isize {
-            let cur_dest = cups_dest.offset(i);
+/// Supported values of the printer, via Get-Printer-Attributes.
+///
+/// With libcups this asks the printer the same way as cupsCopyDestInfo.
+pub fn capabilities(pr_name: &str) -> std::io::Result<Capabilities> {
+    server_capabilities(None, pr_name)
+}
 
-            if (*cur_dest).is_default == 1 {
-                let c_name = CStr::from_ptr((*cur_dest).name);
-                let name = c_name.to_string_lossy().to_string();
-                cupsFreeDests(n_dests, cups_dest);
-                return Ok(name);
-            }
-        }
+/// Capabilities from the given server or the default one.
+pub(crate) fn server_capabilities(
+    server: Option<&CupsConnection>,
+    pr_name: &str,
+) -> std::io::Result<Capabilities> {
+    let res = ipp_request(server, pr_name, Operation::GetPrinterAttributes, |req| {
+        req.add(
+            GroupTag::Operation,
+            requested_attributes(CAPABILITIES_ATTRIBUTES),
+        );
+    })?;
 
-        cupsFreeDests(n_dests, cups_dest);
+    let attr = res
+        .group(GroupTag::Printer)
+        .map(|v| v.attributes.as_slice())
+        .unwrap_or_default();
+    Ok(ipp_capabilities(attr))
+}
 
-        Err(PrintError::io_error(PrintError::NoDefaultPrinter))
+/// Asks the printer with Validate-Job if it takes the job parameters.
+/// Returns the unsupported or conflicting ones.
+pub fn validate_job(pr_name: &str, param: &JobParam) -> std::io::Result<Vec<Conflict>> {
+    let res = ipp_request(None, pr_name, Operation::ValidateJob, |req| {
+        validate_job_request(
+            req,
+            "validate",
+            document_format(&param.data_format),
+            &job_template(param),
+        );
+    });
+    let mut invalid = param.check_options();
+    match res {
+        Ok(res) => invalid.extend(conflicts(&res)),
+        Err(e) => invalid.extend(invalid_param(e)?),
     }
+    Ok(invalid)
+}
+
+/// Status of a printjob, via Get-Job-Attributes.
+pub fn job_status(pr_name: &str, job_id: u32) -> std::io::Result<JobStatus> {
+    server_job_status(None, pr_name, job_id)
+}
+
+/// Job status from the given server or the default one.
+pub(crate) fn server_job_status(
+    server: Option<&CupsConnection>,
+    pr_name: &str,
+    job_id: u32,
+) -> std::io::Result<JobStatus> {
+    let res = ipp_request(server, pr_name, Operation::GetJobAttributes, |req| {
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("job-id", Value::Integer(job_id as i32)),
+        );
+        req.add(
+            GroupTag::Operation,
+            requested_attributes(JOB_STATUS_ATTRIBUTES),
+        );
+    })?;
+
+    let attr = res
+        .group(GroupTag::Job)
+        .map(|v| v.attributes.as_slice())
+        .unwrap_or_default();
+    Ok(ipp_job_status(job_id as i32, attr))
+}
+
+/// Jobs of a printer, via Get-Jobs.
+pub fn list_jobs(pr_name: &str, which: WhichJobs) -> std::io::Result<Vec<JobInfo>> {
+    let res = ipp_request(None, pr_name, Operation::GetJobs, |req| {
+        get_jobs_request(req, which);
+    })?;
+    Ok(job_infos(&res))
+}
+
+/// Cancels a job.
+pub fn cancel_job(pr_name: &str, job_id: u32) -> std::io::Result<()> {
+    job_operation(pr_name, Operation::CancelJob, job_id)
+}
+
+/// Cancels all jobs of a printer. Needs an operator, unless all the jobs
+/// belong to the current user.
+pub fn cancel_all_jobs(pr_name: &str) -> std::io::Result<()> {
+    ipp_request(None, pr_name, Operation::CancelJobs, |_| {})?;
+    Ok(())
+}
+
+/// Holds a job until it's released.
+pub fn hold_job(pr_name: &str, job_id: u32) -> std::io::Result<()> {
+    job_operation(pr_name, Operation::HoldJob, job_id)
+}
+
+/// Releases a held job.
+pub fn release_job(pr_name: &str, job_id: u32) -> std::io::Result<()> {
+    job_operation(pr_name, Operation::ReleaseJob, job_id)
+}
+
+fn job_operation(pr_name: &str, operation: Operation, job_id: u32) -> std::io::Result<()> {
+    ipp_request(None, pr_name, operation, |req| {
+        req.add(
+            GroupTag::Operation,
+            Attribute::new("job-id", Value::Integer(job_id as i32)),
+        );
+    })?;
+    Ok(())
 }
 
 /// Data format.
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Format {
     #[default]
     Raw,
     Auto,
     Command,
     Jpeg,
+    Png,
     Pdf,
     Postscript,
     Text,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Finishings {
     Named(String),
     #[default]
@@ -99,7 +224,7 @@ pub enum Finishings {
     Trim,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum PaperSize {
     Named(String),
     Size3x5,
@@ -118,9 +243,33 @@ pub enum PaperSize {
     PhotoL,
     SuperBA3,
     Tabloid,
+    /// Width and height. Sent as custom_WxHmm, or as media-size in
+    /// a media-col via IPP, both in 1/100mm.
+    Custom {
+        width: Length,
+        height: Length,
+    },
 }
 
-#[derive(Default, Debug, Clone)]
+impl PaperSize {
+    /// The standard media size, if there is one.
+    pub fn media_size(&self) -> Option<&'static MediaSize> {
+        match self {
+            PaperSize::Custom { width, height } => media::by_size(*width, *height),
+            PaperSize::Named(v) => media::by_name(v).or_else(|| media::by_alias(v)),
+            v => media::by_name(&v.keyword()),
+        }
+    }
+}
+
+/// One of the named sizes, otherwise the PWG name.
+impl From<&MediaSize> for PaperSize {
+    fn from(media: &MediaSize) -> Self {
+        PaperSize::from_keyword(media.name)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum PaperSource {
     Named(String),
     #[default]
@@ -128,7 +277,7 @@ pub enum PaperSource {
     Manual,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum PaperType {
     Named(String),
     #[default]
@@ -143,7 +292,7 @@ pub enum PaperType {
     Transparency,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Orientation {
     Named(String),
     #[default]
@@ -151,7 +300,7 @@ pub enum Orientation {
     Landscape,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum ColorMode {
     Named(String),
     Auto,
@@ -160,7 +309,7 @@ pub enum ColorMode {
     Color,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Quality {
     Named(String),
     Draft,
@@ -169,7 +318,7 @@ pub enum Quality {
     High,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Duplex {
     Named(String),
     #[default]
@@ -178,14 +327,6 @@ pub enum Duplex {
     TwoSidedLandscape,
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PrinterState {
-    #[default]
-    Idle,
-    Printing,
-    Stopped,
-}
-
 /// Printer status and settings.
 ///
 /// <https://www.rfc-editor.org/rfc/rfc8011>
@@ -236,7 +377,11 @@ pub struct Info {
 
     pub printer_state: PrinterState,
     pub printer_state_change_time: u64,
+    /// Not in the options from libcups.
+    pub printer_state_message: String,
 
+    /// printer-state-reasons, without "none".
+    pub state_reasons: Vec<StateReason>,
     pub state_reason_none: bool,
     pub state_reason_other: bool,
     pub state_reason_developer_low: bool,
@@ -283,656 +428,190 @@ pub struct Info {
 }
 
 impl Info {
+    /// Maps the printer-state and the severity of the state reasons.
     pub fn status(&self) -> Status {
-        if self.printer_state == PrinterState::Stopped {
-            if self.state_reason_developer_low
-                || self.state_reason_door_open
-                || self.state_reason_fuser_over_temp
-                || self.state_reason_fuser_under_temp
-                || self.state_reason_interlock_open
-                || self.state_reason_marker_supply_low
-                || self.state_reason_waste_almost_full
-                || self.state_reason_media_low
-                || self.state_reason_media_needed
-                || self.state_reason_opc_near_eol
-                || self.state_reason_output_area_almost_full
-                || self.state_reason_toner_low
-                || self.state_reason_insecure_filter_warning
-                || self.state_reason_missing_filter_warning
-                || self.state_reason_waiting_for_job_completed
-            {
-                Status::Warn
-            } else if self.state_reason_toner_empty
-                || self.state_reason_input_tray_missing
-                || self.state_reason_interpreter_resource_unavailable
-                || self.state_reason_marker_supply_empty
-                || self.state_reason_waste_full
-                || self.state_reason_media_empty
-                || self.state_reason_media_jam
-                || self.state_reason_opc_life_over
-                || self.state_reason_output_area_full
-                || self.state_reason_output_tray_missing
-                || self.state_reason_spool_area_full
-                || self.state_reason_timed_out
-                || self.state_reason_toner_empty
-                || self.state_reason_connection_to_device
-            {
-                Status::Error
-            } else {
-                Status::Stopped
+        summary_status(self.printer_state, &self.state_reasons)
+    }
+
+    /// The common part of the status.
+    pub fn printer_status(&self) -> PrinterStatus {
+        PrinterStatus {
+            state: self.printer_state,
+            reasons: self.state_reasons.clone(),
+            accepting_jobs: self.printer_is_accepting_jobs,
+            changed: (self.printer_state_change_time > 0).then(|| {
+                SystemTime::UNIX_EPOCH + Duration::from_secs(self.printer_state_change_time)
+            }),
+            message: self.printer_state_message.clone(),
+        }
+    }
+
+    /// Sets the flag for a state reason.
+    fn set_state_reason(&mut self, reason: Reason) {
+        match reason {
+            Reason::None => self.state_reason_none = true,
+            Reason::Other => self.state_reason_other = true,
+            Reason::DeveloperLow => self.state_reason_developer_low = true,
+            Reason::DoorOpen => self.state_reason_door_open = true,
+            Reason::FuserOverTemp => self.state_reason_fuser_over_temp = true,
+            Reason::FuserUnderTemp => self.state_reason_fuser_under_temp = true,
+            Reason::InputTrayMissing => self.state_reason_input_tray_missing = true,
+            Reason::InterlockOpen => self.state_reason_interlock_open = true,
+            Reason::InterpreterResourceUnavailable => {
+                self.state_reason_interpreter_resource_unavailable = true
             }
-        } else if self.printer_state == PrinterState::Printing {
-            Status::Busy
-        } else {
-            Status::Idle
+            Reason::MarkerSupplyEmpty => self.state_reason_marker_supply_empty = true,
+            Reason::MarkerSupplyLow => self.state_reason_marker_supply_low = true,
+            Reason::MarkerWasteAlmostFull => self.state_reason_waste_almost_full = true,
+            Reason::MarkerWasteFull => self.state_reason_waste_full = true,
+            Reason::MediaEmpty => self.state_reason_media_empty = true,
+            Reason::MediaJam => self.state_reason_media_jam = true,
+            Reason::MediaLow => self.state_reason_media_low = true,
+            Reason::MediaNeeded => self.state_reason_media_needed = true,
+            Reason::MovingToPaused => self.state_reason_moving_to_paused = true,
+            Reason::OpcLifeOver => self.state_reason_opc_life_over = true,
+            Reason::OpcNearEol => self.state_reason_opc_near_eol = true,
+            Reason::OutputAreaAlmostFull => self.state_reason_output_area_almost_full = true,
+            Reason::OutputAreaFull => self.state_reason_output_area_full = true,
+            Reason::OutputTrayMissing => self.state_reason_output_tray_missing = true,
+            Reason::Paused => self.state_reason_paused = true,
+            Reason::Shutdown => self.state_reason_shutdown = true,
+            Reason::SpoolAreaFull => self.state_reason_spool_area_full = true,
+            Reason::StoppedPartly => self.state_reason_stopped_partly = true,
+            Reason::Stopping => self.state_reason_stopping = true,
+            Reason::TimedOut => self.state_reason_timed_out = true,
+            Reason::TonerEmpty => self.state_reason_toner_empty = true,
+            Reason::TonerLow => self.state_reason_toner_low = true,
+            Reason::ConnectingToDevice => self.state_reason_connection_to_device = true,
+            Reason::Offline => self.state_reason_offline_report = true,
+            Reason::InsecureFilter => self.state_reason_insecure_filter_warning = true,
+            Reason::MissingFilter => self.state_reason_missing_filter_warning = true,
+            Reason::RemoteAborted => self.state_reason_remote_aborted = true,
+            Reason::RemoteCanceled => self.state_reason_remote_canceled = true,
+            Reason::RemoteCompleted => self.state_reason_remote_completed = true,
+            Reason::RemotePending => self.state_reason_remote_pending = true,
+            Reason::RemotePendingHeld => self.state_reason_remote_pending_held = true,
+            Reason::RemoteProcessing => self.state_reason_remote_processing = true,
+            Reason::RemoteStopped => self.state_reason_remote_stopped = true,
+            Reason::WaitingForJobCompleted => self.state_reason_waiting_for_job_completed = true,
+            _ => {}
         }
     }
 }
 
-pub fn printer_attr(pr_name: &str) -> std::io::Result<Info> {
-    unsafe {
-        let cups_dest = cupsGetNamedDest(
-            ptr::null_mut::<http_t>(),
-            pr_name.as_ptr() as *const c_char,
-            ptr::null(),
-        );
-        if !cups_dest.is_null() {
-            let cups_dest = &mut *cups_dest;
-
-            let mut result = Info::default();
-
-            result.printer_name = CStr::from_ptr(cups_dest.name).to_string_lossy().to_string();
-            result.printer_instance = if !cups_dest.instance.is_null() {
-                Some(
-                    CStr::from_ptr(cups_dest.instance)
-                        .to_string_lossy()
-                        .to_string(),
-                )
-            } else {
-                None
-            };
-
-            result.printer_uri = find_option(
-                "printer-uri-supported",
-                cups_dest.num_options,
-                cups_dest.options,
-            );
-
-            result.device_uri = find_option("device-uri", cups_dest.num_options, cups_dest.options);
-
-            result.driver_name = find_option(
-                "printer-make-and-model",
-                cups_dest.num_options,
-                cups_dest.options,
-            );
-
-            result.printer_info =
-                find_option("printer-info", cups_dest.num_options, cups_dest.options);
-
-            result.printer_location =
-                find_option("printer-location", cups_dest.num_options, cups_dest.options);
-
-            result.job_priority =
-                find_num_option("job-priority", cups_dest.num_options, cups_dest.options)?;
-
-            result.job_cancel_after =
-                find_num_option("job-cancel-after", cups_dest.num_options, cups_dest.options)?;
-
-            result.job_hold_until =
-                find_option("job-hold-until", cups_dest.num_options, cups_dest.options);
-
-            result.job_sheets = find_option("job-sheets", cups_dest.num_options, cups_dest.options);
-
-            result.marker_change_time = find_num_option(
-                "marker-change-time",
-                cups_dest.num_options,
-                cups_dest.options,
-            )?;
-
-            let opt = find_option("print-color-mode", cups_dest.num_options, cups_dest.options);
-            result.print_color_mode = option_map(
-                &opt,
-                &[
-                    ("auto", ColorMode::Auto),
-                    ("monochrome", ColorMode::Monochrome),
-                    ("color", ColorMode::Color),
-                ],
-            );
-
-            result.copies = find_num_option("copies", cups_dest.num_options, cups_dest.options)?;
-
-            let opt = find_option("finishings", cups_dest.num_options, cups_dest.options);
-            result.finishings = option_map(
-                &opt,
-                &[
-                    ("3", Finishings::None),
-                    ("4", Finishings::Staple),
-                    ("5", Finishings::Punch),
-                    ("6", Finishings::Cover),
-                    ("7", Finishings::Bind),
-                    ("10", Finishings::Fold),
-                    ("11", Finishings::Trim),
-                ],
-            );
-
-            result.number_up =
-                find_num_option("number-up", cups_dest.num_options, cups_dest.options)?;
-
-            let opt = find_option(
-                "printer-is-accepting-jobs",
-                cups_dest.num_options,
-                cups_dest.options,
-            );
-            result.printer_is_accepting_jobs =
-                option_map(&opt, &[("true", true), ("false", false)]);
-
-            let opt = find_option(
-                "printer-is-shared",
-                cups_dest.num_options,
-                cups_dest.options,
-            );
-            result.printer_is_shared = option_map(&opt, &[("true", true), ("false", false)]);
-
-            let opt = find_option(
-                "printer-is-temporary",
-                cups_dest.num_options,
-                cups_dest.options,
-            );
-            result.printer_is_temporary = option_map(&opt, &[("true", true), ("false", false)]);
-
-            let opt = find_option("printer-state", cups_dest.num_options, cups_dest.options);
-            result.printer_state = option_map(
-                &opt,
-                &[
-                    ("3", PrinterState::Idle),
-                    ("4", PrinterState::Printing),
-                    ("5", PrinterState::Stopped),
-                ],
-            );
-
-            result.printer_state_change_time = find_num_option(
-                "printer-state-change-time",
-                cups_dest.num_options,
-                cups_dest.options,
-            )?;
-
-            let opt = find_option(
-                "printer-state-reasons",
-                cups_dest.num_options,
-                cups_dest.options,
-            );
-            match opt.as_str() {
-                "none" => result.state_reason_none = true,
-                "other" => result.state_reason_other = true,
-                "developer-low" => result.state_reason_developer_low = true,
-                "door-open" => result.state_reason_door_open = true,
-                "fuser-over-temp" => result.state_reason_fuser_over_temp = true,
-                "fuser-under-temp" => result.state_reason_fuser_under_temp = true,
-                "input-tray-missing" => result.state_reason_input_tray_missing = true,
-                "interlock-open" => result.state_reason_interlock_open = true,
-                "interpreter-resource-unavailable" => {
-                    result.state_reason_interpreter_resource_unavailable = true
-                }
-                "marker-supply-empty" => result.state_reason_marker_supply_empty = true,
-                "marker-supply-low" => result.state_reason_marker_supply_low = true,
-                "waste-almost-full" => result.state_reason_waste_almost_full = true,
-                "waste-full" => result.state_reason_waste_full = true,
-                "media-empty" => result.state_reason_media_empty = true,
-                "media-jam" => result.state_reason_media_jam = true,
-                "media-low" => result.state_reason_media_low = true,
-                "media-needed" => result.state_reason_media_needed = true,
-                "moving-to-paused" => result.state_reason_moving_to_paused = true,
-                "opc-life-over" => result.state_reason_opc_life_over = true,
-                "opc-near-eol" => result.state_reason_opc_near_eol = true,
-                "output-area-almost-full" => result.state_reason_output_area_almost_full = true,
-                "output-area-full" => result.state_reason_output_area_full = true,
-                "output-tray-missing" => result.state_reason_output_tray_missing = true,
-                "paused" => result.state_reason_paused = true,
-                "shutdown" => result.state_reason_shutdown = true,
-                "spool-area-full" => result.state_reason_spool_area_full = true,
-                "stopped-partly" => result.state_reason_stopped_partly = true,
-                "stopping" => result.state_reason_stopping = true,
-                "timed-out" => result.state_reason_timed_out = true,
-                "toner-empty" => result.state_reason_toner_empty = true,
-                "toner-low" => result.state_reason_toner_low = true,
-                "connection-to-device" => result.state_reason_connection_to_device = true,
-                "offline-report" => result.state_reason_offline_report = true,
-                "insecure-filter-warning" => result.state_reason_insecure_filter_warning = true,
-                "missing-filter-warning" => result.state_reason_missing_filter_warning = true,
-                "remote-aborted" => result.state_reason_remote_aborted = true,
-                "remote-canceled" => result.state_reason_remote_canceled = true,
-                "remote-completed" => result.state_reason_remote_completed = true,
-                "remote-pending" => result.state_reason_remote_pending = true,
-                "remote-pending-held" => result.state_reason_remote_pending_held = true,
-                "remote-processing" => result.state_reason_remote_processing = true,
-                "remote-stopped" => result.state_reason_remote_stopped = true,
-                "waiting-for-job-completed" => result.state_reason_waiting_for_job_completed = true,
-                _ => {}
-            }
+impl Info {
+    /// Fills the Info from the printer options as name/value pairs.
+    /// Multiple values are separated by ','.
+    #[allow(clippy::field_reassign_with_default)]
+    pub(crate) fn from_options(
+        name: String,
+        instance: Option<String>,
+        options: &[(String, String)],
+    ) -> std::io::Result<Info> {
+        let mut result = Info::default();
 
-            result.printer_type =
-                find_num_option("printer-type", cups_dest.num_options, cups_dest.options)?;
+        result.printer_name = name;
+        result.printer_instance = instance;
 
-            cupsFreeDests(1, cups_dest);
+        result.printer_uri = find_option("printer-uri-supported", options);
 
-            Ok(result)
-        } else {
-            Err(PrintError::io_error(PrintError::NotFound))
-        }
-    }
-}
+        result.device_uri = find_option("device-uri", options);
 
-// future: supported and more actual values
-//
-//            let dinfo = cupsCopyDestInfo(ptr::null_mut::<http_t>(), cups_dest as *mut cups_dest_t);
-//
-//            let copies = cupsCheckDestSupported(
-//                ptr::null_mut::<http_t>(),
-//                cups_dest as *mut cups_dest_t,
-//                dinfo,
-//                // CString::new("copies").expect("copies").as_bytes_with_nul().as_ptr() as *const c_char,
-//                CUPS_COPIES.as_ptr() as *const c_char,
-//                ptr::null(),
-//            ) != 0;
-//            println!("copies={}", copies);
-//
-//            let copies = cupsFindDestReady(
-//                ptr::null_mut::<http_t>(),
-//                cups_dest as *mut cups_dest_t,
-//                dinfo,
-//                CUPS_COPIES.as_ptr() as *const c_char,
-//            );
-//            let copies = ippGetInteger(copies, 0);
-//            println!("copies={}", copies);
-//
-//            cupsFreeDestInfo(dinfo);
+        result.driver_name = find_option("printer-make-and-model", options);
 
-/// Find a specific option.
-/// Assumes options are ordered by name.
-fn find_option(name: &str, n: i32, options: *const cups_option_t) -> String {
-    unsafe {
-        let options = &*slice_from_raw_parts(options, n as usize);
-
-        if let Ok(n) = options.binary_search_by(|v| {
-            let c_name = CStr::from_ptr(v.name);
-            c_name.to_bytes().cmp(name.as_bytes())
-        }) {
-            let c_val = CStr::from_ptr(options[n].value);
-            c_val.to_string_lossy().to_string()
-        } else {
-            String::default()
-        }
-    }
-}
+        result.printer_info = find_option("printer-info", options);
 
-/// Find a specific option and converts it to a number via parse().
-/// Assumes options are ordered by name.
-fn find_num_option<T>(
-    name: &str,
-    n: i32,
-    options: *const cups_option_t,
-) -> Result<T, std::io::Error>
-where
-    T: FromStr + Default,
-    <T as FromStr>::Err: Into<PrintError>,
-{
-    unsafe {
-        let options = &*slice_from_raw_parts(options, n as usize);
-
-        if let Ok(n) = options.binary_search_by(|v| {
-            let c_name = CStr::from_ptr(v.name);
-            c_name.to_bytes().cmp(name.as_bytes())
-        }) {
-            let c_val = CStr::from_ptr(options[n].value);
-            match (*c_val).to_string_lossy().parse() {
-                Ok(v) => Ok(v),
-                Err(e) => Err(PrintError::io_error(e.into())),
-            }
-        } else {
-            Ok(T::default())
-        }
-    }
-}
+        result.printer_location = find_option("printer-location", options);
 
-/// Find a specific option and map it to some T.
-/// The first value is treated as default value.
-fn option_map<T: Clone>(s: &str, opt: &[(&str, T)]) -> T {
-    for v in opt {
-        if s == v.0 {
-            return v.1.clone();
-        }
-    }
-    opt[0].1.clone() // todo: is this ok?
-}
+        result.job_priority = find_num_option("job-priority", options)?;
 
-/// List installed printers.
-pub fn list_printers() -> std::io::Result<Vec<String>> {
-    let mut r = Vec::new();
+        result.job_cancel_after = find_num_option("job-cancel-after", options)?;
 
-    unsafe {
-        let mut cups_dest: *mut cups_dest_t = ptr::null_mut::<cups_dest_t>();
-        let pcups_dest = (&mut cups_dest) as *mut *mut cups_dest_t;
+        result.job_hold_until = find_option("job-hold-until", options);
 
-        let n_dests = cupsGetDests(pcups_dest);
+        result.job_sheets = find_option("job-sheets", options);
 
-        for i in 0isize..n_dests as isize {
-            let cur_dest = cups_dest.offset(i);
+        result.marker_change_time = find_num_option("marker-change-time", options)?;
 
-            let c_name = CStr::from_ptr((*cur_dest).name);
-            r.push(String::from_utf8_lossy(c_name.to_bytes()).to_string());
-        }
+        let opt = find_option("print-color-mode", options);
+        result.print_color_mode = PRINT_COLOR_MODE.parse(&opt);
 
-        cupsFreeDests(n_dests, cups_dest);
-    }
+        result.copies = find_num_option("copies", options)?;
 
-    Ok(r)
-}
-
-/// Printjob data.
-#[derive(Clone, Debug)]
-pub struct LinuxPrintJob {
-    pr_name: CString,
-    doc_name: CString,
-    job_id: c_int,
-}
-
-impl Write for LinuxPrintJob {
-    /// Write bytes to the printer.
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        unsafe {
-            if cupsWriteRequestData(
-                ptr::null_mut::<http_t>(),
-                buf.as_ptr() as *const c_char,
-                buf.len(),
-            ) != HTTP_STATUS_CONTINUE
-            {
-                Err(PrintError::last_io_error())
-            } else {
-                Ok(buf.len())
-            }
-        }
-    }
+        let opt = find_option("finishings", options);
+        result.finishings = FINISHINGS.parse(&opt);
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        Ok(())
-    }
-}
+        result.number_up = find_num_option("number-up", options)?;
 
-impl Drop for LinuxPrintJob {
-    /// Closes the printjob and sends it to the printer.
-    /// Any error is eaten. Use close() directly for error-handling.
-    fn drop(&mut self) {
-        let _ = self.close();
-    }
-}
+        let opt = find_option("printer-is-accepting-jobs", options);
+        result.printer_is_accepting_jobs = option_map(&opt, &[("true", true), ("false", false)]);
 
-impl LinuxPrintJob {
-    /// Starts a printjob.
-    pub fn new(pr_name: &str, doc_name: &str) -> std::io::Result<Self> {
-        Self::new_with(pr_name, doc_name, &JobParam::default())
-    }
+        let opt = find_option("printer-is-shared", options);
+        result.printer_is_shared = option_map(&opt, &[("true", true), ("false", false)]);
 
-    pub fn new_with(pr_name: &str, doc_name: &str, param: &JobParam) -> std::io::Result<Self> {
-        let mut job = LinuxPrintJob {
-            pr_name: CString::new(pr_name)?,
-            doc_name: CString::new(doc_name)?,
-            job_id: 0,
-        };
-
-        unsafe {
-            let (options, num_options) = Self::create_options(param)?;
-
-            job.job_id = cupsCreateJob(
-                ptr::null_mut::<http_t>(),
-                job.pr_name.as_ptr().cast(),
-                job.doc_name.as_ptr().cast(),
-                num_options,
-                options,
-            );
-            if job.job_id == 0 {
-                return Err(PrintError::last_io_error());
-            }
+        let opt = find_option("printer-is-temporary", options);
+        result.printer_is_temporary = option_map(&opt, &[("true", true), ("false", false)]);
 
-            let format = match param.data_format {
-                Format::Raw => CUPS_FORMAT_RAW.as_ptr(),
-                Format::Auto => CUPS_FORMAT_AUTO.as_ptr(),
-                Format::Command => CUPS_FORMAT_COMMAND.as_ptr(),
-                Format::Jpeg => CUPS_FORMAT_JPEG.as_ptr(),
-                Format::Pdf => CUPS_FORMAT_PDF.as_ptr(),
-                Format::Postscript => CUPS_FORMAT_POSTSCRIPT.as_ptr(),
-                Format::Text => CUPS_FORMAT_TEXT.as_ptr(),
-            };
-
-            if cupsStartDocument(
-                ptr::null_mut::<http_t>(),
-                job.pr_name.as_ptr().cast(),
-                job.job_id,
-                job.doc_name.as_ptr().cast(),
-                format as *const c_char,
-                1,
-            ) != HTTP_STATUS_CONTINUE
-            {
-                Err(PrintError::last_io_error())
-            } else {
-                Ok(job)
-            }
-        }
-    }
+        let opt = find_option("printer-state", options);
+        result.printer_state = option_map(
+            &opt,
+            &[
+                ("3", PrinterState::Idle),
+                ("4", PrinterState::Printing),
+                ("5", PrinterState::Stopped),
+            ],
+        );
 
-    fn create_options(param: &JobParam) -> std::io::Result<(*mut cups_option_t, i32)> {
-        let mut options = ptr::null_mut::<cups_option_t>();
-        let p_options = (&mut options) as *mut *mut cups_option_t;
-        let mut num_options = 0;
-
-        unsafe {
-            if let Some(copies) = param.copies {
-                let copies = CString::new(copies.to_string())?;
-                num_options = cupsAddOption(
-                    CUPS_COPIES.as_ptr() as *const c_char,
-                    copies.as_ptr(),
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(finishings) = param.finishings.clone() {
-                let named;
-                let finishings = match finishings {
-                    Finishings::Named(v) => {
-                        named = CString::new(v)?;
-                        named.as_ptr() as *const u8
-                    }
-                    Finishings::None => CUPS_FINISHINGS_NONE.as_ptr(),
-                    Finishings::Staple => CUPS_FINISHINGS_STAPLE.as_ptr(),
-                    Finishings::Punch => CUPS_FINISHINGS_PUNCH.as_ptr(),
-                    Finishings::Cover => CUPS_FINISHINGS_COVER.as_ptr(),
-                    Finishings::Bind => CUPS_FINISHINGS_BIND.as_ptr(),
-                    Finishings::Fold => CUPS_FINISHINGS_FOLD.as_ptr(),
-                    Finishings::Trim => CUPS_FINISHINGS_TRIM.as_ptr(),
-                };
-                num_options = cupsAddOption(
-                    CUPS_FINISHINGS.as_ptr() as *const c_char,
-                    finishings as *const c_char,
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(paper_size) = param.paper_size.clone() {
-                let named;
-                let paper_size = match paper_size {
-                    PaperSize::Named(v) => {
-                        named = CString::new(v)?;
-                        named.as_ptr() as *const u8
-                    }
-                    PaperSize::Size3x5 => CUPS_MEDIA_3X5.as_ptr(),
-                    PaperSize::Size4x6 => CUPS_MEDIA_4X6.as_ptr(),
-                    PaperSize::Size5x7 => CUPS_MEDIA_5X7.as_ptr(),
-                    PaperSize::Size8x10 => CUPS_MEDIA_8X10.as_ptr(),
-                    PaperSize::A3 => CUPS_MEDIA_A3.as_ptr(),
-                    PaperSize::A4 => CUPS_MEDIA_A4.as_ptr(),
-                    PaperSize::A5 => CUPS_MEDIA_A5.as_ptr(),
-                    PaperSize::A6 => CUPS_MEDIA_A6.as_ptr(),
-                    PaperSize::Env10 => CUPS_MEDIA_ENV10.as_ptr(),
-                    PaperSize::EnvDl => CUPS_MEDIA_ENVDL.as_ptr(),
-                    PaperSize::Legal => CUPS_MEDIA_LEGAL.as_ptr(),
-                    PaperSize::Letter => CUPS_MEDIA_LETTER.as_ptr(),
-                    PaperSize::PhotoL => CUPS_MEDIA_PHOTO_L.as_ptr(),
-                    PaperSize::SuperBA3 => CUPS_MEDIA_SUPERBA3.as_ptr(),
-                    PaperSize::Tabloid => CUPS_MEDIA_TABLOID.as_ptr(),
-                };
-                num_options = cupsAddOption(
-                    CUPS_MEDIA.as_ptr() as *const c_char,
-                    paper_size as *const c_char,
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(paper_source) = param.paper_source.clone() {
-                let named;
-                let paper_source = match paper_source {
-                    PaperSource::Named(v) => {
-                        named = CString::new(v)?;
-                        named.as_ptr() as *const u8
-                    }
-                    PaperSource::Auto => CUPS_MEDIA_SOURCE_AUTO.as_ptr(),
-                    PaperSource::Manual => CUPS_MEDIA_SOURCE_MANUAL.as_ptr(),
-                };
-                num_options = cupsAddOption(
-                    CUPS_MEDIA_SOURCE.as_ptr() as *const c_char,
-                    paper_source as *const c_char,
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(paper_type) = param.paper_type.clone() {
-                let named;
-                let paper_type = match paper_type {
-                    PaperType::Named(v) => {
-                        named = CString::new(v)?;
-                        named.as_ptr() as *const u8
-                    }
-                    PaperType::Auto => CUPS_MEDIA_TYPE_AUTO.as_ptr(),
-                    PaperType::Envelope => CUPS_MEDIA_TYPE_ENVELOPE.as_ptr(),
-                    PaperType::Labels => CUPS_MEDIA_TYPE_LABELS.as_ptr(),
-                    PaperType::Letterhead => CUPS_MEDIA_TYPE_LETTERHEAD.as_ptr(),
-                    PaperType::Photo => CUPS_MEDIA_TYPE_PHOTO.as_ptr(),
-                    PaperType::PhotoGlossy => CUPS_MEDIA_TYPE_PHOTO_GLOSSY.as_ptr(),
-                    PaperType::PhotoMatte => CUPS_MEDIA_TYPE_PHOTO_MATTE.as_ptr(),
-                    PaperType::Plain => CUPS_MEDIA_TYPE_PLAIN.as_ptr(),
-                    PaperType::Transparency => CUPS_MEDIA_TYPE_TRANSPARENCY.as_ptr(),
-                };
-                num_options = cupsAddOption(
-                    CUPS_MEDIA_TYPE.as_ptr() as *const c_char,
-                    paper_type as *const c_char,
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(number_up) = param.number_up {
-                let number_up = CString::new(number_up.to_string())?;
-                num_options = cupsAddOption(
-                    CUPS_NUMBER_UP.as_ptr() as *const c_char,
-                    number_up.as_ptr(),
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(orientation) = param.orientation.clone() {
-                let named;
-                let orientation = match orientation {
-                    Orientation::Named(v) => {
-                        named = CString::new(v)?;
-                        named.as_ptr() as *const u8
-                    }
-                    Orientation::Portrait => CUPS_ORIENTATION_LANDSCAPE.as_ptr(),
-                    Orientation::Landscape => CUPS_ORIENTATION_LANDSCAPE.as_ptr(),
-                };
-                num_options = cupsAddOption(
-                    CUPS_ORIENTATION.as_ptr() as *const c_char,
-                    orientation as *const c_char,
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(color) = param.color.clone() {
-                let named;
-                let color = match color {
-                    ColorMode::Named(v) => {
-                        named = CString::new(v)?;
-                        named.as_ptr() as *const u8
-                    }
-                    ColorMode::Auto => CUPS_PRINT_COLOR_MODE_AUTO.as_ptr(),
-                    ColorMode::Monochrome => CUPS_PRINT_COLOR_MODE_COLOR.as_ptr(),
-                    ColorMode::Color => CUPS_PRINT_COLOR_MODE_MONOCHROME.as_ptr(),
-                };
-                num_options = cupsAddOption(
-                    CUPS_PRINT_COLOR_MODE.as_ptr() as *const c_char,
-                    color as *const c_char,
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(quality) = param.quality.clone() {
-                let named;
-                let quality = match quality {
-                    Quality::Named(v) => {
-                        named = CString::new(v)?;
-                        named.as_ptr() as *const u8
-                    }
-                    Quality::Draft => CUPS_PRINT_QUALITY_DRAFT.as_ptr(),
-                    Quality::Normal => CUPS_PRINT_QUALITY_NORMAL.as_ptr(),
-                    Quality::High => CUPS_PRINT_QUALITY_HIGH.as_ptr(),
-                };
-                num_options = cupsAddOption(
-                    CUPS_PRINT_QUALITY.as_ptr() as *const c_char,
-                    quality as *const c_char,
-                    num_options,
-                    p_options,
-                );
-            }
-            if let Some(duplex) = param.duplex.clone() {
-                let named;
-                let duplex = match duplex {
-                    Duplex::Named(v) => {
-                        named = CString::new(v)?;
-                        named.as_ptr() as *const u8
-                    }
-                    Duplex::Simplex => CUPS_SIDES_ONE_SIDED.as_ptr(),
-                    Duplex::TwoSidedPortrait => CUPS_SIDES_TWO_SIDED_PORTRAIT.as_ptr(),
-                    Duplex::TwoSidedLandscape => CUPS_SIDES_TWO_SIDED_LANDSCAPE.as_ptr(),
-                };
-                num_options = cupsAddOption(
-                    CUPS_SIDES.as_ptr() as *const c_char,
-                    duplex as *const c_char,
-                    num_options,
-                    p_options,
-                );
-            }
+        result.printer_state_change_time = find_num_option("printer-state-change-time", options)?;
+        result.printer_state_message = find_option("printer-state-message", options);
+
+        let opt = find_option("printer-state-reasons", options);
+        if opt == "none" {
+            result.state_reason_none = true;
+        }
+        let state_reasons = StateReason::parse_list(&opt);
+        for reason in &state_reasons {
+            result.set_state_reason(reason.reason);
         }
+        result.state_reasons = state_reasons;
 
-        Ok((options, num_options))
-    }
+        result.printer_type = find_num_option("printer-type", options)?;
 
-    /// Close the printjob.
-    pub fn close(&mut self) -> Result<(), std::io::Error> {
-        unsafe {
-            if cupsFinishDocument(ptr::null_mut::<http_t>(), self.pr_name.as_ptr().cast())
-                == IPP_STATUS_OK
-            {
-                Ok(())
-            } else {
-                Err(PrintError::last_io_error())
-            }
-        }
+        Ok(result)
     }
+}
 
-    /// Start a new page. More a hint to the spooling system, wherever it
-    /// displays a page count.
-    pub fn start_page(&self) -> Result<(), std::io::Error> {
-        Ok(())
+/// Find a specific option.
+fn find_option(name: &str, options: &[(String, String)]) -> String {
+    options
+        .iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.clone())
+        .unwrap_or_default()
+}
+
+/// Find a specific option and converts it to a number via parse().
+fn find_num_option<T>(name: &str, options: &[(String, String)]) -> Result<T, std::io::Error>
+where
+    T: FromStr + Default,
+    <T as FromStr>::Err: Into<PrintError>,
+{
+    match options.iter().find(|(k, _)| k == name) {
+        Some((_, v)) => match v.parse() {
+            Ok(v) => Ok(v),
+            Err(e) => Err(PrintError::io_error(e.into())),
+        },
+        None => Ok(T::default()),
     }
+}
 
-    /// End a page.
-    pub fn end_page(&self) -> Result<(), std::io::Error> {
-        Ok(())
+/// Find a specific option and map it to some T.
+/// The first value is treated as default value.
+fn option_map<T: Clone>(s: &str, opt: &[(&str, T)]) -> T {
+    for v in opt {
+        if s == v.0 {
+            return v.1.clone();
+        }
     }
+    opt[0].1.clone() // todo: is this ok?
 }
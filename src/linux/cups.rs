@@ -0,0 +1,945 @@
+//!
+//! Printing with libcups.
+//!
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::io::Write;
+use std::ptr::{self, slice_from_raw_parts};
+use std::time::Duration;
+
+use crate::ipp::client::requested_attributes;
+use crate::ipp::http::{printer_path, Uri};
+use crate::ipp::{Attribute, GroupTag, Message, Operation, Value};
+use crate::linux::ffi::{
+    cupsAddOption, cupsCancelJob2, cupsConnectDest, cupsCreateJob, cupsFinishDocument,
+    cupsFreeDests, cupsFreeOptions, cupsGetDests, cupsGetDests2, cupsGetNamedDest, cupsLastError,
+    cupsLastErrorString, cupsSetPasswordCB2, cupsSetUser, cupsStartDocument, cupsWriteRequestData,
+    httpConnect2, CUPS_FORMAT_AUTO, CUPS_FORMAT_COMMAND, CUPS_FORMAT_JPEG, CUPS_FORMAT_PDF,
+    CUPS_FORMAT_PNG, CUPS_FORMAT_POSTSCRIPT, CUPS_FORMAT_TEXT, HTTP_ENCRYPTION_IF_REQUESTED,
+    HTTP_ENCRYPTION_NEVER, HTTP_ENCRYPTION_REQUIRED,
+};
+use crate::linux::ffi::{
+    cupsDoRequest, cupsUser, cups_dest_t, cups_option_t, httpClose, ippDelete, ippNew, ippReadIO,
+    ippSetState, ippWriteIO, CUPS_DEST_FLAGS_NONE, IPP_STATE_DATA, IPP_STATE_IDLE,
+};
+use crate::linux::ffi::{httpGetHostname, http_t, load, CUPS_FORMAT_RAW, HTTP_STATUS_CONTINUE};
+use crate::linux::ffi::{
+    IPP_STATUS_ERROR_CUPS_AUTHENTICATION_CANCELED, IPP_STATUS_ERROR_NOT_AUTHENTICATED,
+    IPP_STATUS_OK,
+};
+
+use crate::credentials::{credentials, PASSWORD_TRIES};
+use crate::linux::options::{
+    FINISHINGS, MEDIA, MEDIA_SOURCE, MEDIA_TYPE, ORIENTATION, PRINT_COLOR_MODE, PRINT_QUALITY,
+    SIDES,
+};
+use crate::linux::{
+    server_capabilities, server_job_status, CupsConnection, Encryption, Format, Info,
+};
+use crate::{wait_for_job, Backend, JobParam, Lifecycle, PrintError};
+
+impl PrintError {
+    /// Fetch the last error.
+    pub(crate) fn last_error() -> Self {
+        unsafe {
+            let status = cupsLastError();
+            if status == IPP_STATUS_ERROR_NOT_AUTHENTICATED
+                || status == IPP_STATUS_ERROR_CUPS_AUTHENTICATION_CANCELED
+            {
+                return PrintError::AuthenticationRequired;
+            }
+            let e = CStr::from_ptr(cupsLastErrorString());
+            PrintError::Print(e.to_string_lossy().to_string())
+        }
+    }
+}
+
+thread_local! {
+    static PASSWORD: RefCell<Option<CString>> = const { RefCell::new(None) };
+    static PASSWORD_CALLS: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Loads libcups and installs the password callback. The callback is
+/// kept per thread by libcups, so this runs before each operation. A
+/// printjob can move to another thread, each of its methods that talks
+/// to the server calls this too.
+fn init() -> std::io::Result<()> {
+    load()?;
+    PASSWORD_CALLS.set(0);
+    unsafe {
+        cupsSetPasswordCB2(Some(password_cb), ptr::null_mut());
+    }
+    Ok(())
+}
+
+/// Answers the password prompt of libcups with the credentials provider.
+/// Sets the user too, libcups takes the user name from cupsUser().
+unsafe extern "C" fn password_cb(
+    _prompt: *const c_char,
+    http: *mut http_t,
+    _method: *const c_char,
+    _resource: *const c_char,
+    _user_data: *mut c_void,
+) -> *const c_char {
+    if PASSWORD_CALLS.replace(PASSWORD_CALLS.get() + 1) >= PASSWORD_TRIES {
+        return ptr::null();
+    }
+
+    let mut host: [c_char; 256] = [0; 256];
+    // SAFETY: host has room for 256 bytes, http is the connection
+    // of the request or null for the local host.
+    let host = unsafe {
+        httpGetHostname(http, host.as_mut_ptr(), host.len() as c_int);
+        CStr::from_ptr(host.as_ptr()).to_string_lossy().to_string()
+    };
+
+    // a panic must not unwind into libcups.
+    let Ok(Some(cred)) = std::panic::catch_unwind(|| credentials(&host)) else {
+        return ptr::null();
+    };
+    let (Ok(user), Ok(password)) = (CString::new(cred.user), CString::new(cred.password)) else {
+        return ptr::null();
+    };
+    // SAFETY: libcups copies the user.
+    unsafe {
+        cupsSetUser(user.as_ptr());
+    }
+    // libcups copies the password before the next call on this thread.
+    PASSWORD.with_borrow_mut(|v| v.insert(password).as_ptr())
+}
+
+/// Default printer.
+pub fn default_printer() -> std::io::Result<String> {
+    init()?;
+
+    unsafe {
+        let mut cups_dest: *mut cups_dest_t = ptr::null_mut::<cups_dest_t>();
+        let pcups_dest = (&mut cups_dest) as *mut *mut cups_dest_t;
+
+        let n_dests = cupsGetDests(pcups_dest);
+
+        for i in 0isize..n_dests as isize {
+            let cur_dest = cups_dest.offset(i);
+
+            if (*cur_dest).is_default == 1 {
+                let c_name = CStr::from_ptr((*cur_dest).name);
+                let name = c_name.to_string_lossy().to_string();
+                cupsFreeDests(n_dests, cups_dest);
+                return Ok(name);
+            }
+        }
+
+        cupsFreeDests(n_dests, cups_dest);
+
+        Err(PrintError::io_error(PrintError::NoDefaultPrinter))
+    }
+}
+
+/// Printer status and settings.
+pub fn printer_attr(pr_name: &str) -> std::io::Result<Info> {
+    init()?;
+    unsafe { dest_attr(ptr::null_mut(), pr_name) }
+}
+
+/// Printer status and settings from the given server.
+pub fn printer_attr_on(server: &CupsConnection, pr_name: &str) -> std::io::Result<Info> {
+    let http = connect_server(server)?;
+    unsafe {
+        let res = dest_attr(http, pr_name);
+        httpClose(http);
+        res
+    }
+}
+
+/// The destination from the server of the connection, null is the
+/// default server.
+unsafe fn dest_attr(http: *mut http_t, pr_name: &str) -> std::io::Result<Info> {
+    let c_name = CString::new(pr_name)?;
+
+    unsafe {
+        let cups_dest = cupsGetNamedDest(http, c_name.as_ptr(), ptr::null());
+        if cups_dest.is_null() {
+            return Err(PrintError::io_error(PrintError::NotFound));
+        }
+
+        let name = CStr::from_ptr((*cups_dest).name)
+            .to_string_lossy()
+            .to_string();
+        let instance = if !(*cups_dest).instance.is_null() {
+            Some(
+                CStr::from_ptr((*cups_dest).instance)
+                    .to_string_lossy()
+                    .to_string(),
+            )
+        } else {
+            None
+        };
+
+        let options =
+            &*slice_from_raw_parts((*cups_dest).options, (*cups_dest).num_options as usize);
+        let options = options
+            .iter()
+            .map(|v| {
+                (
+                    CStr::from_ptr(v.name).to_string_lossy().to_string(),
+                    CStr::from_ptr(v.value).to_string_lossy().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        cupsFreeDests(1, cups_dest);
+
+        Info::from_options(name, instance, &options)
+    }
+}
+
+// future: supported and more actual values
+//
+//            let dinfo = cupsCopyDestInfo(ptr::null_mut::<http_t>(), cups_dest as *mut cups_dest_t);
+//
+//            let copies = cupsCheckDestSupported(
+//                ptr::null_mut::<http_t>(),
+//                cups_dest as *mut cups_dest_t,
+//                dinfo,
+//                // CString::new("copies").expect("copies").as_bytes_with_nul().as_ptr() as *const c_char,
+//                CUPS_COPIES.as_ptr() as *const c_char,
+//                ptr::null(),
+//            ) != 0;
+//            println!("copies={}", copies);
+//
+//            let copies = cupsFindDestReady(
+//                ptr::null_mut::<http_t>(),
+//                cups_dest as *mut cups_dest_t,
+//                dinfo,
+//                CUPS_COPIES.as_ptr() as *const c_char,
+//            );
+//            let copies = ippGetInteger(copies, 0);
+//            println!("copies={}", copies);
+//
+//            cupsFreeDestInfo(dinfo);
+
+/// List installed printers.
+pub fn list_printers() -> std::io::Result<Vec<String>> {
+    init()?;
+    unsafe { dest_names(ptr::null_mut()) }
+}
+
+/// List the printers of the given server.
+pub fn list_printers_on(server: &CupsConnection) -> std::io::Result<Vec<String>> {
+    let http = connect_server(server)?;
+    unsafe {
+        let res = dest_names(http);
+        httpClose(http);
+        res
+    }
+}
+
+/// Names of the destinations, null is the default server.
+unsafe fn dest_names(http: *mut http_t) -> std::io::Result<Vec<String>> {
+    let mut r = Vec::new();
+
+    unsafe {
+        let mut cups_dest: *mut cups_dest_t = ptr::null_mut::<cups_dest_t>();
+        let pcups_dest = (&mut cups_dest) as *mut *mut cups_dest_t;
+
+        let n_dests = if http.is_null() {
+            cupsGetDests(pcups_dest)
+        } else {
+            cupsGetDests2(http, pcups_dest)
+        };
+
+        for i in 0isize..n_dests as isize {
+            let cur_dest = cups_dest.offset(i);
+
+            let c_name = CStr::from_ptr((*cur_dest).name);
+            r.push(String::from_utf8_lossy(c_name.to_bytes()).to_string());
+        }
+
+        cupsFreeDests(n_dests, cups_dest);
+    }
+
+    Ok(r)
+}
+
+/// New connection to the server of the destination. The resource gets
+/// the path for requests.
+unsafe fn connect_dest(dest: *mut cups_dest_t, resource: &mut [c_char; 256]) -> *mut http_t {
+    unsafe {
+        cupsConnectDest(
+            dest,
+            CUPS_DEST_FLAGS_NONE,
+            30000,
+            ptr::null_mut(),
+            resource.as_mut_ptr(),
+            resource.len(),
+            None,
+            ptr::null_mut(),
+        )
+    }
+}
+
+/// New connection to the given server.
+fn connect_server(server: &CupsConnection) -> std::io::Result<*mut http_t> {
+    init()?;
+    let host = CString::new(server.host.as_str())?;
+    let encryption = match server.encryption {
+        Encryption::Never => HTTP_ENCRYPTION_NEVER,
+        Encryption::IfRequested => HTTP_ENCRYPTION_IF_REQUESTED,
+        Encryption::Required => HTTP_ENCRYPTION_REQUIRED,
+    };
+    unsafe {
+        let http = httpConnect2(
+            host.as_ptr(),
+            server.port as c_int,
+            ptr::null_mut(),
+            0,
+            encryption,
+            1,
+            30000,
+            ptr::null_mut(),
+        );
+        if http.is_null() {
+            Err(PrintError::io_error(PrintError::last_error()))
+        } else {
+            Ok(http)
+        }
+    }
+}
+
+/// Sends an IPP request for the printer via libcups.
+///
+/// The request gets printer-uri and requesting-user-name, the rest is
+/// added by the closure. The messages are converted with ippReadIO and
+/// ippWriteIO, so the same IPP code works with and without libcups.
+/// Fails if the response status is not successful.
+///
+/// Without a server the request goes to the printer-uri-supported of
+/// the destination.
+pub(crate) fn ipp_request(
+    server: Option<&CupsConnection>,
+    pr_name: &str,
+    operation: Operation,
+    build: impl FnOnce(&mut Message),
+) -> std::io::Result<Message> {
+    init()?;
+
+    let (http, printer_uri, resource) = if let Some(server) = server {
+        let path = printer_path(pr_name);
+        let printer_uri = Uri::new("ipp", &server.host, server.port, &path).to_string();
+        let resource = CString::new(path)?;
+        (connect_server(server)?, printer_uri, resource)
+    } else {
+        dest_request(pr_name)?
+    };
+
+    let mut req = Message::request(operation, 1);
+    req.add(
+        GroupTag::Operation,
+        Attribute::new("printer-uri", Value::Uri(printer_uri)),
+    );
+    let user = unsafe { CStr::from_ptr(cupsUser()).to_string_lossy().to_string() };
+    req.add(
+        GroupTag::Operation,
+        Attribute::new("requesting-user-name", Value::Name(user)),
+    );
+    build(&mut req);
+
+    let res = unsafe {
+        let res = do_request(http, resource.as_ptr(), &req);
+        httpClose(http);
+        res?
+    };
+
+    if res.status().is_success() {
+        Ok(res)
+    } else {
+        Err(crate::ipp::client::status_error(&res))
+    }
+}
+
+/// Connection to the printer-uri-supported of the destination, with
+/// the uri and the resource for the requests.
+fn dest_request(pr_name: &str) -> std::io::Result<(*mut http_t, String, CString)> {
+    let c_name = CString::new(pr_name)?;
+    let mut resource: [c_char; 256] = [0; 256];
+
+    unsafe {
+        let dest = cupsGetNamedDest(ptr::null_mut::<http_t>(), c_name.as_ptr(), ptr::null());
+        if dest.is_null() {
+            return Err(PrintError::io_error(PrintError::NotFound));
+        }
+        let options = &*slice_from_raw_parts((*dest).options, (*dest).num_options as usize);
+        let printer_uri = options
+            .iter()
+            .find(|v| CStr::from_ptr(v.name).to_bytes() == b"printer-uri-supported")
+            .map(|v| CStr::from_ptr(v.value).to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("ipp://localhost{}", printer_path(pr_name)));
+
+        let http = connect_dest(dest, &mut resource);
+        cupsFreeDests(1, dest);
+        if http.is_null() {
+            return Err(PrintError::io_error(PrintError::last_error()));
+        }
+        let resource = CStr::from_ptr(resource.as_ptr()).to_owned();
+        Ok((http, printer_uri, resource))
+    }
+}
+
+/// Source for ippReadIO.
+struct ReadBuf<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+unsafe extern "C" fn read_buf(ctx: *mut c_void, buffer: *mut u8, bytes: usize) -> isize {
+    // SAFETY: ctx is the ReadBuf given to ippReadIO, buffer has room
+    // for bytes.
+    unsafe {
+        let src = &mut *(ctx as *mut ReadBuf<'_>);
+        let n = bytes.min(src.buf.len() - src.pos);
+        ptr::copy_nonoverlapping(src.buf[src.pos..].as_ptr(), buffer, n);
+        src.pos += n;
+        n as isize
+    }
+}
+
+unsafe extern "C" fn write_buf(ctx: *mut c_void, buffer: *mut u8, bytes: usize) -> isize {
+    // SAFETY: ctx is the Vec given to ippWriteIO, buffer holds bytes.
+    unsafe {
+        let dst = &mut *(ctx as *mut Vec<u8>);
+        dst.extend_from_slice(&*slice_from_raw_parts(buffer, bytes));
+        bytes as isize
+    }
+}
+
+/// Converts the request to an ipp_t, sends it and converts the response
+/// back.
+unsafe fn do_request(
+    http: *mut http_t,
+    resource: *const c_char,
+    req: &Message,
+) -> std::io::Result<Message> {
+    let buf = req.encode()?;
+    let mut src = ReadBuf { buf: &buf, pos: 0 };
+
+    unsafe {
+        let ipp = ippNew();
+        if ippReadIO(
+            (&mut src as *mut ReadBuf<'_>).cast(),
+            Some(read_buf),
+            1,
+            ptr::null_mut(),
+            ipp,
+        ) != IPP_STATE_DATA
+        {
+            ippDelete(ipp);
+            return Err(PrintError::io_error(PrintError::Protocol(
+                "Invalid IPP request.".to_string(),
+            )));
+        }
+
+        // frees the request
+        let res = cupsDoRequest(http, ipp, resource);
+        if res.is_null() {
+            return Err(PrintError::io_error(PrintError::last_error()));
+        }
+
+        let mut dst = Vec::new();
+        ippSetState(res, IPP_STATE_IDLE);
+        let state = ippWriteIO(
+            (&mut dst as *mut Vec<u8>).cast(),
+            Some(write_buf),
+            1,
+            ptr::null_mut(),
+            res,
+        );
+        ippDelete(res);
+        if state != IPP_STATE_DATA {
+            return Err(PrintError::io_error(PrintError::Protocol(
+                "Invalid IPP response.".to_string(),
+            )));
+        }
+
+        Ok(Message::decode(&dst)?.0)
+    }
+}
+
+/// Printjob data.
+///
+/// Each printjob has its own connection to the server, so several
+/// printjobs can run in parallel, each one in any thread.
+#[derive(Debug)]
+pub struct LinuxPrintJob {
+    pr_name: CString,
+    doc_name: CString,
+    dest: *mut cups_dest_t,
+    http: *mut http_t,
+    /// None is the default server.
+    server: Option<CupsConnection>,
+    job_id: c_int,
+    /// Index of the current document.
+    document: usize,
+    /// JobParam::multi_document.
+    multi_document: bool,
+    lifecycle: Lifecycle,
+    last_error: Option<PrintError>,
+}
+
+// SAFETY: The connection and the destination are owned by the printjob
+// and never shared. The thread-local error state of libcups is read
+// directly after each call.
+unsafe impl Send for LinuxPrintJob {}
+
+impl Write for LinuxPrintJob {
+    /// Write bytes to the printer.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.lifecycle.is_done() || self.http.is_null() {
+            return Err(PrintError::io_error(PrintError::Closed));
+        }
+        init()?;
+        self.lifecycle = Lifecycle::Writing;
+        unsafe {
+            if cupsWriteRequestData(self.http, buf.as_ptr() as *const c_char, buf.len())
+                != HTTP_STATUS_CONTINUE
+            {
+                Err(self.error())
+            } else {
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LinuxPrintJob {
+    /// Aborts the printjob if it was not closed successfully.
+    /// Any error is eaten. Use close() directly for error-handling.
+    fn drop(&mut self) {
+        if !self.lifecycle.is_done() {
+            let _ = self.abort();
+        }
+        unsafe {
+            if !self.http.is_null() {
+                httpClose(self.http);
+            }
+            cupsFreeDests(1, self.dest);
+        }
+    }
+}
+
+impl LinuxPrintJob {
+    /// Starts a printjob.
+    pub fn new(pr_name: &str, doc_name: &str) -> std::io::Result<Self> {
+        Self::new_with(pr_name, doc_name, &JobParam::default())
+    }
+
+    pub fn new_with(pr_name: &str, doc_name: &str, param: &JobParam) -> std::io::Result<Self> {
+        Self::create(None, pr_name, doc_name, param)
+    }
+
+    /// Starts a printjob on the given server.
+    pub fn new_on(
+        server: &CupsConnection,
+        pr_name: &str,
+        doc_name: &str,
+        param: &JobParam,
+    ) -> std::io::Result<Self> {
+        Self::create(Some(server), pr_name, doc_name, param)
+    }
+
+    fn create(
+        server: Option<&CupsConnection>,
+        pr_name: &str,
+        doc_name: &str,
+        param: &JobParam,
+    ) -> std::io::Result<Self> {
+        init()?;
+
+        let param = param.prepare(|| server_capabilities(server, pr_name))?;
+        let pr_name = CString::new(pr_name)?;
+        let doc_name = CString::new(doc_name)?;
+
+        // the destination is looked up on the same connection.
+        let http = match server {
+            Some(server) => connect_server(server)?,
+            None => ptr::null_mut(),
+        };
+
+        unsafe {
+            let dest = cupsGetNamedDest(http, pr_name.as_ptr(), ptr::null());
+            if dest.is_null() {
+                if !http.is_null() {
+                    httpClose(http);
+                }
+                return Err(PrintError::io_error(PrintError::NotFound));
+            }
+
+            let mut job = LinuxPrintJob {
+                pr_name,
+                doc_name,
+                dest,
+                http,
+                server: server.cloned(),
+                job_id: 0,
+                document: 0,
+                multi_document: param.multi_document,
+                lifecycle: Lifecycle::Created,
+                last_error: None,
+            };
+
+            if job.http.is_null() {
+                job.http = job.connect()?;
+            }
+
+            let (options, num_options) = Self::create_options(&param)?;
+
+            job.job_id = cupsCreateJob(
+                job.http,
+                job.pr_name.as_ptr().cast(),
+                job.doc_name.as_ptr().cast(),
+                num_options,
+                options,
+            );
+            cupsFreeOptions(num_options, options);
+            if job.job_id == 0 {
+                return Err(job.error());
+            }
+
+            job.start_document(&param.data_format)?;
+            Ok(job)
+        }
+    }
+
+    /// Starts a document. A job with several documents sends them with
+    /// last_document = 0, close() ends the job.
+    fn start_document(&mut self, format: &Format) -> std::io::Result<()> {
+        self.send_document(format, !self.multi_document)
+    }
+
+    fn send_document(&mut self, format: &Format, last: bool) -> std::io::Result<()> {
+        let format = match format {
+            Format::Raw => CUPS_FORMAT_RAW.as_ptr(),
+            Format::Auto => CUPS_FORMAT_AUTO.as_ptr(),
+            Format::Command => CUPS_FORMAT_COMMAND.as_ptr(),
+            Format::Jpeg => CUPS_FORMAT_JPEG.as_ptr(),
+            Format::Png => CUPS_FORMAT_PNG.as_ptr(),
+            Format::Pdf => CUPS_FORMAT_PDF.as_ptr(),
+            Format::Postscript => CUPS_FORMAT_POSTSCRIPT.as_ptr(),
+            Format::Text => CUPS_FORMAT_TEXT.as_ptr(),
+        };
+        unsafe {
+            if cupsStartDocument(
+                self.http,
+                self.pr_name.as_ptr().cast(),
+                self.job_id,
+                self.doc_name.as_ptr().cast(),
+                format as *const c_char,
+                last as c_int,
+            ) != HTTP_STATUS_CONTINUE
+            {
+                Err(self.error())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Ends the current document.
+    fn finish_document(&mut self) -> std::io::Result<()> {
+        unsafe {
+            if cupsFinishDocument(self.http, self.pr_name.as_ptr().cast()) == IPP_STATUS_OK {
+                Ok(())
+            } else {
+                Err(self.error())
+            }
+        }
+    }
+
+    /// Error for the current document of a job with several documents.
+    fn document_error(&self, e: std::io::Error) -> std::io::Error {
+        let name = self.doc_name.to_string_lossy();
+        PrintError::document_error(self.document, &name, e)
+    }
+
+    /// Ends the current document and starts the next one in the same
+    /// job. Everything written from now on goes to the new document.
+    ///
+    /// The job must be created with JobParam::multi_document, otherwise
+    /// this fails with InvalidInput. Fails with PrintError::Document if
+    /// the current document is not accepted.
+    pub fn next_document(&mut self, doc_name: &str, format: Format) -> std::io::Result<()> {
+        if self.lifecycle.is_done() || self.http.is_null() {
+            return Err(PrintError::io_error(PrintError::Closed));
+        }
+        if !self.multi_document {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "next_document() needs JobParam::multi_document",
+            ));
+        }
+        init()?;
+        let doc_name = CString::new(doc_name)?;
+        self.finish_document().map_err(|e| self.document_error(e))?;
+        self.doc_name = doc_name;
+        self.document += 1;
+        self.start_document(&format)
+    }
+
+    /// New connection to the server of the destination.
+    fn connect(&mut self) -> std::io::Result<*mut http_t> {
+        init()?;
+        if let Some(server) = &self.server {
+            return connect_server(server);
+        }
+        let mut resource: [c_char; 256] = [0; 256];
+        unsafe {
+            let http = connect_dest(self.dest, &mut resource);
+            if http.is_null() {
+                Err(self.error())
+            } else {
+                Ok(http)
+            }
+        }
+    }
+
+    /// The job-id.
+    pub fn job_id(&self) -> u32 {
+        self.job_id as u32
+    }
+
+    /// Keeps the last error of libcups with the printjob.
+    fn error(&mut self) -> std::io::Error {
+        let e = PrintError::last_error();
+        self.last_error = Some(e.clone());
+        PrintError::io_error(e)
+    }
+
+    /// The last error of this printjob.
+    pub fn last_error(&self) -> Option<&PrintError> {
+        self.last_error.as_ref()
+    }
+
+    fn create_options(param: &JobParam) -> std::io::Result<(*mut cups_option_t, i32)> {
+        let mut values = Vec::new();
+        if let Some(copies) = param.copies {
+            values.push(("copies", Cow::Owned(copies.to_string())));
+        }
+        if let Some(finishings) = &param.finishings {
+            values.push((FINISHINGS.name, FINISHINGS.text(finishings)));
+        }
+        if let Some(paper_size) = &param.paper_size {
+            values.push((MEDIA.name, MEDIA.text(paper_size)));
+        }
+        if let Some(paper_source) = &param.paper_source {
+            values.push((MEDIA_SOURCE.name, MEDIA_SOURCE.text(paper_source)));
+        }
+        if let Some(paper_type) = &param.paper_type {
+            values.push((MEDIA_TYPE.name, MEDIA_TYPE.text(paper_type)));
+        }
+        if let Some(number_up) = param.number_up {
+            values.push(("number-up", Cow::Owned(number_up.to_string())));
+        }
+        if let Some(orientation) = &param.orientation {
+            values.push((ORIENTATION.name, ORIENTATION.text(orientation)));
+        }
+        if let Some(color) = &param.color {
+            values.push((PRINT_COLOR_MODE.name, PRINT_COLOR_MODE.text(color)));
+        }
+        if let Some(quality) = &param.quality {
+            values.push((PRINT_QUALITY.name, PRINT_QUALITY.text(quality)));
+        }
+        if let Some(duplex) = &param.duplex {
+            values.push((SIDES.name, SIDES.text(duplex)));
+        }
+        // cupsAddOption replaces an earlier value of the same name.
+        for (name, value) in &param.options {
+            values.push((name, Cow::Borrowed(value)));
+        }
+
+        // convert everything first, an error later would leak the options.
+        let values = values
+            .into_iter()
+            .map(|(name, value)| Ok((CString::new(name)?, CString::new(value.as_ref())?)))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        let mut options = ptr::null_mut::<cups_option_t>();
+        let p_options = (&mut options) as *mut *mut cups_option_t;
+        let mut num_options = 0;
+        for (name, value) in values {
+            unsafe {
+                num_options = cupsAddOption(name.as_ptr(), value.as_ptr(), num_options, p_options);
+            }
+        }
+
+        Ok((options, num_options))
+    }
+
+    /// Close the printjob.
+    pub fn close(&mut self) -> Result<(), std::io::Error> {
+        match self.lifecycle {
+            Lifecycle::Closed => return Ok(()),
+            Lifecycle::Aborted => return Err(PrintError::io_error(PrintError::Closed)),
+            _ => {}
+        }
+        if self.http.is_null() {
+            return Err(PrintError::io_error(PrintError::Closed));
+        }
+        init()?;
+        let result = match self.finish_document() {
+            Err(e) if self.document > 0 => Err(self.document_error(e)),
+            Ok(()) if self.multi_document => self.end_job(),
+            result => result,
+        };
+        if result.is_ok() {
+            self.lifecycle = Lifecycle::Closed;
+        }
+        unsafe {
+            httpClose(self.http);
+        }
+        self.http = ptr::null_mut();
+        result
+    }
+
+    /// Ends a job with several documents the same way as
+    /// cupsCloseDestJob. Close-Job if the printer supports it, otherwise
+    /// an empty document with last_document = 1.
+    fn end_job(&mut self) -> std::io::Result<()> {
+        if self.supports_close_job() {
+            let pr_name = self.pr_name.to_string_lossy();
+            let job_id = self.job_id;
+            ipp_request(self.server.as_ref(), &pr_name, Operation::CloseJob, |req| {
+                req.add(
+                    GroupTag::Operation,
+                    Attribute::new("job-id", Value::Integer(job_id)),
+                );
+            })?;
+            Ok(())
+        } else {
+            self.send_document(&Format::Auto, true)?;
+            self.finish_document()
+        }
+    }
+
+    /// Close-Job is in operations-supported. If the printer can't be
+    /// asked the empty last document still ends the job.
+    fn supports_close_job(&self) -> bool {
+        let pr_name = self.pr_name.to_string_lossy();
+        let Ok(res) = ipp_request(
+            self.server.as_ref(),
+            &pr_name,
+            Operation::GetPrinterAttributes,
+            |req| {
+                req.add(
+                    GroupTag::Operation,
+                    requested_attributes(&["operations-supported"]),
+                );
+            },
+        ) else {
+            return false;
+        };
+        res.attribute(GroupTag::Printer, "operations-supported")
+            .is_some_and(|v| {
+                v.values
+                    .iter()
+                    .any(|v| v.as_i32() == Some(Operation::CloseJob.code() as i32))
+            })
+    }
+
+    /// Waits until the job is printed.
+    ///
+    /// Fails with PrintError::JobFailed if the job was canceled or aborted,
+    /// and with PrintError::Timeout if it takes too long.
+    pub fn wait_for_completion(&self, timeout: Duration) -> std::io::Result<()> {
+        let pr_name = self.pr_name.to_string_lossy();
+        wait_for_job(timeout, || {
+            server_job_status(self.server.as_ref(), &pr_name, self.job_id as u32)
+        })
+    }
+
+    /// Closes the printjob and waits until it is printed.
+    pub fn close_and_wait(&mut self, timeout: Duration) -> std::io::Result<()> {
+        self.close()?;
+        self.wait_for_completion(timeout)
+    }
+
+    /// State of the printjob.
+    pub fn lifecycle(&self) -> Lifecycle {
+        self.lifecycle
+    }
+
+    /// Cancel the printjob.
+    ///
+    /// The document may still be open on the job's connection, so the
+    /// cancel goes out via a second connection. The job's connection is
+    /// dropped afterwards, which discards an unfinished document.
+    pub fn abort(&mut self) -> Result<(), std::io::Error> {
+        // job-id 0 would cancel the current job of the queue.
+        if self.job_id == 0 {
+            if !self.http.is_null() {
+                unsafe {
+                    httpClose(self.http);
+                }
+                self.http = ptr::null_mut();
+            }
+            self.lifecycle = Lifecycle::Aborted;
+            return Ok(());
+        }
+        unsafe {
+            let result = match self.connect() {
+                Ok(http) => {
+                    let status = cupsCancelJob2(http, self.pr_name.as_ptr().cast(), self.job_id, 0);
+                    let result = if status == IPP_STATUS_OK {
+                        Ok(())
+                    } else {
+                        Err(self.error())
+                    };
+                    httpClose(http);
+                    result
+                }
+                Err(e) => Err(e),
+            };
+
+            if !self.http.is_null() {
+                httpClose(self.http);
+                self.http = ptr::null_mut();
+            }
+            self.lifecycle = Lifecycle::Aborted;
+
+            result
+        }
+    }
+
+    /// Start a new page. More a hint to the spooling system, wherever it
+    /// displays a page count.
+    pub fn start_page(&self) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    /// End a page.
+    pub fn end_page(&self) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+}
+
+impl Backend for LinuxPrintJob {
+    fn start_page(&mut self) -> std::io::Result<()> {
+        LinuxPrintJob::start_page(self)
+    }
+
+    fn end_page(&mut self) -> std::io::Result<()> {
+        LinuxPrintJob::end_page(self)
+    }
+
+    fn close(&mut self) -> std::io::Result<()> {
+        LinuxPrintJob::close(self)
+    }
+
+    fn abort(&mut self) -> std::io::Result<()> {
+        LinuxPrintJob::abort(self)
+    }
+}
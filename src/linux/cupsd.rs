@@ -0,0 +1,361 @@
+//!
+//! Printing via IPP to cupsd, without libcups.
+//!
+//! The server is taken from CUPS_SERVER and defaults to localhost:631,
+//! or is given with a CupsConnection. Domain sockets are not supported.
+//!
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::backend::ipp::IppJob;
+use crate::ipp::http::{printer_path, Uri};
+use crate::ipp::{Attribute, GroupTag, IppClient, Message, Operation, StatusCode, Value};
+use crate::linux::{CupsConnection, Encryption, Info};
+use crate::{Backend, Format, JobParam, Lifecycle, PrintError};
+
+/// Uri of the cupsd. Without a connection CUPS_SERVER as host or
+/// host:port, or localhost.
+fn server_uri(server: Option<&CupsConnection>) -> String {
+    match server {
+        Some(server) => {
+            // ipps is rejected by the client, there is no TLS.
+            let scheme = match server.encryption {
+                Encryption::Required => "ipps",
+                Encryption::Never | Encryption::IfRequested => "ipp",
+            };
+            Uri::new(scheme, &server.host, server.port, "/").to_string()
+        }
+        None => match std::env::var("CUPS_SERVER") {
+            Ok(v) if !v.is_empty() && !v.starts_with('/') => format!("ipp://{}/", v),
+            _ => "ipp://localhost/".to_string(),
+        },
+    }
+}
+
+/// Uri for a printer name. Anything that looks like an uri is used as is.
+fn printer_uri(server: Option<&CupsConnection>, pr_name: &str) -> String {
+    if pr_name.contains("://") {
+        pr_name.to_string()
+    } else {
+        let uri = server_uri(server);
+        format!("{}{}", uri.trim_end_matches('/'), printer_path(pr_name))
+    }
+}
+
+/// Request for the server itself. These operations don't want a
+/// printer-uri.
+fn server_request(client: &IppClient, operation: Operation) -> Message {
+    let mut req = Message::request(operation, 1);
+    req.add(
+        GroupTag::Operation,
+        Attribute::new(
+            "requesting-user-name",
+            Value::Name(client.user().to_string()),
+        ),
+    );
+    req.add(
+        GroupTag::Operation,
+        Attribute::new(
+            "requested-attributes",
+            Value::Keyword("printer-name".to_string()),
+        ),
+    );
+    req
+}
+
+fn printer_name(attr: &[Attribute]) -> Option<String> {
+    attr.iter()
+        .find(|v| v.name == "printer-name")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_string())
+}
+
+/// Default printer.
+///
+/// Uses LPDEST or PRINTER if set, otherwise asks the server.
+pub fn default_printer() -> io::Result<String> {
+    for var in ["LPDEST", "PRINTER"] {
+        match std::env::var(var) {
+            Ok(v) if !v.is_empty() && v != "lp" => return Ok(v),
+            _ => {}
+        }
+    }
+
+    let client = IppClient::new(&server_uri(None))?;
+    let res = client.send_raw(&server_request(&client, Operation::CupsGetDefault), &[])?;
+    if !res.status().is_success() {
+        return Err(PrintError::io_error(PrintError::NoDefaultPrinter));
+    }
+
+    match res
+        .group(GroupTag::Printer)
+        .and_then(|v| printer_name(&v.attributes))
+    {
+        Some(v) => Ok(v),
+        None => Err(PrintError::io_error(PrintError::NoDefaultPrinter)),
+    }
+}
+
+/// Sends an IPP request for the printer to the cupsd.
+///
+/// The request gets printer-uri and requesting-user-name, the rest is
+/// added by the closure. Fails if the response status is not successful.
+pub(crate) fn ipp_request(
+    server: Option<&CupsConnection>,
+    pr_name: &str,
+    operation: Operation,
+    build: impl FnOnce(&mut Message),
+) -> io::Result<Message> {
+    let client = IppClient::new(&printer_uri(server, pr_name))?;
+    let mut req = client.request(operation);
+    build(&mut req);
+    client.send(&req, &[])
+}
+
+/// Printer status and settings.
+pub fn printer_attr(pr_name: &str) -> io::Result<Info> {
+    server_printer_attr(None, pr_name)
+}
+
+/// Printer status and settings from the given server.
+pub fn printer_attr_on(server: &CupsConnection, pr_name: &str) -> io::Result<Info> {
+    server_printer_attr(Some(server), pr_name)
+}
+
+fn server_printer_attr(server: Option<&CupsConnection>, pr_name: &str) -> io::Result<Info> {
+    let client = IppClient::new(&printer_uri(server, pr_name))?;
+    let attr = client.get_printer_attributes(&["all"])?;
+
+    let name = printer_name(&attr).unwrap_or_else(|| pr_name.to_string());
+    let options = attr
+        .iter()
+        .map(|v| {
+            // libcups strips the suffix for the defaults too
+            let name = v.name.strip_suffix("-default").unwrap_or(&v.name);
+            (name.to_string(), option_value(&v.values))
+        })
+        .collect::<Vec<_>>();
+
+    Info::from_options(name, None, &options)
+}
+
+/// Values as text, the same way libcups gives the options of a
+/// destination.
+fn option_value(values: &[Value]) -> String {
+    let mut buf = String::new();
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        match value {
+            Value::Integer(v) | Value::Enum(v) => buf.push_str(&v.to_string()),
+            Value::Boolean(v) => buf.push_str(if *v { "true" } else { "false" }),
+            Value::Range(lower, upper) => buf.push_str(&format!("{}-{}", lower, upper)),
+            Value::Resolution { x, y, units } => {
+                let units = if *units == crate::ipp::RESOLUTION_DPCM {
+                    "dpcm"
+                } else {
+                    "dpi"
+                };
+                if x == y {
+                    buf.push_str(&format!("{}{}", x, units));
+                } else {
+                    buf.push_str(&format!("{}x{}{}", x, y, units));
+                }
+            }
+            v => buf.push_str(v.as_str().unwrap_or_default()),
+        }
+    }
+    buf
+}
+
+/// List installed printers.
+pub fn list_printers() -> io::Result<Vec<String>> {
+    server_list_printers(None)
+}
+
+/// List the printers of the given server.
+pub fn list_printers_on(server: &CupsConnection) -> io::Result<Vec<String>> {
+    server_list_printers(Some(server))
+}
+
+fn server_list_printers(server: Option<&CupsConnection>) -> io::Result<Vec<String>> {
+    let client = IppClient::new(&server_uri(server))?;
+    let res = client.send_raw(&server_request(&client, Operation::CupsGetPrinters), &[])?;
+
+    // no printers at all is reported as not-found
+    if res.status() == StatusCode::CLIENT_ERROR_NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    if !res.status().is_success() {
+        return Err(crate::ipp::client::status_error(&res));
+    }
+
+    Ok(res
+        .groups(GroupTag::Printer)
+        .filter_map(|v| printer_name(&v.attributes))
+        .collect())
+}
+
+/// Printjob data.
+///
+/// The data is collected and sent on close().
+#[derive(Debug)]
+pub struct LinuxPrintJob {
+    job: IppJob,
+    /// JobParam::multi_document, the same as with libcups.
+    multi_document: bool,
+    last_error: Option<PrintError>,
+}
+
+impl Write for LinuxPrintJob {
+    /// Write bytes to the printer.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let res = self.job.write(buf);
+        self.keep_error(res)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.job.flush()
+    }
+}
+
+impl Drop for LinuxPrintJob {
+    /// Aborts the printjob if it was not closed successfully.
+    fn drop(&mut self) {
+        if !self.job.lifecycle().is_done() {
+            let _ = self.abort();
+        }
+    }
+}
+
+impl LinuxPrintJob {
+    /// Starts a printjob.
+    pub fn new(pr_name: &str, doc_name: &str) -> io::Result<Self> {
+        Self::new_with(pr_name, doc_name, &JobParam::default())
+    }
+
+    /// Starts a printjob. The printer is a queue name of the cupsd or an
+    /// ipp:// uri.
+    pub fn new_with(pr_name: &str, doc_name: &str, param: &JobParam) -> io::Result<Self> {
+        Ok(Self {
+            job: IppJob::new_with(&printer_uri(None, pr_name), doc_name, param)?,
+            multi_document: param.multi_document,
+            last_error: None,
+        })
+    }
+
+    /// Starts a printjob on the given server.
+    pub fn new_on(
+        server: &CupsConnection,
+        pr_name: &str,
+        doc_name: &str,
+        param: &JobParam,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            job: IppJob::new_with(&printer_uri(Some(server), pr_name), doc_name, param)?,
+            multi_document: param.multi_document,
+            last_error: None,
+        })
+    }
+
+    /// Ends the current document and starts the next one in the same
+    /// job.
+    ///
+    /// The job must be created with JobParam::multi_document, otherwise
+    /// this fails with InvalidInput.
+    pub fn next_document(&mut self, doc_name: &str, format: Format) -> io::Result<()> {
+        let res = if self.multi_document {
+            self.job.next_document(doc_name, format)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "next_document() needs JobParam::multi_document",
+            ))
+        };
+        self.keep_error(res)
+    }
+
+    /// Close the printjob.
+    pub fn close(&mut self) -> io::Result<()> {
+        let res = self.job.close();
+        self.keep_error(res)
+    }
+
+    /// Cancel the printjob. Nothing has been sent yet.
+    pub fn abort(&mut self) -> io::Result<()> {
+        let res = self.job.abort();
+        self.keep_error(res)
+    }
+
+    /// Keeps the error with the printjob.
+    fn keep_error<T>(&mut self, res: io::Result<T>) -> io::Result<T> {
+        if let Err(e) = &res {
+            let e = match e.get_ref().and_then(|v| v.downcast_ref::<PrintError>()) {
+                Some(v) => v.clone(),
+                None => PrintError::Print(e.to_string()),
+            };
+            self.last_error = Some(e);
+        }
+        res
+    }
+
+    /// The last error of this printjob.
+    pub fn last_error(&self) -> Option<&PrintError> {
+        self.last_error.as_ref()
+    }
+
+    /// The job-id. Only known after close().
+    pub fn job_id(&self) -> u32 {
+        self.job.job_id()
+    }
+
+    /// Waits until the job is printed.
+    ///
+    /// Fails with PrintError::JobFailed if the job was canceled or aborted,
+    /// and with PrintError::Timeout if it takes too long.
+    pub fn wait_for_completion(&self, timeout: Duration) -> io::Result<()> {
+        self.job.wait_for_completion(timeout)
+    }
+
+    /// Closes the printjob and waits until it is printed.
+    pub fn close_and_wait(&mut self, timeout: Duration) -> io::Result<()> {
+        self.job.close_and_wait(timeout)
+    }
+
+    /// State of the printjob.
+    pub fn lifecycle(&self) -> Lifecycle {
+        self.job.lifecycle()
+    }
+
+    /// Start a new page. More a hint to the spooling system, wherever it
+    /// displays a page count.
+    pub fn start_page(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// End a page.
+    pub fn end_page(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Backend for LinuxPrintJob {
+    fn start_page(&mut self) -> io::Result<()> {
+        LinuxPrintJob::start_page(self)
+    }
+
+    fn end_page(&mut self) -> io::Result<()> {
+        LinuxPrintJob::end_page(self)
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        LinuxPrintJob::close(self)
+    }
+
+    fn abort(&mut self) -> io::Result<()> {
+        LinuxPrintJob::abort(self)
+    }
+}
@@ -0,0 +1,199 @@
+//!
+//! The part of the libcups API used here.
+//!
+//! With feature `cups-dynamic` libcups.so.2 is loaded on first use,
+//! otherwise it is linked as usual. Either way load() must be called
+//! before any of the functions.
+//!
+//! This replaces cups-sys. cups-sys runs bindgen in its build script,
+//! which needs libclang and the cups headers even for a build that only
+//! loads libcups at runtime. The loader needs the list of functions
+//! anyway, so the same list is used for linking. Declare only what
+//! cups.rs calls, and copy the signatures from the libcups headers.
+//!
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::ffi::{c_char, c_int, c_uint, c_void};
+use std::io;
+
+#[repr(C)]
+#[derive(Debug)]
+pub(crate) struct cups_option_t {
+    pub(crate) name: *mut c_char,
+    pub(crate) value: *mut c_char,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub(crate) struct cups_dest_t {
+    pub(crate) name: *mut c_char,
+    pub(crate) instance: *mut c_char,
+    pub(crate) is_default: c_int,
+    pub(crate) num_options: c_int,
+    pub(crate) options: *mut cups_option_t,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub(crate) struct http_t {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub(crate) struct ipp_t {
+    _private: [u8; 0],
+}
+
+pub(crate) type http_status_t = c_int;
+pub(crate) type ipp_status_t = c_int;
+pub(crate) type ipp_state_t = c_int;
+pub(crate) type http_encryption_t = c_int;
+pub(crate) type ipp_iocb_t = Option<unsafe extern "C" fn(*mut c_void, *mut u8, usize) -> isize>;
+pub(crate) type cups_dest_cb_t =
+    Option<unsafe extern "C" fn(*mut c_void, c_uint, *mut cups_dest_t) -> c_int>;
+pub(crate) type cups_password_cb2_t = Option<
+    unsafe extern "C" fn(
+        *const c_char,
+        *mut http_t,
+        *const c_char,
+        *const c_char,
+        *mut c_void,
+    ) -> *const c_char,
+>;
+
+pub(crate) const CUPS_DEST_FLAGS_NONE: c_uint = 0;
+
+pub(crate) const HTTP_ENCRYPTION_IF_REQUESTED: http_encryption_t = 0;
+pub(crate) const HTTP_ENCRYPTION_NEVER: http_encryption_t = 1;
+pub(crate) const HTTP_ENCRYPTION_REQUIRED: http_encryption_t = 2;
+
+pub(crate) const HTTP_STATUS_CONTINUE: http_status_t = 100;
+pub(crate) const IPP_STATUS_OK: ipp_status_t = 0;
+pub(crate) const IPP_STATUS_ERROR_NOT_AUTHENTICATED: ipp_status_t = 0x402;
+pub(crate) const IPP_STATUS_ERROR_CUPS_AUTHENTICATION_CANCELED: ipp_status_t = 0x1000;
+pub(crate) const IPP_STATE_IDLE: ipp_state_t = 0;
+pub(crate) const IPP_STATE_DATA: ipp_state_t = 3;
+
+pub(crate) const CUPS_FORMAT_AUTO: &[u8; 25] = b"application/octet-stream\0";
+pub(crate) const CUPS_FORMAT_COMMAND: &[u8; 29] = b"application/vnd.cups-command\0";
+pub(crate) const CUPS_FORMAT_JPEG: &[u8; 11] = b"image/jpeg\0";
+pub(crate) const CUPS_FORMAT_PDF: &[u8; 16] = b"application/pdf\0";
+pub(crate) const CUPS_FORMAT_PNG: &[u8; 10] = b"image/png\0";
+pub(crate) const CUPS_FORMAT_POSTSCRIPT: &[u8; 23] = b"application/postscript\0";
+pub(crate) const CUPS_FORMAT_RAW: &[u8; 25] = b"application/vnd.cups-raw\0";
+pub(crate) const CUPS_FORMAT_TEXT: &[u8; 11] = b"text/plain\0";
+
+/// Declares the functions. Linked directly, or as wrappers around the
+/// function pointers from the loaded library.
+macro_rules! cups_functions {
+    ($(fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        #[cfg(not(feature = "cups-dynamic"))]
+        #[link(name = "cups")]
+        extern "C" {
+            $(pub(crate) fn $name($($arg: $ty),*) $(-> $ret)?;)*
+        }
+
+        #[cfg(feature = "cups-dynamic")]
+        struct Cups {
+            _lib: libloading::Library,
+            $($name: unsafe extern "C" fn($($ty),*) $(-> $ret)?,)*
+        }
+
+        #[cfg(feature = "cups-dynamic")]
+        impl Cups {
+            fn open() -> Result<Cups, String> {
+                let mut last_err = String::new();
+                for lib_name in LIB_NAMES {
+                    // SAFETY: libcups has no initialization code that could
+                    // cause trouble.
+                    match unsafe { libloading::Library::new(lib_name) } {
+                        Ok(lib) => {
+                            // SAFETY: The signatures match the libcups headers.
+                            unsafe {
+                                return Ok(Cups {
+                                    $($name: *lib
+                                        .get(concat!(stringify!($name), "\0").as_bytes())
+                                        .map_err(|e| e.to_string())?,)*
+                                    _lib: lib,
+                                });
+                            }
+                        }
+                        Err(e) => last_err = e.to_string(),
+                    }
+                }
+                Err(last_err)
+            }
+        }
+
+        $(
+            #[cfg(feature = "cups-dynamic")]
+            #[allow(clippy::too_many_arguments)]
+            pub(crate) unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                // SAFETY: Same contract as the C function.
+                unsafe { (cups().$name)($($arg),*) }
+            }
+        )*
+    };
+}
+
+cups_functions! {
+    fn cupsAddOption(name: *const c_char, value: *const c_char, num_options: c_int, options: *mut *mut cups_option_t) -> c_int;
+    fn cupsCancelJob2(http: *mut http_t, name: *const c_char, job_id: c_int, purge: c_int) -> ipp_status_t;
+    fn cupsConnectDest(dest: *mut cups_dest_t, flags: c_uint, msec: c_int, cancel: *mut c_int, resource: *mut c_char, resourcesize: usize, cb: cups_dest_cb_t, user_data: *mut c_void) -> *mut http_t;
+    fn cupsCreateJob(http: *mut http_t, name: *const c_char, title: *const c_char, num_options: c_int, options: *mut cups_option_t) -> c_int;
+    fn cupsDoRequest(http: *mut http_t, request: *mut ipp_t, resource: *const c_char) -> *mut ipp_t;
+    fn cupsFinishDocument(http: *mut http_t, name: *const c_char) -> ipp_status_t;
+    fn cupsFreeDests(num_dests: c_int, dests: *mut cups_dest_t);
+    fn cupsFreeOptions(num_options: c_int, options: *mut cups_option_t);
+    fn cupsGetDests(dests: *mut *mut cups_dest_t) -> c_int;
+    fn cupsGetDests2(http: *mut http_t, dests: *mut *mut cups_dest_t) -> c_int;
+    fn cupsGetNamedDest(http: *mut http_t, name: *const c_char, instance: *const c_char) -> *mut cups_dest_t;
+    fn cupsLastError() -> ipp_status_t;
+    fn cupsLastErrorString() -> *const c_char;
+    fn cupsSetPasswordCB2(cb: cups_password_cb2_t, user_data: *mut c_void);
+    fn cupsSetUser(user: *const c_char);
+    fn cupsStartDocument(http: *mut http_t, name: *const c_char, job_id: c_int, docname: *const c_char, format: *const c_char, last_document: c_int) -> http_status_t;
+    fn cupsUser() -> *const c_char;
+    fn cupsWriteRequestData(http: *mut http_t, buffer: *const c_char, length: usize) -> http_status_t;
+    fn httpClose(http: *mut http_t);
+    fn httpGetHostname(http: *mut http_t, s: *mut c_char, slen: c_int) -> *const c_char;
+    fn httpConnect2(host: *const c_char, port: c_int, addrlist: *mut c_void, family: c_int, encryption: http_encryption_t, blocking: c_int, msec: c_int, cancel: *mut c_int) -> *mut http_t;
+    fn ippDelete(ipp: *mut ipp_t);
+    fn ippNew() -> *mut ipp_t;
+    fn ippReadIO(src: *mut c_void, cb: ipp_iocb_t, blocking: c_int, parent: *mut ipp_t, ipp: *mut ipp_t) -> ipp_state_t;
+    fn ippSetState(ipp: *mut ipp_t, state: ipp_state_t) -> c_int;
+    fn ippWriteIO(dst: *mut c_void, cb: ipp_iocb_t, blocking: c_int, parent: *mut ipp_t, ipp: *mut ipp_t) -> ipp_state_t;
+}
+
+#[cfg(feature = "cups-dynamic")]
+const LIB_NAMES: [&str; 2] = ["libcups.so.2", "libcups.so"];
+
+#[cfg(feature = "cups-dynamic")]
+static CUPS: std::sync::OnceLock<Result<Cups, String>> = std::sync::OnceLock::new();
+
+#[cfg(feature = "cups-dynamic")]
+fn cups() -> &'static Cups {
+    match CUPS.get() {
+        Some(Ok(cups)) => cups,
+        _ => panic!("libcups used before load()"),
+    }
+}
+
+/// Loads libcups if necessary.
+#[cfg(feature = "cups-dynamic")]
+pub(crate) fn load() -> io::Result<()> {
+    match CUPS.get_or_init(Cups::open) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(crate::PrintError::io_error(crate::PrintError::Unavailable(
+            e.clone(),
+        ))),
+    }
+}
+
+/// Loads libcups if necessary.
+#[cfg(not(feature = "cups-dynamic"))]
+pub(crate) fn load() -> io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,281 @@
+//!
+//! One table per job option that maps the enum to the IPP keyword and,
+//! for enum attributes, the IPP enum value.
+//!
+//! Used for the libcups options, the job-template attributes and for
+//! reading the values back from the printer attributes.
+//!
+
+use std::borrow::Cow;
+
+use crate::ipp::capabilities::{pwg_custom_name, pwg_dimensions};
+use crate::ipp::Value;
+use crate::linux::{
+    ColorMode, Duplex, Finishings, Orientation, PaperSize, PaperSource, PaperType, Quality,
+};
+
+/// Mapping for one option.
+#[derive(Debug)]
+pub(crate) struct OptionMap<T: 'static> {
+    /// The IPP attribute name.
+    pub(crate) name: &'static str,
+    /// Value, keyword and the IPP enum value.
+    values: &'static [(T, &'static str, Option<i32>)],
+}
+
+/// The enums with a Named(String) variant for everything not in the table.
+pub(crate) trait NamedOption: Sized + Clone + PartialEq + Default {
+    fn named(&self) -> Option<Cow<'_, str>>;
+
+    fn from_named(v: &str) -> Self;
+}
+
+impl<T: NamedOption> OptionMap<T> {
+    /// IPP keyword. For enum attributes this is the name of the value.
+    pub(crate) fn keyword<'a>(&self, v: &'a T) -> Cow<'a, str> {
+        match v.named() {
+            Some(v) => v,
+            None => Cow::Borrowed(self.find(v).map(|v| v.1).unwrap_or_default()),
+        }
+    }
+
+    /// The value as libcups takes it with cupsAddOption.
+    /// Enum attributes are sent with their number.
+    #[cfg(feature = "cups")]
+    pub(crate) fn text<'a>(&self, v: &'a T) -> Cow<'a, str> {
+        match v.named() {
+            Some(v) => v,
+            None => match self.find(v) {
+                Some((_, _, Some(n))) => Cow::Owned(n.to_string()),
+                Some((_, k, None)) => Cow::Borrowed(k),
+                None => Cow::Borrowed(""),
+            },
+        }
+    }
+
+    /// The IPP attribute value. Named values are sent as enum if
+    /// they are numeric.
+    pub(crate) fn value(&self, v: &T) -> Value {
+        match v.named() {
+            Some(v) => match v.parse::<i32>() {
+                Ok(v) => Value::Enum(v),
+                Err(_) => Value::Keyword(v.to_string()),
+            },
+            None => match self.find(v) {
+                Some((_, _, Some(n))) => Value::Enum(*n),
+                Some((_, k, None)) => Value::Keyword(k.to_string()),
+                None => Value::Keyword(String::new()),
+            },
+        }
+    }
+
+    /// From a keyword or the enum value as text. An empty string
+    /// gives the default, anything unknown a Named value.
+    pub(crate) fn parse(&self, v: &str) -> T {
+        if v.is_empty() {
+            return T::default();
+        }
+        let found = match v.parse::<i32>() {
+            Ok(n) => self.values.iter().find(|w| w.2 == Some(n)),
+            Err(_) => self.values.iter().find(|w| w.1 == v),
+        };
+        match found {
+            Some(w) => w.0.clone(),
+            None => T::from_named(v),
+        }
+    }
+
+    /// From an IPP attribute value.
+    pub(crate) fn parse_value(&self, v: &Value) -> Option<T> {
+        if let Some(n) = v.as_i32() {
+            Some(self.parse(&n.to_string()))
+        } else {
+            v.as_str().map(|v| self.parse(v))
+        }
+    }
+
+    fn find(&self, v: &T) -> Option<&'static (T, &'static str, Option<i32>)> {
+        self.values.iter().find(|w| w.0 == *v)
+    }
+}
+
+macro_rules! option_methods {
+    ($($t:ident => $map:ident;)*) => {
+        $(
+            impl $t {
+                /// IPP keyword. For enum attributes this is the name of the value.
+                pub fn keyword(&self) -> Cow<'_, str> {
+                    $map.keyword(self)
+                }
+
+                /// From an IPP keyword or the enum value as text.
+                /// Anything unknown is kept as Named.
+                pub fn from_keyword(v: &str) -> Self {
+                    $map.parse(v)
+                }
+
+                /// The IPP attribute value.
+                pub fn ipp_value(&self) -> Value {
+                    $map.value(self)
+                }
+
+                /// From an IPP attribute value.
+                pub fn from_ipp_value(v: &Value) -> Option<Self> {
+                    $map.parse_value(v)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! named_option {
+    ($($t:ident;)*) => {
+        $(
+            impl NamedOption for $t {
+                fn named(&self) -> Option<Cow<'_, str>> {
+                    match self {
+                        $t::Named(v) => Some(Cow::Borrowed(v)),
+                        _ => None,
+                    }
+                }
+
+                fn from_named(v: &str) -> Self {
+                    $t::Named(v.to_string())
+                }
+            }
+        )*
+    };
+}
+
+option_methods! {
+    Finishings => FINISHINGS;
+    PaperSize => MEDIA;
+    PaperSource => MEDIA_SOURCE;
+    PaperType => MEDIA_TYPE;
+    Orientation => ORIENTATION;
+    ColorMode => PRINT_COLOR_MODE;
+    Quality => PRINT_QUALITY;
+    Duplex => SIDES;
+}
+
+named_option! {
+    Finishings;
+    PaperSource;
+    PaperType;
+    Orientation;
+    ColorMode;
+    Quality;
+    Duplex;
+}
+
+/// Custom sizes use the PWG name custom_WxHmm.
+impl NamedOption for PaperSize {
+    fn named(&self) -> Option<Cow<'_, str>> {
+        match self {
+            PaperSize::Named(v) => Some(Cow::Borrowed(v)),
+            PaperSize::Custom { width, height } => {
+                Some(Cow::Owned(pwg_custom_name(*width, *height)))
+            }
+            _ => None,
+        }
+    }
+
+    fn from_named(v: &str) -> Self {
+        match v.strip_prefix("custom_").and_then(pwg_dimensions) {
+            Some((width, height)) => PaperSize::Custom { width, height },
+            None => PaperSize::Named(v.to_string()),
+        }
+    }
+}
+
+pub(crate) const FINISHINGS: OptionMap<Finishings> = OptionMap {
+    name: "finishings",
+    values: &[
+        (Finishings::None, "none", Some(3)),
+        (Finishings::Staple, "staple", Some(4)),
+        (Finishings::Punch, "punch", Some(5)),
+        (Finishings::Cover, "cover", Some(6)),
+        (Finishings::Bind, "bind", Some(7)),
+        (Finishings::Fold, "fold", Some(10)),
+        (Finishings::Trim, "trim", Some(11)),
+    ],
+};
+
+pub(crate) const MEDIA: OptionMap<PaperSize> = OptionMap {
+    name: "media",
+    values: &[
+        (PaperSize::Size3x5, "na_index-3x5_3x5in", None),
+        (PaperSize::Size4x6, "na_index-4x6_4x6in", None),
+        (PaperSize::Size5x7, "na_5x7_5x7in", None),
+        (PaperSize::Size8x10, "na_govt-letter_8x10in", None),
+        (PaperSize::A3, "iso_a3_297x420mm", None),
+        (PaperSize::A4, "iso_a4_210x297mm", None),
+        (PaperSize::A5, "iso_a5_148x210mm", None),
+        (PaperSize::A6, "iso_a6_105x148mm", None),
+        (PaperSize::Env10, "na_number-10_4.125x9.5in", None),
+        (PaperSize::EnvDl, "iso_dl_110x220mm", None),
+        (PaperSize::Legal, "na_legal_8.5x14in", None),
+        (PaperSize::Letter, "na_letter_8.5x11in", None),
+        (PaperSize::PhotoL, "oe_photo-l_3.5x5in", None),
+        (PaperSize::SuperBA3, "na_super-b_13x19in", None),
+        (PaperSize::Tabloid, "na_ledger_11x17in", None),
+    ],
+};
+
+pub(crate) const MEDIA_SOURCE: OptionMap<PaperSource> = OptionMap {
+    name: "media-source",
+    values: &[
+        (PaperSource::Auto, "auto", None),
+        (PaperSource::Manual, "manual", None),
+    ],
+};
+
+pub(crate) const MEDIA_TYPE: OptionMap<PaperType> = OptionMap {
+    name: "media-type",
+    values: &[
+        (PaperType::Auto, "auto", None),
+        (PaperType::Envelope, "envelope", None),
+        (PaperType::Labels, "labels", None),
+        (PaperType::Letterhead, "stationery-letterhead", None),
+        (PaperType::Photo, "photographic", None),
+        (PaperType::PhotoGlossy, "photographic-glossy", None),
+        (PaperType::PhotoMatte, "photographic-matte", None),
+        (PaperType::Plain, "stationery", None),
+        (PaperType::Transparency, "transparency", None),
+    ],
+};
+
+pub(crate) const ORIENTATION: OptionMap<Orientation> = OptionMap {
+    name: "orientation-requested",
+    values: &[
+        (Orientation::Portrait, "portrait", Some(3)),
+        (Orientation::Landscape, "landscape", Some(4)),
+    ],
+};
+
+pub(crate) const PRINT_COLOR_MODE: OptionMap<ColorMode> = OptionMap {
+    name: "print-color-mode",
+    values: &[
+        (ColorMode::Auto, "auto", None),
+        (ColorMode::Monochrome, "monochrome", None),
+        (ColorMode::Color, "color", None),
+    ],
+};
+
+pub(crate) const PRINT_QUALITY: OptionMap<Quality> = OptionMap {
+    name: "print-quality",
+    values: &[
+        (Quality::Draft, "draft", Some(3)),
+        (Quality::Normal, "normal", Some(4)),
+        (Quality::High, "high", Some(5)),
+    ],
+};
+
+pub(crate) const SIDES: OptionMap<Duplex> = OptionMap {
+    name: "sides",
+    values: &[
+        (Duplex::Simplex, "one-sided", None),
+        (Duplex::TwoSidedPortrait, "two-sided-long-edge", None),
+        (Duplex::TwoSidedLandscape, "two-sided-short-edge", None),
+    ],
+};
@@ -0,0 +1,1125 @@
+//!
+//! PWG 5101.1 media names with their dimensions.
+//!
+//! <https://ftp.pwg.org/pub/pwg/candidates/cs-pwgmsn20-20130328-5101.1.pdf>
+//!
+
+use crate::Length;
+
+/// A standard media size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaSize {
+    /// PWG self describing name, eg. iso_a4_210x297mm.
+    pub name: &'static str,
+    /// Legacy names, as used by PPD files.
+    pub aliases: &'static [&'static str],
+    pub width: Length,
+    pub length: Length,
+    /// The windows DMPAPER values, the first one is preferred.
+    pub dmpaper: &'static [u16],
+}
+
+impl MediaSize {
+    /// The name without the dimensions, eg. iso_a4.
+    pub fn short_name(&self) -> &'static str {
+        match self.name.rsplit_once('_') {
+            Some((v, _)) => v,
+            None => self.name,
+        }
+    }
+}
+
+/// Finds the media by its PWG name. The name can be given without
+/// the dimensions, eg. iso_a4.
+pub fn by_name(name: &str) -> Option<&'static MediaSize> {
+    MEDIA_SIZES
+        .iter()
+        .find(|v| v.name == name)
+        .or_else(|| MEDIA_SIZES.iter().find(|v| v.short_name() == name))
+}
+
+/// Finds the media by a legacy name, eg. A4 or Env10.
+/// Ignores the case.
+pub fn by_alias(alias: &str) -> Option<&'static MediaSize> {
+    MEDIA_SIZES
+        .iter()
+        .find(|v| v.aliases.iter().any(|w| w.eq_ignore_ascii_case(alias)))
+}
+
+/// Finds the media by its windows DMPAPER value.
+pub fn by_dmpaper(dmpaper: u16) -> Option<&'static MediaSize> {
+    MEDIA_SIZES.iter().find(|v| v.dmpaper.contains(&dmpaper))
+}
+
+/// Finds the media with the nearest dimensions.
+/// Landscape dimensions find the same media.
+pub fn nearest(width: Length, length: Length) -> &'static MediaSize {
+    let (short, long) = (width.min(length), width.max(length));
+    MEDIA_SIZES
+        .iter()
+        .min_by_key(|v| (v.width - short).abs() + (v.length - long).abs())
+        .expect("media")
+}
+
+/// Finds the media with the same dimensions within 1mm.
+pub fn by_size(width: Length, length: Length) -> Option<&'static MediaSize> {
+    let media = nearest(width, length);
+    let (short, long) = (width.min(length), width.max(length));
+    let tolerance = Length::mm(1.0);
+    ((media.width - short).abs() <= tolerance && (media.length - long).abs() <= tolerance)
+        .then_some(media)
+}
+
+/// All known media sizes.
+pub const MEDIA_SIZES: &[MediaSize] = &[
+    MediaSize {
+        name: "asme_f_28x40in",
+        aliases: &["F"],
+        width: Length::inch(28.0),
+        length: Length::inch(40.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_2a0_1189x1682mm",
+        aliases: &["2A0"],
+        width: Length::mm(1189.0),
+        length: Length::mm(1682.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_a0_841x1189mm",
+        aliases: &["A0"],
+        width: Length::mm(841.0),
+        length: Length::mm(1189.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_a1_594x841mm",
+        aliases: &["A1"],
+        width: Length::mm(594.0),
+        length: Length::mm(841.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_a2_420x594mm",
+        aliases: &["A2"],
+        width: Length::mm(420.0),
+        length: Length::mm(594.0),
+        dmpaper: &[66],
+    },
+    MediaSize {
+        name: "iso_a3_297x420mm",
+        aliases: &["A3"],
+        width: Length::mm(297.0),
+        length: Length::mm(420.0),
+        dmpaper: &[8],
+    },
+    MediaSize {
+        name: "iso_a3-extra_322x445mm",
+        aliases: &["A3Extra"],
+        width: Length::mm(322.0),
+        length: Length::mm(445.0),
+        dmpaper: &[63],
+    },
+    MediaSize {
+        name: "iso_a4_210x297mm",
+        aliases: &["A4"],
+        width: Length::mm(210.0),
+        length: Length::mm(297.0),
+        dmpaper: &[9, 10],
+    },
+    MediaSize {
+        name: "iso_a4-extra_235.5x322.3mm",
+        aliases: &["A4Extra"],
+        width: Length::mm(235.5),
+        length: Length::mm(322.3),
+        dmpaper: &[53],
+    },
+    MediaSize {
+        name: "iso_a4-tab_225x297mm",
+        aliases: &["A4Tab"],
+        width: Length::mm(225.0),
+        length: Length::mm(297.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_a5_148x210mm",
+        aliases: &["A5"],
+        width: Length::mm(148.0),
+        length: Length::mm(210.0),
+        dmpaper: &[11],
+    },
+    MediaSize {
+        name: "iso_a5-extra_174x235mm",
+        aliases: &["A5Extra"],
+        width: Length::mm(174.0),
+        length: Length::mm(235.0),
+        dmpaper: &[64],
+    },
+    MediaSize {
+        name: "iso_a6_105x148mm",
+        aliases: &["A6"],
+        width: Length::mm(105.0),
+        length: Length::mm(148.0),
+        dmpaper: &[70],
+    },
+    MediaSize {
+        name: "iso_a7_74x105mm",
+        aliases: &["A7"],
+        width: Length::mm(74.0),
+        length: Length::mm(105.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_a8_52x74mm",
+        aliases: &["A8"],
+        width: Length::mm(52.0),
+        length: Length::mm(74.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_a9_37x52mm",
+        aliases: &["A9"],
+        width: Length::mm(37.0),
+        length: Length::mm(52.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_a10_26x37mm",
+        aliases: &["A10"],
+        width: Length::mm(26.0),
+        length: Length::mm(37.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b0_1000x1414mm",
+        aliases: &["ISOB0"],
+        width: Length::mm(1000.0),
+        length: Length::mm(1414.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b1_707x1000mm",
+        aliases: &["ISOB1"],
+        width: Length::mm(707.0),
+        length: Length::mm(1000.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b2_500x707mm",
+        aliases: &["ISOB2"],
+        width: Length::mm(500.0),
+        length: Length::mm(707.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b3_353x500mm",
+        aliases: &["ISOB3"],
+        width: Length::mm(353.0),
+        length: Length::mm(500.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b4_250x353mm",
+        aliases: &["ISOB4"],
+        width: Length::mm(250.0),
+        length: Length::mm(353.0),
+        dmpaper: &[42],
+    },
+    MediaSize {
+        name: "iso_b5_176x250mm",
+        aliases: &["ISOB5", "EnvISOB5"],
+        width: Length::mm(176.0),
+        length: Length::mm(250.0),
+        dmpaper: &[34],
+    },
+    MediaSize {
+        name: "iso_b6_125x176mm",
+        aliases: &["ISOB6", "EnvISOB6"],
+        width: Length::mm(125.0),
+        length: Length::mm(176.0),
+        dmpaper: &[35],
+    },
+    MediaSize {
+        name: "iso_b6c4_125x324mm",
+        aliases: &[],
+        width: Length::mm(125.0),
+        length: Length::mm(324.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b7_88x125mm",
+        aliases: &["ISOB7"],
+        width: Length::mm(88.0),
+        length: Length::mm(125.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b8_62x88mm",
+        aliases: &["ISOB8"],
+        width: Length::mm(62.0),
+        length: Length::mm(88.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b9_44x62mm",
+        aliases: &["ISOB9"],
+        width: Length::mm(44.0),
+        length: Length::mm(62.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_b10_31x44mm",
+        aliases: &["ISOB10"],
+        width: Length::mm(31.0),
+        length: Length::mm(44.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_c3_324x458mm",
+        aliases: &["EnvC3"],
+        width: Length::mm(324.0),
+        length: Length::mm(458.0),
+        dmpaper: &[29],
+    },
+    MediaSize {
+        name: "iso_c4_229x324mm",
+        aliases: &["EnvC4"],
+        width: Length::mm(229.0),
+        length: Length::mm(324.0),
+        dmpaper: &[30],
+    },
+    MediaSize {
+        name: "iso_c5_162x229mm",
+        aliases: &["EnvC5"],
+        width: Length::mm(162.0),
+        length: Length::mm(229.0),
+        dmpaper: &[28],
+    },
+    MediaSize {
+        name: "iso_c6_114x162mm",
+        aliases: &["EnvC6"],
+        width: Length::mm(114.0),
+        length: Length::mm(162.0),
+        dmpaper: &[31],
+    },
+    MediaSize {
+        name: "iso_c6c5_114x229mm",
+        aliases: &["EnvC65"],
+        width: Length::mm(114.0),
+        length: Length::mm(229.0),
+        dmpaper: &[32],
+    },
+    MediaSize {
+        name: "iso_c7_81x114mm",
+        aliases: &["EnvC7"],
+        width: Length::mm(81.0),
+        length: Length::mm(114.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_dl_110x220mm",
+        aliases: &["EnvDL", "DL"],
+        width: Length::mm(110.0),
+        length: Length::mm(220.0),
+        dmpaper: &[27],
+    },
+    MediaSize {
+        name: "iso_ra0_860x1220mm",
+        aliases: &["RA0"],
+        width: Length::mm(860.0),
+        length: Length::mm(1220.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_ra1_610x860mm",
+        aliases: &["RA1"],
+        width: Length::mm(610.0),
+        length: Length::mm(860.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_ra2_430x610mm",
+        aliases: &["RA2"],
+        width: Length::mm(430.0),
+        length: Length::mm(610.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_sra0_900x1280mm",
+        aliases: &["SRA0"],
+        width: Length::mm(900.0),
+        length: Length::mm(1280.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_sra1_640x900mm",
+        aliases: &["SRA1"],
+        width: Length::mm(640.0),
+        length: Length::mm(900.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_sra2_450x640mm",
+        aliases: &["SRA2"],
+        width: Length::mm(450.0),
+        length: Length::mm(640.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "iso_sra3_320x450mm",
+        aliases: &["SRA3"],
+        width: Length::mm(320.0),
+        length: Length::mm(450.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jis_b0_1030x1456mm",
+        aliases: &["B0"],
+        width: Length::mm(1030.0),
+        length: Length::mm(1456.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jis_b1_728x1030mm",
+        aliases: &["B1"],
+        width: Length::mm(728.0),
+        length: Length::mm(1030.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jis_b2_515x728mm",
+        aliases: &["B2"],
+        width: Length::mm(515.0),
+        length: Length::mm(728.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jis_b3_364x515mm",
+        aliases: &["B3"],
+        width: Length::mm(364.0),
+        length: Length::mm(515.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jis_b4_257x364mm",
+        aliases: &["B4"],
+        width: Length::mm(257.0),
+        length: Length::mm(364.0),
+        dmpaper: &[12],
+    },
+    MediaSize {
+        name: "jis_b5_182x257mm",
+        aliases: &["B5"],
+        width: Length::mm(182.0),
+        length: Length::mm(257.0),
+        dmpaper: &[13],
+    },
+    MediaSize {
+        name: "jis_b6_128x182mm",
+        aliases: &["B6"],
+        width: Length::mm(128.0),
+        length: Length::mm(182.0),
+        dmpaper: &[88],
+    },
+    MediaSize {
+        name: "jis_b7_91x128mm",
+        aliases: &["B7"],
+        width: Length::mm(91.0),
+        length: Length::mm(128.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jis_b8_64x91mm",
+        aliases: &["B8"],
+        width: Length::mm(64.0),
+        length: Length::mm(91.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jis_b9_45x64mm",
+        aliases: &["B9"],
+        width: Length::mm(45.0),
+        length: Length::mm(64.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jis_b10_32x45mm",
+        aliases: &["B10"],
+        width: Length::mm(32.0),
+        length: Length::mm(45.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jpn_chou3_120x235mm",
+        aliases: &["EnvChou3"],
+        width: Length::mm(120.0),
+        length: Length::mm(235.0),
+        dmpaper: &[73],
+    },
+    MediaSize {
+        name: "jpn_chou4_90x205mm",
+        aliases: &["EnvChou4"],
+        width: Length::mm(90.0),
+        length: Length::mm(205.0),
+        dmpaper: &[74],
+    },
+    MediaSize {
+        name: "jpn_hagaki_100x148mm",
+        aliases: &["Postcard"],
+        width: Length::mm(100.0),
+        length: Length::mm(148.0),
+        dmpaper: &[43],
+    },
+    MediaSize {
+        name: "jpn_kaku2_240x332mm",
+        aliases: &["EnvKaku2"],
+        width: Length::mm(240.0),
+        length: Length::mm(332.0),
+        dmpaper: &[71],
+    },
+    MediaSize {
+        name: "jpn_kaku3_216x277mm",
+        aliases: &["EnvKaku3"],
+        width: Length::mm(216.0),
+        length: Length::mm(277.0),
+        dmpaper: &[72],
+    },
+    MediaSize {
+        name: "jpn_oufuku_148x200mm",
+        aliases: &["DoublePostcard"],
+        width: Length::mm(148.0),
+        length: Length::mm(200.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "jpn_you4_105x235mm",
+        aliases: &["EnvYou4"],
+        width: Length::mm(105.0),
+        length: Length::mm(235.0),
+        dmpaper: &[91],
+    },
+    MediaSize {
+        name: "na_10x11_10x11in",
+        aliases: &["10x11"],
+        width: Length::inch(10.0),
+        length: Length::inch(11.0),
+        dmpaper: &[45],
+    },
+    MediaSize {
+        name: "na_10x13_10x13in",
+        aliases: &["10x13"],
+        width: Length::inch(10.0),
+        length: Length::inch(13.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_10x14_10x14in",
+        aliases: &["10x14"],
+        width: Length::inch(10.0),
+        length: Length::inch(14.0),
+        dmpaper: &[16],
+    },
+    MediaSize {
+        name: "na_10x15_10x15in",
+        aliases: &["10x15"],
+        width: Length::inch(10.0),
+        length: Length::inch(15.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_11x12_11x12in",
+        aliases: &["11x12"],
+        width: Length::inch(11.0),
+        length: Length::inch(12.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_11x15_11x15in",
+        aliases: &["11x15"],
+        width: Length::inch(11.0),
+        length: Length::inch(15.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_12x19_12x19in",
+        aliases: &["12x19"],
+        width: Length::inch(12.0),
+        length: Length::inch(19.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_5x7_5x7in",
+        aliases: &["5x7"],
+        width: Length::inch(5.0),
+        length: Length::inch(7.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_6x9_6x9in",
+        aliases: &["6x9"],
+        width: Length::inch(6.0),
+        length: Length::inch(9.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_7x9_7x9in",
+        aliases: &["7x9"],
+        width: Length::inch(7.0),
+        length: Length::inch(9.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_9x11_9x11in",
+        aliases: &["9x11"],
+        width: Length::inch(9.0),
+        length: Length::inch(11.0),
+        dmpaper: &[44],
+    },
+    MediaSize {
+        name: "na_a2_4.375x5.75in",
+        aliases: &["EnvA2"],
+        width: Length::inch(4.375),
+        length: Length::inch(5.75),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_arch-a_9x12in",
+        aliases: &["ARCHA"],
+        width: Length::inch(9.0),
+        length: Length::inch(12.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_arch-b_12x18in",
+        aliases: &["ARCHB"],
+        width: Length::inch(12.0),
+        length: Length::inch(18.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_arch-c_18x24in",
+        aliases: &["ARCHC"],
+        width: Length::inch(18.0),
+        length: Length::inch(24.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_arch-d_24x36in",
+        aliases: &["ARCHD"],
+        width: Length::inch(24.0),
+        length: Length::inch(36.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_arch-e_36x48in",
+        aliases: &["ARCHE"],
+        width: Length::inch(36.0),
+        length: Length::inch(48.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_c_17x22in",
+        aliases: &["AnsiC", "CSheet"],
+        width: Length::inch(17.0),
+        length: Length::inch(22.0),
+        dmpaper: &[24],
+    },
+    MediaSize {
+        name: "na_d_22x34in",
+        aliases: &["AnsiD", "DSheet"],
+        width: Length::inch(22.0),
+        length: Length::inch(34.0),
+        dmpaper: &[25],
+    },
+    MediaSize {
+        name: "na_e_34x44in",
+        aliases: &["AnsiE", "ESheet"],
+        width: Length::inch(34.0),
+        length: Length::inch(44.0),
+        dmpaper: &[26],
+    },
+    MediaSize {
+        name: "na_edp_11x14in",
+        aliases: &["11x14"],
+        width: Length::inch(11.0),
+        length: Length::inch(14.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_eur-edp_12x14in",
+        aliases: &[],
+        width: Length::inch(12.0),
+        length: Length::inch(14.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_executive_7.25x10.5in",
+        aliases: &["Executive"],
+        width: Length::inch(7.25),
+        length: Length::inch(10.5),
+        dmpaper: &[7],
+    },
+    MediaSize {
+        name: "na_fanfold-eur_8.5x12in",
+        aliases: &["FanFoldGerman"],
+        width: Length::inch(8.5),
+        length: Length::inch(12.0),
+        dmpaper: &[40],
+    },
+    MediaSize {
+        name: "na_fanfold-us_11x14.875in",
+        aliases: &["FanFoldUS"],
+        width: Length::inch(11.0),
+        length: Length::inch(14.875),
+        dmpaper: &[39],
+    },
+    MediaSize {
+        name: "na_foolscap_8.5x13in",
+        aliases: &["FanFoldGermanLegal", "Folio"],
+        width: Length::inch(8.5),
+        length: Length::inch(13.0),
+        dmpaper: &[14],
+    },
+    MediaSize {
+        name: "na_govt-legal_8x13in",
+        aliases: &["8x13"],
+        width: Length::inch(8.0),
+        length: Length::inch(13.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_govt-letter_8x10in",
+        aliases: &["8x10"],
+        width: Length::inch(8.0),
+        length: Length::inch(10.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_index-3x5_3x5in",
+        aliases: &["3x5"],
+        width: Length::inch(3.0),
+        length: Length::inch(5.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_index-4x6_4x6in",
+        aliases: &["4x6"],
+        width: Length::inch(4.0),
+        length: Length::inch(6.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_index-4x6-ext_6x8in",
+        aliases: &["6x8"],
+        width: Length::inch(6.0),
+        length: Length::inch(8.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_index-5x8_5x8in",
+        aliases: &["5x8"],
+        width: Length::inch(5.0),
+        length: Length::inch(8.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_invoice_5.5x8.5in",
+        aliases: &["Statement"],
+        width: Length::inch(5.5),
+        length: Length::inch(8.5),
+        dmpaper: &[6],
+    },
+    MediaSize {
+        name: "na_ledger_11x17in",
+        aliases: &["Tabloid", "Ledger", "11x17"],
+        width: Length::inch(11.0),
+        length: Length::inch(17.0),
+        dmpaper: &[3, 4, 17],
+    },
+    MediaSize {
+        name: "na_legal_8.5x14in",
+        aliases: &["Legal"],
+        width: Length::inch(8.5),
+        length: Length::inch(14.0),
+        dmpaper: &[5],
+    },
+    MediaSize {
+        name: "na_legal-extra_9.5x15in",
+        aliases: &["LegalExtra"],
+        width: Length::inch(9.5),
+        length: Length::inch(15.0),
+        dmpaper: &[51],
+    },
+    MediaSize {
+        name: "na_letter_8.5x11in",
+        aliases: &["Letter"],
+        width: Length::inch(8.5),
+        length: Length::inch(11.0),
+        dmpaper: &[1, 2, 18],
+    },
+    MediaSize {
+        name: "na_letter-extra_9.5x12in",
+        aliases: &["LetterExtra"],
+        width: Length::inch(9.5),
+        length: Length::inch(12.0),
+        dmpaper: &[50],
+    },
+    MediaSize {
+        name: "na_letter-plus_8.5x12.69in",
+        aliases: &["LetterPlus"],
+        width: Length::inch(8.5),
+        length: Length::inch(12.69),
+        dmpaper: &[59],
+    },
+    MediaSize {
+        name: "na_monarch_3.875x7.5in",
+        aliases: &["EnvMonarch"],
+        width: Length::inch(3.875),
+        length: Length::inch(7.5),
+        dmpaper: &[37],
+    },
+    MediaSize {
+        name: "na_number-9_3.875x8.875in",
+        aliases: &["Env9"],
+        width: Length::inch(3.875),
+        length: Length::inch(8.875),
+        dmpaper: &[19],
+    },
+    MediaSize {
+        name: "na_number-10_4.125x9.5in",
+        aliases: &["Env10"],
+        width: Length::inch(4.125),
+        length: Length::inch(9.5),
+        dmpaper: &[20],
+    },
+    MediaSize {
+        name: "na_number-11_4.5x10.375in",
+        aliases: &["Env11"],
+        width: Length::inch(4.5),
+        length: Length::inch(10.375),
+        dmpaper: &[21],
+    },
+    MediaSize {
+        name: "na_number-12_4.75x11in",
+        aliases: &["Env12"],
+        width: Length::inch(4.75),
+        length: Length::inch(11.0),
+        dmpaper: &[22],
+    },
+    MediaSize {
+        name: "na_number-14_5x11.5in",
+        aliases: &["Env14"],
+        width: Length::inch(5.0),
+        length: Length::inch(11.5),
+        dmpaper: &[23],
+    },
+    MediaSize {
+        name: "na_personal_3.625x6.5in",
+        aliases: &["EnvPersonal"],
+        width: Length::inch(3.625),
+        length: Length::inch(6.5),
+        dmpaper: &[38],
+    },
+    MediaSize {
+        name: "na_quarto_8.5x10.83in",
+        aliases: &["Quarto"],
+        width: Length::inch(8.5),
+        length: Length::inch(10.83),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_super-a_8.94x14in",
+        aliases: &["SuperA"],
+        width: Length::inch(8.94),
+        length: Length::inch(14.0),
+        dmpaper: &[57],
+    },
+    MediaSize {
+        name: "na_super-b_13x19in",
+        aliases: &["SuperB"],
+        width: Length::inch(13.0),
+        length: Length::inch(19.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "na_wide-format_30x42in",
+        aliases: &[],
+        width: Length::inch(30.0),
+        length: Length::inch(42.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_12x16_12x16in",
+        aliases: &["12x16"],
+        width: Length::inch(12.0),
+        length: Length::inch(16.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_14x17_14x17in",
+        aliases: &["14x17"],
+        width: Length::inch(14.0),
+        length: Length::inch(17.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_18x22_18x22in",
+        aliases: &["18x22"],
+        width: Length::inch(18.0),
+        length: Length::inch(22.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_a2plus_17x24in",
+        aliases: &["A2Plus"],
+        width: Length::inch(17.0),
+        length: Length::inch(24.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_business-card_2x3.5in",
+        aliases: &["BusinessCard"],
+        width: Length::inch(2.0),
+        length: Length::inch(3.5),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_photo-10r_10x12in",
+        aliases: &["Photo10R"],
+        width: Length::inch(10.0),
+        length: Length::inch(12.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_photo-20r_20x24in",
+        aliases: &["Photo20R"],
+        width: Length::inch(20.0),
+        length: Length::inch(24.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_photo-l_3.5x5in",
+        aliases: &["3.5x5", "PhotoL"],
+        width: Length::inch(3.5),
+        length: Length::inch(5.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_photo-s10r_10x15in",
+        aliases: &["PhotoS10R"],
+        width: Length::inch(10.0),
+        length: Length::inch(15.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_square-photo_4x4in",
+        aliases: &["4x4"],
+        width: Length::inch(4.0),
+        length: Length::inch(4.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "oe_square-photo_5x5in",
+        aliases: &["5x5"],
+        width: Length::inch(5.0),
+        length: Length::inch(5.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_16k_184x260mm",
+        aliases: &["16K"],
+        width: Length::mm(184.0),
+        length: Length::mm(260.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_16k_195x270mm",
+        aliases: &[],
+        width: Length::mm(195.0),
+        length: Length::mm(270.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_business-card_55x85mm",
+        aliases: &[],
+        width: Length::mm(55.0),
+        length: Length::mm(85.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_business-card_55x91mm",
+        aliases: &[],
+        width: Length::mm(55.0),
+        length: Length::mm(91.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_card_54x86mm",
+        aliases: &["CreditCard"],
+        width: Length::mm(54.0),
+        length: Length::mm(86.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_dai-pa-kai_275x395mm",
+        aliases: &[],
+        width: Length::mm(275.0),
+        length: Length::mm(395.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_dsc-photo_89x119mm",
+        aliases: &[],
+        width: Length::mm(89.0),
+        length: Length::mm(119.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_folio-sp_215x315mm",
+        aliases: &["FolioSP"],
+        width: Length::mm(215.0),
+        length: Length::mm(315.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_folio_210x330mm",
+        aliases: &["A4Plus"],
+        width: Length::mm(210.0),
+        length: Length::mm(330.0),
+        dmpaper: &[60],
+    },
+    MediaSize {
+        name: "om_invite_220x220mm",
+        aliases: &["EnvInvite"],
+        width: Length::mm(220.0),
+        length: Length::mm(220.0),
+        dmpaper: &[47],
+    },
+    MediaSize {
+        name: "om_italian_110x230mm",
+        aliases: &["EnvItalian"],
+        width: Length::mm(110.0),
+        length: Length::mm(230.0),
+        dmpaper: &[36],
+    },
+    MediaSize {
+        name: "om_juuro-ku-kai_198x275mm",
+        aliases: &[],
+        width: Length::mm(198.0),
+        length: Length::mm(275.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_large-photo_200x300mm",
+        aliases: &[],
+        width: Length::mm(200.0),
+        length: Length::mm(300.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_medium-photo_130x180mm",
+        aliases: &[],
+        width: Length::mm(130.0),
+        length: Length::mm(180.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_pa-kai_267x389mm",
+        aliases: &[],
+        width: Length::mm(267.0),
+        length: Length::mm(389.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_postfix_114x229mm",
+        aliases: &["EnvPostfix"],
+        width: Length::mm(114.0),
+        length: Length::mm(229.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_small-photo_100x150mm",
+        aliases: &[],
+        width: Length::mm(100.0),
+        length: Length::mm(150.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "om_wide-photo_100x200mm",
+        aliases: &[],
+        width: Length::mm(100.0),
+        length: Length::mm(200.0),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "prc_1_102x165mm",
+        aliases: &["EnvPRC1"],
+        width: Length::mm(102.0),
+        length: Length::mm(165.0),
+        dmpaper: &[96],
+    },
+    MediaSize {
+        name: "prc_2_102x176mm",
+        aliases: &["EnvPRC2"],
+        width: Length::mm(102.0),
+        length: Length::mm(176.0),
+        dmpaper: &[97],
+    },
+    MediaSize {
+        name: "prc_4_110x208mm",
+        aliases: &["EnvPRC4"],
+        width: Length::mm(110.0),
+        length: Length::mm(208.0),
+        dmpaper: &[99],
+    },
+    MediaSize {
+        name: "prc_6_120x230mm",
+        aliases: &["EnvPRC6"],
+        width: Length::mm(120.0),
+        length: Length::mm(230.0),
+        dmpaper: &[101],
+    },
+    MediaSize {
+        name: "prc_7_160x230mm",
+        aliases: &["EnvPRC7"],
+        width: Length::mm(160.0),
+        length: Length::mm(230.0),
+        dmpaper: &[102],
+    },
+    MediaSize {
+        name: "prc_8_120x309mm",
+        aliases: &["EnvPRC8"],
+        width: Length::mm(120.0),
+        length: Length::mm(309.0),
+        dmpaper: &[103],
+    },
+    MediaSize {
+        name: "prc_16k_146x215mm",
+        aliases: &["PRC16K"],
+        width: Length::mm(146.0),
+        length: Length::mm(215.0),
+        dmpaper: &[93],
+    },
+    MediaSize {
+        name: "prc_32k_97x151mm",
+        aliases: &["PRC32K"],
+        width: Length::mm(97.0),
+        length: Length::mm(151.0),
+        dmpaper: &[94],
+    },
+    MediaSize {
+        name: "roc_16k_7.75x10.75in",
+        aliases: &["roc16k"],
+        width: Length::inch(7.75),
+        length: Length::inch(10.75),
+        dmpaper: &[],
+    },
+    MediaSize {
+        name: "roc_8k_10.75x15.5in",
+        aliases: &["roc8k"],
+        width: Length::inch(10.75),
+        length: Length::inch(15.5),
+        dmpaper: &[],
+    },
+];
@@ -0,0 +1,272 @@
+//!
+//! printer-state-reasons.
+//!
+//! <https://www.rfc-editor.org/rfc/rfc8011#section-5.4.12>
+//! <https://www.cups.org/doc/spec-ipp.html>
+//!
+
+use std::fmt::{Display, Formatter};
+
+/// Severity of a state reason.
+///
+/// Taken from the -report, -warning or -error suffix.
+///
+/// RFC 8011 treats a keyword without a suffix as an error. CUPS sends
+/// most reasons without one, so a known reason gets the severity
+/// Info::status() always gave it, "paused" stays a report and
+/// "media-low" a warning. Unknown keywords without a suffix follow the
+/// RFC and are errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Report,
+    Warning,
+    Error,
+}
+
+/// The known reasons.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    None,
+    Other,
+    ConnectingToDevice,
+    DeveloperEmpty,
+    DeveloperLow,
+    DoorOpen,
+    FuserOverTemp,
+    FuserUnderTemp,
+    InputTrayMissing,
+    InterlockOpen,
+    InterpreterResourceUnavailable,
+    MarkerSupplyEmpty,
+    MarkerSupplyLow,
+    MarkerWasteAlmostFull,
+    MarkerWasteFull,
+    MediaEmpty,
+    MediaJam,
+    MediaLow,
+    MediaNeeded,
+    MovingToPaused,
+    OpcLifeOver,
+    OpcNearEol,
+    Offline,
+    OutputAreaAlmostFull,
+    OutputAreaFull,
+    OutputTrayMissing,
+    Paused,
+    Shutdown,
+    SpoolAreaFull,
+    StoppedPartly,
+    Stopping,
+    TimedOut,
+    TonerEmpty,
+    TonerLow,
+    InsecureFilter,
+    MissingFilter,
+    RemoteAborted,
+    RemoteCanceled,
+    RemoteCompleted,
+    RemotePending,
+    RemotePendingHeld,
+    RemoteProcessing,
+    RemoteStopped,
+    WaitingForJobCompleted,
+    /// Windows only, PRINTER_STATUS_INITIALIZING.
+    Initializing,
+    /// Windows only, PRINTER_STATUS_WARMING_UP.
+    WarmingUp,
+    /// Windows only, PRINTER_STATUS_POWER_SAVE.
+    PowerSave,
+    /// Windows only, PRINTER_STATUS_PAPER_PROBLEM.
+    PaperProblem,
+    /// Windows only, PRINTER_STATUS_USER_INTERVENTION.
+    UserIntervention,
+    /// Windows only, PRINTER_STATUS_NOT_AVAILABLE.
+    NotAvailable,
+    /// Windows only, PRINTER_STATUS_PAGE_PUNT.
+    PagePunt,
+    /// Windows only, PRINTER_STATUS_PENDING_DELETION.
+    PendingDeletion,
+    /// Windows only, PRINTER_STATUS_SERVER_UNKNOWN.
+    ServerUnknown,
+    /// Not one of the above. The keyword is kept in StateReason.
+    Unknown,
+}
+
+/// Keyword, reason and the severity if there is no suffix.
+/// See Severity.
+const REASONS: &[(&str, Reason, Severity)] = &[
+    ("none", Reason::None, Severity::Report),
+    ("other", Reason::Other, Severity::Report),
+    (
+        "connecting-to-device",
+        Reason::ConnectingToDevice,
+        Severity::Error,
+    ),
+    (
+        "connection-to-device",
+        Reason::ConnectingToDevice,
+        Severity::Error,
+    ),
+    ("developer-empty", Reason::DeveloperEmpty, Severity::Error),
+    ("developer-low", Reason::DeveloperLow, Severity::Warning),
+    ("door-open", Reason::DoorOpen, Severity::Warning),
+    ("fuser-over-temp", Reason::FuserOverTemp, Severity::Warning),
+    (
+        "fuser-under-temp",
+        Reason::FuserUnderTemp,
+        Severity::Warning,
+    ),
+    (
+        "input-tray-missing",
+        Reason::InputTrayMissing,
+        Severity::Error,
+    ),
+    ("interlock-open", Reason::InterlockOpen, Severity::Warning),
+    (
+        "interpreter-resource-unavailable",
+        Reason::InterpreterResourceUnavailable,
+        Severity::Error,
+    ),
+    (
+        "marker-supply-empty",
+        Reason::MarkerSupplyEmpty,
+        Severity::Error,
+    ),
+    (
+        "marker-supply-low",
+        Reason::MarkerSupplyLow,
+        Severity::Warning,
+    ),
+    (
+        "marker-waste-almost-full",
+        Reason::MarkerWasteAlmostFull,
+        Severity::Warning,
+    ),
+    (
+        "marker-waste-full",
+        Reason::MarkerWasteFull,
+        Severity::Error,
+    ),
+    (
+        "waste-almost-full",
+        Reason::MarkerWasteAlmostFull,
+        Severity::Warning,
+    ),
+    ("waste-full", Reason::MarkerWasteFull, Severity::Error),
+    ("media-empty", Reason::MediaEmpty, Severity::Error),
+    ("media-jam", Reason::MediaJam, Severity::Error),
+    ("media-low", Reason::MediaLow, Severity::Warning),
+    ("media-needed", Reason::MediaNeeded, Severity::Warning),
+    ("moving-to-paused", Reason::MovingToPaused, Severity::Report),
+    ("opc-life-over", Reason::OpcLifeOver, Severity::Error),
+    ("opc-near-eol", Reason::OpcNearEol, Severity::Warning),
+    ("offline", Reason::Offline, Severity::Report),
+    (
+        "output-area-almost-full",
+        Reason::OutputAreaAlmostFull,
+        Severity::Warning,
+    ),
+    ("output-area-full", Reason::OutputAreaFull, Severity::Error),
+    (
+        "output-tray-missing",
+        Reason::OutputTrayMissing,
+        Severity::Error,
+    ),
+    ("paused", Reason::Paused, Severity::Report),
+    ("shutdown", Reason::Shutdown, Severity::Report),
+    ("spool-area-full", Reason::SpoolAreaFull, Severity::Error),
+    ("stopped-partly", Reason::StoppedPartly, Severity::Report),
+    ("stopping", Reason::Stopping, Severity::Report),
+    ("timed-out", Reason::TimedOut, Severity::Error),
+    ("toner-empty", Reason::TonerEmpty, Severity::Error),
+    ("toner-low", Reason::TonerLow, Severity::Warning),
+    ("insecure-filter", Reason::InsecureFilter, Severity::Warning),
+    ("missing-filter", Reason::MissingFilter, Severity::Warning),
+    ("remote-aborted", Reason::RemoteAborted, Severity::Report),
+    ("remote-canceled", Reason::RemoteCanceled, Severity::Report),
+    (
+        "remote-completed",
+        Reason::RemoteCompleted,
+        Severity::Report,
+    ),
+    ("remote-pending", Reason::RemotePending, Severity::Report),
+    (
+        "remote-pending-held",
+        Reason::RemotePendingHeld,
+        Severity::Report,
+    ),
+    (
+        "remote-processing",
+        Reason::RemoteProcessing,
+        Severity::Report,
+    ),
+    ("remote-stopped", Reason::RemoteStopped, Severity::Report),
+    (
+        "waiting-for-job-completed",
+        Reason::WaitingForJobCompleted,
+        Severity::Warning,
+    ),
+];
+
+/// One value of printer-state-reasons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateReason {
+    /// The keyword without the severity suffix.
+    pub keyword: String,
+    pub reason: Reason,
+    pub severity: Severity,
+}
+
+impl StateReason {
+    /// Parses one keyword.
+    ///
+    /// The CUPS specific reasons can come with a "cups-" prefix,
+    /// this is ignored for the reason but kept in the keyword.
+    pub fn parse(value: &str) -> StateReason {
+        let value = value.trim();
+        let (keyword, severity) = if let Some(v) = value.strip_suffix("-report") {
+            (v, Some(Severity::Report))
+        } else if let Some(v) = value.strip_suffix("-warning") {
+            (v, Some(Severity::Warning))
+        } else if let Some(v) = value.strip_suffix("-error") {
+            (v, Some(Severity::Error))
+        } else {
+            (value, None)
+        };
+
+        let name = keyword.strip_prefix("cups-").unwrap_or(keyword);
+        let (reason, default_severity) = REASONS
+            .iter()
+            .find(|v| v.0 == name)
+            .map(|v| (v.1, v.2))
+            .unwrap_or((Reason::Unknown, Severity::Error));
+
+        StateReason {
+            keyword: keyword.to_string(),
+            reason,
+            severity: severity.unwrap_or(default_severity),
+        }
+    }
+
+    /// Parses a list of keywords separated by ','. Leaves out "none".
+    pub fn parse_list(value: &str) -> Vec<StateReason> {
+        value
+            .split(',')
+            .filter(|v| !v.trim().is_empty())
+            .map(StateReason::parse)
+            .filter(|v| v.reason != Reason::None)
+            .collect()
+    }
+}
+
+impl Display for StateReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let suffix = match self.severity {
+            Severity::Report => "report",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}-{}", self.keyword, suffix)
+    }
+}
@@ -6,10 +6,14 @@ use std::mem::{align_of, size_of};
 use std::os::windows::ffi::{OsStrExt, OsStringExt};
 use std::ptr;
 use std::ptr::slice_from_raw_parts;
+use std::time::{Duration, SystemTime};
 
 use libc::{wchar_t, wcslen};
 use windows_sys::core::{PCWSTR, PWSTR};
-use windows_sys::Win32::Foundation::{GetLastError, LocalFree, FALSE, HANDLE, HLOCAL, TRUE};
+use windows_sys::Win32::Foundation::{
+    GetLastError, LocalFree, ERROR_INVALID_PARAMETER, FALSE, FILETIME, HANDLE, HLOCAL, SYSTEMTIME,
+    TRUE,
+};
 use windows_sys::Win32::Graphics::Gdi::{
     CCHFORMNAME, DEVMODEW, DEVMODEW_0, DEVMODEW_0_0, DEVMODEW_1, DMBIN_AUTO, DMBIN_CASSETTE,
     DMBIN_ENVELOPE, DMBIN_ENVMANUAL, DMBIN_FORMSOURCE, DMBIN_LARGECAPACITY, DMBIN_LARGEFMT,
@@ -51,18 +55,24 @@ use windows_sys::Win32::Graphics::Gdi::{
     DM_YRESOLUTION,
 };
 use windows_sys::Win32::Graphics::Printing::{
-    ClosePrinter, EndDocPrinter, EndPagePrinter, EnumPrintersW, GetDefaultPrinterW, GetPrinterW,
-    OpenPrinterW, StartDocPrinterW, StartPagePrinter, WritePrinter, DOC_INFO_1W,
-    PRINTER_ACCESS_USE, PRINTER_ATTRIBUTE_DEFAULT, PRINTER_ATTRIBUTE_DIRECT,
-    PRINTER_ATTRIBUTE_DO_COMPLETE_FIRST, PRINTER_ATTRIBUTE_ENABLE_BIDI,
-    PRINTER_ATTRIBUTE_ENABLE_DEVQ, PRINTER_ATTRIBUTE_ENTERPRISE_CLOUD, PRINTER_ATTRIBUTE_FAX,
-    PRINTER_ATTRIBUTE_FRIENDLY_NAME, PRINTER_ATTRIBUTE_HIDDEN, PRINTER_ATTRIBUTE_KEEPPRINTEDJOBS,
-    PRINTER_ATTRIBUTE_LOCAL, PRINTER_ATTRIBUTE_MACHINE, PRINTER_ATTRIBUTE_NETWORK,
-    PRINTER_ATTRIBUTE_PER_USER, PRINTER_ATTRIBUTE_PUBLISHED, PRINTER_ATTRIBUTE_PUSHED_MACHINE,
-    PRINTER_ATTRIBUTE_PUSHED_USER, PRINTER_ATTRIBUTE_QUEUED, PRINTER_ATTRIBUTE_RAW_ONLY,
-    PRINTER_ATTRIBUTE_SHARED, PRINTER_ATTRIBUTE_TS, PRINTER_ATTRIBUTE_TS_GENERIC_DRIVER,
-    PRINTER_ATTRIBUTE_WORK_OFFLINE, PRINTER_DEFAULTSW, PRINTER_ENUM_LOCAL, PRINTER_INFO_2W,
-    PRINTER_INFO_4W, PRINTER_STATUS_BUSY, PRINTER_STATUS_DOOR_OPEN, PRINTER_STATUS_ERROR,
+    AbortPrinter, ClosePrinter, EndDocPrinter, EndPagePrinter, EnumJobsW,
+    EnumPrintProcessorDatatypesW, EnumPrintersW, GetDefaultPrinterW, GetJobW, GetPrinterW,
+    OpenPrinterW, SetJobW, SetPrinterW, StartDocPrinterW, StartPagePrinter, WritePrinter,
+    DATATYPES_INFO_1W, DOC_INFO_1W, JOB_CONTROL_DELETE, JOB_CONTROL_PAUSE, JOB_CONTROL_RESUME,
+    JOB_INFO_1W, JOB_INFO_2W, JOB_STATUS_BLOCKED_DEVQ, JOB_STATUS_COMPLETE, JOB_STATUS_DELETED,
+    JOB_STATUS_DELETING, JOB_STATUS_ERROR, JOB_STATUS_OFFLINE, JOB_STATUS_PAPEROUT,
+    JOB_STATUS_PAUSED, JOB_STATUS_PRINTED, JOB_STATUS_PRINTING, JOB_STATUS_SPOOLING,
+    JOB_STATUS_USER_INTERVENTION, PRINTER_ACCESS_ADMINISTER, PRINTER_ACCESS_USE,
+    PRINTER_ATTRIBUTE_DEFAULT, PRINTER_ATTRIBUTE_DIRECT, PRINTER_ATTRIBUTE_DO_COMPLETE_FIRST,
+    PRINTER_ATTRIBUTE_ENABLE_BIDI, PRINTER_ATTRIBUTE_ENABLE_DEVQ,
+    PRINTER_ATTRIBUTE_ENTERPRISE_CLOUD, PRINTER_ATTRIBUTE_FAX, PRINTER_ATTRIBUTE_FRIENDLY_NAME,
+    PRINTER_ATTRIBUTE_HIDDEN, PRINTER_ATTRIBUTE_KEEPPRINTEDJOBS, PRINTER_ATTRIBUTE_LOCAL,
+    PRINTER_ATTRIBUTE_MACHINE, PRINTER_ATTRIBUTE_NETWORK, PRINTER_ATTRIBUTE_PER_USER,
+    PRINTER_ATTRIBUTE_PUBLISHED, PRINTER_ATTRIBUTE_PUSHED_MACHINE, PRINTER_ATTRIBUTE_PUSHED_USER,
+    PRINTER_ATTRIBUTE_QUEUED, PRINTER_ATTRIBUTE_RAW_ONLY, PRINTER_ATTRIBUTE_SHARED,
+    PRINTER_ATTRIBUTE_TS, PRINTER_ATTRIBUTE_TS_GENERIC_DRIVER, PRINTER_ATTRIBUTE_WORK_OFFLINE,
+    PRINTER_CONTROL_PURGE, PRINTER_DEFAULTSW, PRINTER_ENUM_LOCAL, PRINTER_INFO_2W, PRINTER_INFO_4W,
+    PRINTER_STATUS_BUSY, PRINTER_STATUS_DOOR_OPEN, PRINTER_STATUS_ERROR,
     PRINTER_STATUS_INITIALIZING, PRINTER_STATUS_IO_ACTIVE, PRINTER_STATUS_MANUAL_FEED,
     PRINTER_STATUS_NOT_AVAILABLE, PRINTER_STATUS_NO_TONER, PRINTER_STATUS_OFFLINE,
     PRINTER_STATUS_OUTPUT_BIN_FULL, PRINTER_STATUS_OUT_OF_MEMORY, PRINTER_STATUS_PAGE_PUNT,
@@ -72,15 +82,25 @@ use windows_sys::Win32::Graphics::Printing::{
     PRINTER_STATUS_TONER_LOW, PRINTER_STATUS_USER_INTERVENTION, PRINTER_STATUS_WAITING,
     PRINTER_STATUS_WARMING_UP,
 };
+use windows_sys::Win32::Storage::Xps::{
+    DeviceCapabilitiesW, DC_COLORDEVICE, DC_COPIES, DC_DUPLEX, DC_ENUMRESOLUTIONS, DC_MAXEXTENT,
+    DC_MINEXTENT, DC_PAPERNAMES, DC_PAPERS, DC_PAPERSIZE, PRINTER_DEVICE_CAPABILITIES,
+};
 use windows_sys::Win32::System::Diagnostics::Debug::{
     FormatMessageW, FORMAT_MESSAGE_ALLOCATE_BUFFER, FORMAT_MESSAGE_FROM_SYSTEM,
     FORMAT_MESSAGE_IGNORE_INSERTS,
 };
+use windows_sys::Win32::System::Time::SystemTimeToFileTime;
 
-use crate::{JobParam, PrintError, Status};
+use crate::media::{self, MediaSize};
+use crate::{
+    wait_for_job, Backend, Capabilities, Conflict, JobInfo, JobParam, JobState, JobStatus, Length,
+    Lifecycle, Media, PrintError, PrinterState, PrinterStatus, Reason, Severity, StateReason,
+    Status, WhichJobs,
+};
 
 /// Data format.
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Format {
     #[default]
     Raw,
@@ -220,6 +240,12 @@ pub enum PaperSize {
     Penv9Rotated = DMPAPER_PENV_9_ROTATED,
     Penv10Rotated = DMPAPER_PENV_10_ROTATED,
     User = DMPAPER_USER,
+    /// Width and height. Sent as DMPAPER_USER with dmPaperWidth and
+    /// dmPaperLength in 1/10mm.
+    Custom {
+        width: Length,
+        height: Length,
+    },
 }
 
 impl PaperSize {
@@ -229,10 +255,60 @@ impl PaperSize {
         // field, so we can read the discriminant without offsetting the pointer.
         unsafe { *(self as *const PaperSize as *const u32) }
     }
+
+    /// The DMPAPER value.
+    fn number(&self) -> i16 {
+        match self {
+            PaperSize::Numeric(n) => *n,
+            PaperSize::Custom { .. } => DMPAPER_USER as i16,
+            _ => self.discriminant() as i16,
+        }
+    }
+
+    /// The standard media size, if there is one.
+    pub fn media_size(&self) -> Option<&'static MediaSize> {
+        match self {
+            PaperSize::Custom { width, height } => media::by_size(*width, *height),
+            v => media::by_dmpaper(v.number() as u16),
+        }
+    }
+}
+
+/// Numeric with the DMPAPER value, this compares equal to the
+/// named variant. Custom if there is no DMPAPER value.
+impl From<&MediaSize> for PaperSize {
+    fn from(media: &MediaSize) -> Self {
+        match media.dmpaper.first() {
+            Some(v) => PaperSize::Numeric(*v as i16),
+            None => PaperSize::Custom {
+                width: media.width,
+                height: media.length,
+            },
+        }
+    }
+}
+
+/// Equal if the DMPAPER value is the same. DeviceCapabilities only
+/// knows the numbers. Custom sizes compare their dimensions.
+impl PartialEq for PaperSize {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                PaperSize::Custom { width, height },
+                PaperSize::Custom {
+                    width: other_width,
+                    height: other_height,
+                },
+            ) => width == other_width && height == other_height,
+            _ => self.number() == other.number(),
+        }
+    }
 }
 
+impl Eq for PaperSize {}
+
 #[repr(u32)]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum PaperSource {
     /// Use this number for the attribute.
     Numeric(i16),
@@ -263,7 +339,7 @@ impl PaperSource {
 }
 
 #[repr(u32)]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum PaperType {
     /// Use this number for the attribute.
     Numeric(u32),
@@ -284,7 +360,7 @@ impl PaperType {
 }
 
 #[repr(u32)]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Orientation {
     /// Use this number for the attribute.
     Numeric(i16),
@@ -303,7 +379,7 @@ impl Orientation {
 }
 
 #[repr(i16)]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum ColorMode {
     /// Use this number for the attribute.
     Numeric(i16),
@@ -322,7 +398,7 @@ impl ColorMode {
 }
 
 #[repr(i32)]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Quality {
     /// Use this number for the attribute.
     Numeric(i16),
@@ -343,7 +419,7 @@ impl Quality {
 }
 
 #[repr(i16)]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Duplex {
     /// Use this number for the attribute.
     Numeric(i16),
@@ -363,7 +439,7 @@ impl Duplex {
 }
 
 #[repr(i16)]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum TrueType {
     /// Use this number for the attribute.
     Numeric(i16),
@@ -384,7 +460,7 @@ impl TrueType {
 }
 
 #[repr(i16)]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum Collate {
     /// Use this number for the attribute.
     Numeric(i16) = -1,
@@ -624,6 +700,202 @@ impl Info {
             Status::Idle
         }
     }
+
+    /// The common part of the status.
+    ///
+    /// The status flags are mapped to the IPP reasons where there is one,
+    /// with the IPP severity. The others have their own Reason and the
+    /// name of the flag as keyword. The message lists the flags, the
+    /// spooler has no time for the last change.
+    pub fn printer_status(&self) -> PrinterStatus {
+        let state = if self.status_busy
+            || self.status_printing
+            || self.status_io_active
+            || self.status_processing
+        {
+            PrinterState::Printing
+        } else if self.status_offline
+            || self.status_paused
+            || self.status_error
+            || self.status_paper_jam
+            || self.status_paper_out
+            || self.status_user_intervention
+        {
+            PrinterState::Stopped
+        } else {
+            PrinterState::Idle
+        };
+
+        let flags = [
+            (
+                self.status_offline,
+                "offline",
+                Reason::Offline,
+                Severity::Report,
+                "Offline",
+            ),
+            (
+                self.status_paused,
+                "paused",
+                Reason::Paused,
+                Severity::Report,
+                "Paused",
+            ),
+            (
+                self.status_power_save,
+                "PRINTER_STATUS_POWER_SAVE",
+                Reason::PowerSave,
+                Severity::Report,
+                "Power save",
+            ),
+            (
+                self.status_initializing,
+                "PRINTER_STATUS_INITIALIZING",
+                Reason::Initializing,
+                Severity::Report,
+                "Initializing",
+            ),
+            (
+                self.status_warming_up,
+                "PRINTER_STATUS_WARMING_UP",
+                Reason::WarmingUp,
+                Severity::Report,
+                "Warming up",
+            ),
+            (
+                self.status_manual_feed,
+                "media-needed",
+                Reason::MediaNeeded,
+                Severity::Warning,
+                "Manual feed",
+            ),
+            (
+                self.status_toner_low,
+                "toner-low",
+                Reason::TonerLow,
+                Severity::Warning,
+                "Toner low",
+            ),
+            (
+                self.status_paper_problem,
+                "PRINTER_STATUS_PAPER_PROBLEM",
+                Reason::PaperProblem,
+                Severity::Warning,
+                "Paper problem",
+            ),
+            (
+                self.status_user_intervention,
+                "PRINTER_STATUS_USER_INTERVENTION",
+                Reason::UserIntervention,
+                Severity::Warning,
+                "User intervention",
+            ),
+            (
+                self.status_output_bin_full,
+                "output-area-full",
+                Reason::OutputAreaFull,
+                Severity::Error,
+                "Output bin full",
+            ),
+            (
+                self.status_paper_out,
+                "media-empty",
+                Reason::MediaEmpty,
+                Severity::Error,
+                "Paper out",
+            ),
+            (
+                self.status_door_open,
+                "door-open",
+                Reason::DoorOpen,
+                Severity::Error,
+                "Door open",
+            ),
+            (
+                self.status_error,
+                "other",
+                Reason::Other,
+                Severity::Error,
+                "Error",
+            ),
+            (
+                self.status_no_toner,
+                "toner-empty",
+                Reason::TonerEmpty,
+                Severity::Error,
+                "No toner",
+            ),
+            (
+                self.status_not_available,
+                "PRINTER_STATUS_NOT_AVAILABLE",
+                Reason::NotAvailable,
+                Severity::Error,
+                "Not available",
+            ),
+            (
+                self.status_out_of_memory,
+                "interpreter-resource-unavailable",
+                Reason::InterpreterResourceUnavailable,
+                Severity::Error,
+                "Out of memory",
+            ),
+            (
+                self.status_page_punt,
+                "PRINTER_STATUS_PAGE_PUNT",
+                Reason::PagePunt,
+                Severity::Error,
+                "Page punt",
+            ),
+            (
+                self.status_paper_jam,
+                "media-jam",
+                Reason::MediaJam,
+                Severity::Error,
+                "Paper jam",
+            ),
+            (
+                self.status_pending_deletion,
+                "PRINTER_STATUS_PENDING_DELETION",
+                Reason::PendingDeletion,
+                Severity::Error,
+                "Pending deletion",
+            ),
+            (
+                self.status_server_unknown,
+                "PRINTER_STATUS_SERVER_UNKNOWN",
+                Reason::ServerUnknown,
+                Severity::Error,
+                "Server unknown",
+            ),
+        ];
+        let flags = flags.iter().filter(|v| v.0).collect::<Vec<_>>();
+
+        PrinterStatus {
+            state,
+            reasons: flags
+                .iter()
+                .map(|(_, keyword, reason, severity, _)| StateReason {
+                    keyword: keyword.to_string(),
+                    reason: *reason,
+                    severity: *severity,
+                })
+                .collect(),
+            accepting_jobs: !self.status_pending_deletion,
+            changed: None,
+            message: flags.iter().map(|v| v.4).collect::<Vec<_>>().join(", "),
+        }
+    }
+}
+
+/// Checks the job parameters against capabilities(). There is no
+/// check by the spooler.
+pub fn validate_job(pr_name: &str, param: &JobParam) -> io::Result<Vec<Conflict>> {
+    Ok(param.validate(&capabilities(pr_name)?))
+}
+
+/// Status of the printer.
+pub fn printer_status(pr_name: &str) -> io::Result<PrinterStatus> {
+    Ok(printer_attr(pr_name)?.printer_status())
 }
 
 /// Extended attributes
@@ -668,6 +940,128 @@ pub fn printer_attr(pr_name: &str) -> io::Result<Info> {
     result
 }
 
+/// Status of a printjob.
+///
+/// The spooler removes a job when it's done, unless the printer keeps
+/// printed jobs. Then this fails with PrintError::NotFound.
+pub fn job_status(pr_name: &str, job_id: u32) -> io::Result<JobStatus> {
+    let pr_name = str_to_wstr(pr_name);
+    let mut pr_handle = 0;
+    let mut cb_needed = 0u32;
+
+    unsafe {
+        if OpenPrinterW(pr_name.as_ptr(), &mut pr_handle as *mut HANDLE, ptr::null()) == 0 {
+            return Err(PrintError::last_error());
+        }
+    }
+    let result = unsafe {
+        if GetJobW(
+            pr_handle,
+            job_id,
+            1,
+            ptr::null_mut(),
+            0,
+            &mut cb_needed as *mut u32,
+        ) == 0
+            && cb_needed > 0
+        {
+            let info_layout =
+                Layout::from_size_align(cb_needed as usize, align_of::<JOB_INFO_1W>())
+                    .map_err(|_| io::Error::new(ErrorKind::Other, PrintError::LayoutError))?;
+            let buf = alloc_zeroed(info_layout);
+
+            let result = if GetJobW(
+                pr_handle,
+                job_id,
+                1,
+                buf,
+                cb_needed,
+                &mut cb_needed as *mut u32,
+            ) != 0
+            {
+                Ok(copy_job_info(&*(buf as *mut JOB_INFO_1W)))
+            } else {
+                Err(PrintError::last_error())
+            };
+
+            dealloc(buf, info_layout);
+
+            result
+        } else if GetLastError() == ERROR_INVALID_PARAMETER {
+            // the job is gone
+            Err(PrintError::io_error(PrintError::NotFound))
+        } else {
+            Err(PrintError::last_error())
+        }
+    };
+
+    unsafe {
+        if ClosePrinter(pr_handle) == FALSE {
+            return Err(PrintError::last_error());
+        }
+    }
+
+    result
+}
+
+/// JobState from the job status flags.
+fn job_state(status: u32) -> JobState {
+    if status & (JOB_STATUS_PRINTED | JOB_STATUS_COMPLETE) != 0 {
+        JobState::Completed
+    } else if status & (JOB_STATUS_DELETING | JOB_STATUS_DELETED) != 0 {
+        JobState::Canceled
+    } else if status & JOB_STATUS_BLOCKED_DEVQ != 0 {
+        JobState::Aborted
+    } else if status
+        & (JOB_STATUS_ERROR
+            | JOB_STATUS_OFFLINE
+            | JOB_STATUS_PAPEROUT
+            | JOB_STATUS_USER_INTERVENTION)
+        != 0
+    {
+        JobState::Stopped
+    } else if status & JOB_STATUS_PAUSED != 0 {
+        JobState::Held
+    } else if status & (JOB_STATUS_PRINTING | JOB_STATUS_SPOOLING) != 0 {
+        JobState::Processing
+    } else {
+        JobState::Pending
+    }
+}
+
+fn copy_job_info(info: &JOB_INFO_1W) -> JobStatus {
+    let status = info.Status;
+    let state = job_state(status);
+
+    // the matching IPP job-state-reasons
+    let mut reasons = Vec::new();
+    for (flag, reason) in [
+        (JOB_STATUS_PAUSED, "job-hold-until-specified"),
+        (JOB_STATUS_SPOOLING, "job-incoming"),
+        (JOB_STATUS_PRINTING, "job-printing"),
+        (JOB_STATUS_ERROR, "printer-stopped"),
+        (JOB_STATUS_OFFLINE, "printer-stopped"),
+        (JOB_STATUS_PAPEROUT, "printer-stopped"),
+        (JOB_STATUS_USER_INTERVENTION, "printer-stopped"),
+        (JOB_STATUS_BLOCKED_DEVQ, "aborted-by-system"),
+        (JOB_STATUS_DELETING, "job-canceled-by-user"),
+        (JOB_STATUS_DELETED, "job-canceled-by-user"),
+        (JOB_STATUS_PRINTED, "job-completed-successfully"),
+        (JOB_STATUS_COMPLETE, "job-completed-successfully"),
+    ] {
+        if status & flag != 0 && !reasons.iter().any(|v| v == reason) {
+            reasons.push(reason.to_string());
+        }
+    }
+
+    JobStatus {
+        job_id: info.JobId,
+        state,
+        reasons,
+        impressions_completed: info.PagesPrinted,
+    }
+}
+
 fn copy_info(info: &PRINTER_INFO_2W) -> Info {
     let mut res = Info::default();
 
@@ -858,6 +1252,167 @@ fn copy_info(info: &PRINTER_INFO_2W) -> Info {
     res
 }
 
+/// Jobs of a printer.
+///
+/// The spooler only keeps completed jobs if the printer is set up to keep
+/// printed jobs.
+pub fn list_jobs(pr_name: &str, which: WhichJobs) -> io::Result<Vec<JobInfo>> {
+    with_printer(pr_name, PRINTER_ACCESS_USE, |pr_handle| unsafe {
+        let mut cb_needed = 0u32;
+        let mut c_returned = 0u32;
+        if EnumJobsW(
+            pr_handle,
+            0,
+            u32::MAX,
+            2,
+            ptr::null_mut(),
+            0,
+            &mut cb_needed as *mut u32,
+            &mut c_returned as *mut u32,
+        ) == 0
+            && cb_needed == 0
+        {
+            return Err(PrintError::last_error());
+        }
+        if cb_needed == 0 {
+            return Ok(Vec::new());
+        }
+
+        let info_layout = Layout::from_size_align(cb_needed as usize, align_of::<JOB_INFO_2W>())
+            .map_err(|_| io::Error::new(ErrorKind::Other, PrintError::LayoutError))?;
+        let buf = alloc_zeroed(info_layout);
+
+        let result = if EnumJobsW(
+            pr_handle,
+            0,
+            u32::MAX,
+            2,
+            buf,
+            cb_needed,
+            &mut cb_needed as *mut u32,
+            &mut c_returned as *mut u32,
+        ) != 0
+        {
+            let jobs = &*slice_from_raw_parts(buf as *const JOB_INFO_2W, c_returned as usize);
+            Ok(jobs
+                .iter()
+                .map(copy_job_info_2)
+                .filter(|v| match which {
+                    WhichJobs::Active => !v.state.is_final(),
+                    WhichJobs::Completed => v.state.is_final(),
+                    WhichJobs::All => true,
+                })
+                .collect())
+        } else {
+            Err(PrintError::last_error())
+        };
+
+        dealloc(buf, info_layout);
+
+        result
+    })
+}
+
+fn copy_job_info_2(info: &JOB_INFO_2W) -> JobInfo {
+    let mut file_time = FILETIME {
+        dwLowDateTime: 0,
+        dwHighDateTime: 0,
+    };
+    let created = unsafe {
+        if SystemTimeToFileTime(&info.Submitted as *const SYSTEMTIME, &mut file_time) != 0 {
+            // 100ns intervals since 1601-01-01
+            let ticks = ((file_time.dwHighDateTime as u64) << 32) | file_time.dwLowDateTime as u64;
+            (ticks / 10_000_000)
+                .checked_sub(11_644_473_600)
+                .map(|v| SystemTime::UNIX_EPOCH + Duration::from_secs(v))
+        } else {
+            None
+        }
+    };
+
+    JobInfo {
+        job_id: info.JobId,
+        name: unsafe { wstr_to_string(info.pDocument) },
+        user: unsafe { wstr_to_string(info.pUserName) },
+        size: info.Size as u64,
+        state: job_state(info.Status),
+        created,
+    }
+}
+
+/// Cancels a job.
+pub fn cancel_job(pr_name: &str, job_id: u32) -> io::Result<()> {
+    set_job(pr_name, job_id, JOB_CONTROL_DELETE)
+}
+
+/// Cancels all jobs of a printer. Needs the right to administer the
+/// printer.
+pub fn cancel_all_jobs(pr_name: &str) -> io::Result<()> {
+    with_printer(pr_name, PRINTER_ACCESS_ADMINISTER, |pr_handle| unsafe {
+        if SetPrinterW(pr_handle, 0, ptr::null_mut(), PRINTER_CONTROL_PURGE) != 0 {
+            Ok(())
+        } else {
+            Err(PrintError::last_error())
+        }
+    })
+}
+
+/// Holds a job until it's released.
+pub fn hold_job(pr_name: &str, job_id: u32) -> io::Result<()> {
+    set_job(pr_name, job_id, JOB_CONTROL_PAUSE)
+}
+
+/// Releases a held job.
+pub fn release_job(pr_name: &str, job_id: u32) -> io::Result<()> {
+    set_job(pr_name, job_id, JOB_CONTROL_RESUME)
+}
+
+fn set_job(pr_name: &str, job_id: u32, command: u32) -> io::Result<()> {
+    with_printer(pr_name, PRINTER_ACCESS_USE, |pr_handle| unsafe {
+        if SetJobW(pr_handle, job_id, 0, ptr::null_mut(), command) != 0 {
+            Ok(())
+        } else {
+            Err(PrintError::last_error())
+        }
+    })
+}
+
+/// Opens the printer for the duration of f.
+fn with_printer<T>(
+    pr_name: &str,
+    access: u32,
+    f: impl FnOnce(HANDLE) -> io::Result<T>,
+) -> io::Result<T> {
+    let pr_name = str_to_wstr(pr_name);
+    let mut pr_handle = 0;
+    let defaults = PRINTER_DEFAULTSW {
+        pDatatype: ptr::null_mut(),
+        pDevMode: ptr::null_mut(),
+        DesiredAccess: access,
+    };
+
+    unsafe {
+        if OpenPrinterW(
+            pr_name.as_ptr(),
+            &mut pr_handle as *mut HANDLE,
+            &defaults as *const PRINTER_DEFAULTSW,
+        ) == 0
+        {
+            return Err(PrintError::last_error());
+        }
+    }
+
+    let result = f(pr_handle);
+
+    unsafe {
+        if ClosePrinter(pr_handle) == FALSE {
+            return Err(PrintError::last_error());
+        }
+    }
+
+    result
+}
+
 /// List local printers.
 pub fn list_printers() -> io::Result<Vec<String>> {
     let mut r = Vec::new();
@@ -908,9 +1463,189 @@ pub fn list_printers() -> io::Result<Vec<String>> {
     Ok(r)
 }
 
+/// Supported values of the printer, via DeviceCapabilitiesW.
+///
+/// The document formats are the datatypes of the print processor.
+/// Windows knows no margins per paper.
+pub fn capabilities(pr_name: &str) -> io::Result<Capabilities> {
+    let info = printer_attr(pr_name)?;
+    let device = str_to_wstr(pr_name);
+    let port = str_to_wstr(&info.port_name);
+
+    let mut caps = Capabilities::default();
+
+    unsafe {
+        let papers = device_caps::<u16>(&device, &port, DC_PAPERS, 1)?;
+        let names = device_caps::<wchar_t>(&device, &port, DC_PAPERNAMES, 64)?;
+        let sizes = device_caps::<i32>(&device, &port, DC_PAPERSIZE, 2)?;
+        for (i, paper) in papers.iter().enumerate() {
+            let name = match names.get(i * 64..(i + 1) * 64) {
+                Some(v) => wnstr_to_string(v.as_ptr(), 64),
+                None => String::new(),
+            };
+            let (width, length) = match sizes.get(i * 2..(i + 1) * 2) {
+                Some([x, y]) => (tenth_mm(*x), tenth_mm(*y)),
+                _ => (Length::ZERO, Length::ZERO),
+            };
+            caps.media.push(Media {
+                name,
+                paper_size: Some(PaperSize::Numeric(*paper as i16)),
+                width,
+                length,
+                margins: None,
+            });
+        }
+
+        // POINTS in 1/10mm, x in the low word.
+        let extent = |v: i32| (tenth_mm(v & 0xffff), tenth_mm((v >> 16) & 0xffff));
+        let min = device_caps_value(&device, &port, DC_MINEXTENT);
+        let max = device_caps_value(&device, &port, DC_MAXEXTENT);
+        if min > 0 && max > 0 {
+            caps.custom_size = Some((extent(min), extent(max)));
+        }
+
+        caps.sides = if device_caps_value(&device, &port, DC_DUPLEX) == 1 {
+            vec![
+                Duplex::Simplex,
+                Duplex::TwoSidedPortrait,
+                Duplex::TwoSidedLandscape,
+            ]
+        } else {
+            vec![Duplex::Simplex]
+        };
+        caps.color_modes = if device_caps_value(&device, &port, DC_COLORDEVICE) == 1 {
+            vec![ColorMode::Monochrome, ColorMode::Color]
+        } else {
+            vec![ColorMode::Monochrome]
+        };
+        // the DMRES values are understood by all drivers.
+        caps.qualities = vec![Quality::Draft, Quality::Low, Quality::Normal, Quality::High];
+        caps.resolutions = device_caps::<i32>(&device, &port, DC_ENUMRESOLUTIONS, 2)?
+            .chunks_exact(2)
+            .map(|v| (v[0].max(0) as u32, v[1].max(0) as u32))
+            .collect();
+        caps.copies = match device_caps_value(&device, &port, DC_COPIES) {
+            v if v > 0 => Some((1, v as u32)),
+            _ => None,
+        };
+    }
+
+    caps.document_formats = print_processor_datatypes(&info.print_processor)?;
+
+    Ok(caps)
+}
+
+/// DeviceCapabilitiesW for a list of values. Each item of the list
+/// has the size of per_item T.
+///
+/// Safety
+/// T must match the output of the capability.
+unsafe fn device_caps<T: Copy + Default>(
+    device: &[wchar_t],
+    port: &[wchar_t],
+    capability: PRINTER_DEVICE_CAPABILITIES,
+    per_item: usize,
+) -> io::Result<Vec<T>> {
+    unsafe {
+        let n = DeviceCapabilitiesW(
+            device.as_ptr(),
+            port.as_ptr(),
+            capability,
+            ptr::null_mut(),
+            ptr::null(),
+        );
+        if n < 0 {
+            return Err(PrintError::last_error());
+        }
+
+        let mut buf = vec![T::default(); n as usize * per_item];
+        let n = DeviceCapabilitiesW(
+            device.as_ptr(),
+            port.as_ptr(),
+            capability,
+            buf.as_mut_ptr() as PWSTR,
+            ptr::null(),
+        );
+        if n < 0 {
+            return Err(PrintError::last_error());
+        }
+        buf.truncate(n as usize * per_item);
+
+        Ok(buf)
+    }
+}
+
+/// DeviceCapabilitiesW for a single value. -1 for errors.
+///
+/// Safety
+/// The capability must not write to the output.
+unsafe fn device_caps_value(
+    device: &[wchar_t],
+    port: &[wchar_t],
+    capability: PRINTER_DEVICE_CAPABILITIES,
+) -> i32 {
+    unsafe {
+        DeviceCapabilitiesW(
+            device.as_ptr(),
+            port.as_ptr(),
+            capability,
+            ptr::null_mut(),
+            ptr::null(),
+        )
+    }
+}
+
+/// Datatypes of a print processor.
+fn print_processor_datatypes(print_processor: &str) -> io::Result<Vec<String>> {
+    let print_processor = str_to_wstr(print_processor);
+    let mut r = Vec::new();
+
+    unsafe {
+        let mut cb_needed = 0u32;
+        let mut c_returned = 0u32;
+
+        if EnumPrintProcessorDatatypesW(
+            ptr::null(),
+            print_processor.as_ptr(),
+            1,
+            ptr::null_mut(),
+            0,
+            &mut cb_needed as *mut u32,
+            &mut c_returned as *mut u32,
+        ) == FALSE
+            && cb_needed > 0
+        {
+            let info_layout =
+                Layout::from_size_align(cb_needed as usize, align_of::<DATATYPES_INFO_1W>())
+                    .map_err(|_| io::Error::new(ErrorKind::Other, PrintError::LayoutError))?;
+            let buf = alloc_zeroed(info_layout);
+
+            if EnumPrintProcessorDatatypesW(
+                ptr::null(),
+                print_processor.as_ptr(),
+                1,
+                buf,
+                cb_needed,
+                &mut cb_needed as *mut u32,
+                &mut c_returned as *mut u32,
+            ) == TRUE
+            {
+                for i in 0..c_returned as isize {
+                    let info = &*(buf as *mut DATATYPES_INFO_1W).offset(i);
+                    r.push(wstr_to_string(info.pName))
+                }
+                dealloc(buf, info_layout);
+            } else {
+                dealloc(buf, info_layout);
+                return Err(PrintError::last_error());
+            }
+        }
+    }
+
+    Ok(r)
+}
+
 // EnumPrintProcessors
-// EnumPrintProcessorDatatypes
-// DeviceCapabilitiesA
 // EnumForms
 
 /// Printjob.
@@ -918,14 +1653,21 @@ pub fn list_printers() -> io::Result<Vec<String>> {
 /// see <https://learn.microsoft.com/en-us/windows/win32/printdocs/printing-and-print-spooler-reference>
 #[derive(Debug)]
 pub struct WindowsPrintJob {
+    pr_name: String,
     printer: HANDLE,
     data_format: Vec<wchar_t>,
     job_id: u32,
+    lifecycle: Lifecycle,
 }
 
 impl Write for WindowsPrintJob {
     /// Write data to the printer.
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.lifecycle.is_done() {
+            return Err(PrintError::io_error(PrintError::Closed));
+        }
+        self.lifecycle = Lifecycle::Writing;
+
         let mut written = 0u32;
 
         unsafe {
@@ -949,10 +1691,12 @@ impl Write for WindowsPrintJob {
 }
 
 impl Drop for WindowsPrintJob {
-    /// Closes the printjob and sends it to the printer.
+    /// Aborts the printjob if it was not closed successfully.
     /// Any error is eaten. Use close() directly for error-handling.
     fn drop(&mut self) {
-        let _ = self.close();
+        if !self.lifecycle.is_done() {
+            let _ = self.abort();
+        }
     }
 }
 
@@ -965,16 +1709,19 @@ impl WindowsPrintJob {
     }
 
     pub fn new_with(pr_name: &str, doc_name: &str, param: &JobParam) -> io::Result<Self> {
-        let mut job = Self::open_printer(pr_name, param)?;
+        let param = param.prepare(|| capabilities(pr_name))?;
+        let mut job = Self::open_printer(pr_name, &param)?;
         job.start_doc(doc_name)?;
         Ok(job)
     }
 
     fn open_printer(pr_name: &str, param: &JobParam) -> io::Result<Self> {
         let mut print = WindowsPrintJob {
+            pr_name: pr_name.to_string(),
             printer: 0,
             data_format: Default::default(),
             job_id: 0,
+            lifecycle: Lifecycle::Created,
         };
 
         let data_format = match param.data_format {
@@ -1063,14 +1810,17 @@ impl WindowsPrintJob {
                 devmode.Anonymous1.Anonymous1.dmCopies = copies as i16;
             }
         }
-        if let Some(paper_size) = param.paper_size.clone() {
-            let paper_size = match paper_size {
-                PaperSize::Numeric(n) => n,
-                _ => paper_size.discriminant() as i16,
-            };
+        if let Some(paper_size) = &param.paper_size {
             unsafe {
                 devmode.dmFields |= DM_PAPERSIZE;
-                devmode.Anonymous1.Anonymous1.dmPaperSize = paper_size;
+                devmode.Anonymous1.Anonymous1.dmPaperSize = paper_size.number();
+            }
+            if let PaperSize::Custom { width, height } = paper_size {
+                unsafe {
+                    devmode.dmFields |= DM_PAPERWIDTH | DM_PAPERLENGTH;
+                    devmode.Anonymous1.Anonymous1.dmPaperWidth = devmode_length(*width);
+                    devmode.Anonymous1.Anonymous1.dmPaperLength = devmode_length(*height);
+                }
             }
         }
         if let Some(paper_source) = param.paper_source.clone() {
@@ -1130,13 +1880,13 @@ impl WindowsPrintJob {
         if let Some(paper_length) = param.paper_length {
             unsafe {
                 devmode.dmFields |= DM_PAPERLENGTH;
-                devmode.Anonymous1.Anonymous1.dmPaperLength = paper_length;
+                devmode.Anonymous1.Anonymous1.dmPaperLength = devmode_length(paper_length);
             }
         }
         if let Some(paper_width) = param.paper_width {
             unsafe {
                 devmode.dmFields |= DM_PAPERWIDTH;
-                devmode.Anonymous1.Anonymous1.dmPaperWidth = paper_width;
+                devmode.Anonymous1.Anonymous1.dmPaperWidth = devmode_length(paper_width);
             }
         }
         if let Some(scale) = param.scale {
@@ -1216,7 +1966,7 @@ impl WindowsPrintJob {
     /// Close the current printjob
     pub fn close_doc(&mut self) -> io::Result<()> {
         unsafe {
-            if self.job_id == 0 {
+            if self.job_id == 0 || self.lifecycle.is_done() {
                 return Ok(());
             }
 
@@ -1233,6 +1983,8 @@ impl WindowsPrintJob {
         unsafe {
             self.close_doc()?;
 
+            self.lifecycle = Lifecycle::Closed;
+
             if self.printer == 0 {
                 return Ok(());
             }
@@ -1244,6 +1996,104 @@ impl WindowsPrintJob {
             }
         }
     }
+
+    /// The job-id.
+    pub fn job_id(&self) -> u32 {
+        self.job_id
+    }
+
+    /// Waits until the job is printed.
+    ///
+    /// A job that vanished from the queue counts as printed.
+    /// Fails with PrintError::JobFailed if the job was canceled or aborted,
+    /// and with PrintError::Timeout if it takes too long.
+    pub fn wait_for_completion(&self, timeout: Duration) -> io::Result<()> {
+        wait_for_job(timeout, || match job_status(&self.pr_name, self.job_id) {
+            Err(e)
+                if matches!(
+                    e.get_ref().and_then(|v| v.downcast_ref::<PrintError>()),
+                    Some(PrintError::NotFound)
+                ) =>
+            {
+                Ok(JobStatus {
+                    job_id: self.job_id,
+                    state: JobState::Completed,
+                    reasons: Vec::new(),
+                    impressions_completed: 0,
+                })
+            }
+            v => v,
+        })
+    }
+
+    /// Closes the printjob and waits until it is printed.
+    pub fn close_and_wait(&mut self, timeout: Duration) -> io::Result<()> {
+        self.close()?;
+        self.wait_for_completion(timeout)
+    }
+
+    /// State of the printjob.
+    pub fn lifecycle(&self) -> Lifecycle {
+        self.lifecycle
+    }
+
+    /// Cancel the printjob. Deletes the spoolfile and closes the printer.
+    pub fn abort(&mut self) -> io::Result<()> {
+        unsafe {
+            if self.printer == 0 {
+                return Ok(());
+            }
+            // the printer is closed even if AbortPrinter fails, otherwise
+            // the handle leaks. Aborted only when both went through.
+            let result = if 0 == AbortPrinter(self.printer) {
+                Err(PrintError::last_error())
+            } else {
+                Ok(())
+            };
+            // no EndDocPrinter after AbortPrinter.
+            self.job_id = 0;
+
+            if 0 != ClosePrinter(self.printer) {
+                self.printer = 0;
+                if result.is_ok() {
+                    self.lifecycle = Lifecycle::Aborted;
+                }
+                result
+            } else {
+                Err(PrintError::last_error())
+            }
+        }
+    }
+}
+
+impl Backend for WindowsPrintJob {
+    fn start_page(&mut self) -> io::Result<()> {
+        WindowsPrintJob::start_page(self)
+    }
+
+    fn end_page(&mut self) -> io::Result<()> {
+        WindowsPrintJob::end_page(self)
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        WindowsPrintJob::close(self)
+    }
+
+    fn abort(&mut self) -> io::Result<()> {
+        WindowsPrintJob::abort(self)
+    }
+}
+
+/// Sizes from DeviceCapabilities are in 1/10mm.
+fn tenth_mm(v: i32) -> Length {
+    Length::mm(0.1) * v.max(0) as i64
+}
+
+/// The DEVMODE paper sizes are in 1/10mm.
+fn devmode_length(v: Length) -> i16 {
+    v.count(Length::mm(0.1))
+        .unwrap_or_default()
+        .clamp(0, i16::MAX as i64) as i16
 }
 
 /// Length of a wchar_t string with a maximum buffer size.
@@ -0,0 +1,208 @@
+#![allow(dead_code)]
+
+//! A stand-in IPP server for the tests.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use uniprint::ipp::{Attribute, GroupTag, Message, Operation, StatusCode, Value};
+
+/// Received requests: (request, document data)
+pub(crate) type Requests = Vec<(Message, Vec<u8>)>;
+
+/// Answers n requests with the response from the closure.
+pub(crate) fn ipp_server<F>(
+    n: usize,
+    chunked: bool,
+    answer: F,
+) -> (String, thread::JoinHandle<Requests>)
+where
+    F: Fn(&Message) -> Message + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let uri = format!(
+        "ipp://{}/printers/test",
+        listener.local_addr().expect("addr")
+    );
+
+    let server = thread::spawn(move || {
+        let mut requests = Vec::new();
+        serve(&listener, n, chunked, &answer, &mut requests);
+        requests
+    });
+
+    (uri, server)
+}
+
+/// Answers any number of requests, for when the count is not known
+/// in advance. The requests are logged before they are answered.
+pub(crate) fn ipp_log_server<F>(answer: F) -> (String, Arc<Mutex<Vec<Message>>>)
+where
+    F: Fn(&Message) -> Message + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let uri = format!(
+        "ipp://{}/printers/test",
+        listener.local_addr().expect("addr")
+    );
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let server_log = log.clone();
+    let answer = move |req: &Message| {
+        server_log.lock().expect("log").push(req.clone());
+        answer(req)
+    };
+    thread::spawn(move || loop {
+        serve(&listener, 1, false, &answer, &mut Vec::new());
+    });
+
+    (uri, log)
+}
+
+fn serve<F>(listener: &TcpListener, n: usize, chunked: bool, answer: &F, requests: &mut Requests)
+where
+    F: Fn(&Message) -> Message,
+{
+    for _ in 0..n {
+        let (conn, _) = listener.accept().expect("accept");
+        let mut wr = conn.try_clone().expect("clone");
+        let mut rd = BufReader::new(conn);
+
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            rd.read_line(&mut line).expect("header");
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(v) = line.strip_prefix("Content-Length: ") {
+                len = v.parse().expect("len");
+            }
+        }
+        let mut body = vec![0u8; len];
+        rd.read_exact(&mut body).expect("body");
+
+        let (req, n) = Message::decode(&body).expect("decode");
+        let res = answer(&req).encode().expect("encode");
+        if chunked {
+            let (a, b) = res.split_at(res.len() / 2);
+            write!(
+                wr,
+                "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\nContent-Type: application/ipp\r\nTransfer-Encoding: chunked\r\n\r\n"
+            )
+            .expect("write");
+            for chunk in [a, b] {
+                write!(wr, "{:x}\r\n", chunk.len()).expect("write");
+                wr.write_all(chunk).expect("write");
+                write!(wr, "\r\n").expect("write");
+            }
+            write!(wr, "0\r\n\r\n").expect("write");
+        } else {
+            write!(
+                wr,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/ipp\r\nContent-Length: {}\r\n\r\n",
+                res.len()
+            )
+            .expect("write");
+            wr.write_all(&res).expect("write");
+        }
+
+        requests.push((req, body[n..].to_vec()));
+    }
+}
+
+/// Successful response, with a job-id for job creation.
+pub(crate) fn answer_ok(req: &Message) -> Message {
+    let mut res = Message::response(StatusCode::OK, req.request_id);
+    match req.operation() {
+        Operation::PrintJob | Operation::CreateJob => {
+            res.add(GroupTag::Job, Attribute::new("job-id", Value::Integer(42)));
+        }
+        Operation::GetPrinterAttributes => {
+            res.add(
+                GroupTag::Printer,
+                Attribute::new("printer-state", Value::Enum(3)),
+            );
+            res.add(
+                GroupTag::Printer,
+                Attribute::with_values(
+                    "sides-supported",
+                    vec![
+                        Value::Keyword("one-sided".into()),
+                        Value::Keyword("two-sided-long-edge".into()),
+                    ],
+                ),
+            );
+        }
+        _ => {}
+    }
+    res
+}
+
+/// Answers n requests. Without the expected Authorization header the
+/// answer is a 401 with the challenges, otherwise answer_ok.
+/// Returns the Authorization headers received.
+pub(crate) fn auth_server(
+    n: usize,
+    challenges: &'static [&'static str],
+    expect: &'static str,
+) -> (String, thread::JoinHandle<Vec<Option<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let uri = format!(
+        "ipp://{}/printers/test",
+        listener.local_addr().expect("addr")
+    );
+
+    let server = thread::spawn(move || {
+        let mut auth = Vec::new();
+        for _ in 0..n {
+            let (conn, _) = listener.accept().expect("accept");
+            let mut wr = conn.try_clone().expect("clone");
+            let mut rd = BufReader::new(conn);
+
+            let mut len = 0;
+            let mut authorization = None;
+            loop {
+                let mut line = String::new();
+                rd.read_line(&mut line).expect("header");
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(v) = line.strip_prefix("Content-Length: ") {
+                    len = v.parse().expect("len");
+                }
+                if let Some(v) = line.strip_prefix("Authorization: ") {
+                    authorization = Some(v.to_string());
+                }
+            }
+            let mut body = vec![0u8; len];
+            rd.read_exact(&mut body).expect("body");
+
+            if authorization.as_deref() == Some(expect) {
+                let (req, _) = Message::decode(&body).expect("decode");
+                let res = answer_ok(&req).encode().expect("encode");
+                write!(
+                    wr,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/ipp\r\nContent-Length: {}\r\n\r\n",
+                    res.len()
+                )
+                .expect("write");
+                wr.write_all(&res).expect("write");
+            } else {
+                write!(wr, "HTTP/1.1 401 Unauthorized\r\n").expect("write");
+                for challenge in challenges {
+                    write!(wr, "WWW-Authenticate: {}\r\n", challenge).expect("write");
+                }
+                write!(wr, "Content-Length: 0\r\n\r\n").expect("write");
+            }
+            auth.push(authorization);
+        }
+        auth
+    });
+
+    (uri, server)
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::auth_server;
+use uniprint::ipp::IppClient;
+use uniprint::{clear_credentials_provider, set_credentials_provider, Credentials, PrintError};
+
+fn is_auth_required(err: &std::io::Error) -> bool {
+    matches!(
+        err.get_ref().and_then(|v| v.downcast_ref::<PrintError>()),
+        Some(PrintError::AuthenticationRequired)
+    )
+}
+
+/// The provider is global, so everything runs in one test.
+#[test]
+fn test_ipp_auth() {
+    // no provider
+    let (uri, server) = auth_server(1, &["Basic realm=\"CUPS\""], "");
+    let client = IppClient::new(&uri).expect("client");
+    let err = client.get_printer_attributes(&[]).expect_err("auth");
+    assert!(is_auth_required(&err));
+    assert_eq!(server.join().expect("server"), vec![None]);
+
+    set_credentials_provider(|host| {
+        (host == "127.0.0.1").then(|| Credentials::new("user", "secret"))
+    });
+
+    // basic
+    let (uri, server) = auth_server(2, &["Basic realm=\"CUPS\""], "Basic dXNlcjpzZWNyZXQ=");
+    let client = IppClient::new(&uri).expect("client");
+    let attr = client.get_printer_attributes(&[]).expect("attr");
+    assert_eq!(attr[0].name, "printer-state");
+    assert_eq!(
+        server.join().expect("server"),
+        vec![None, Some("Basic dXNlcjpzZWNyZXQ=".to_string())]
+    );
+
+    // digest without qop, preferred over basic
+    let expect = "Digest username=\"user\", realm=\"CUPS\", nonce=\"abc\", uri=\"/printers/test\", response=\"39223f98ffa6eae1f7174e4a9fa99c83\"";
+    let (uri, server) = auth_server(
+        2,
+        &[
+            "Basic realm=\"CUPS\"",
+            "Digest realm=\"CUPS\", nonce=\"abc\"",
+        ],
+        expect,
+    );
+    let client = IppClient::new(&uri).expect("client");
+    client.get_printer_attributes(&[]).expect("attr");
+    server.join().expect("server");
+
+    // digest with qop, rejected once more
+    let (uri, server) = auth_server(
+        2,
+        &["Digest realm=\"CUPS\", nonce=\"abc\", qop=\"auth,auth-int\", opaque=\"xyz\""],
+        "",
+    );
+    let client = IppClient::new(&uri).expect("client");
+    let err = client.get_printer_attributes(&[]).expect_err("auth");
+    assert!(is_auth_required(&err));
+    let auth = server.join().expect("server");
+    let digest = auth[1].as_deref().expect("digest");
+    assert!(digest.starts_with("Digest username=\"user\", realm=\"CUPS\", nonce=\"abc\""));
+    assert!(digest.contains(", qop=auth, nc=00000001, cnonce=\""));
+    assert!(digest.ends_with(", opaque=\"xyz\""));
+
+    // quotes and backslashes are escaped
+    set_credentials_provider(|_| Some(Credentials::new("us\"er\\", "secret")));
+    let (uri, server) = auth_server(2, &["Digest realm=\"CU\\\"PS\", nonce=\"abc\""], "");
+    let client = IppClient::new(&uri).expect("client");
+    let err = client.get_printer_attributes(&[]).expect_err("auth");
+    assert!(is_auth_required(&err));
+    let auth = server.join().expect("server");
+    let digest = auth[1].as_deref().expect("digest");
+    assert!(digest.starts_with(r#"Digest username="us\"er\\", realm="CU\"PS", nonce="abc""#));
+
+    // a line break can't be sent at all
+    set_credentials_provider(|_| Some(Credentials::new("user\r\nX-Admin: 1", "secret")));
+    let (uri, server) = auth_server(1, &["Digest realm=\"CUPS\", nonce=\"abc\""], "");
+    let client = IppClient::new(&uri).expect("client");
+    let err = client.get_printer_attributes(&[]).expect_err("auth");
+    assert!(is_auth_required(&err));
+    assert_eq!(server.join().expect("server"), vec![None]);
+
+    clear_credentials_provider();
+}
@@ -0,0 +1,175 @@
+#![cfg(all(target_os = "linux", feature = "cups"))]
+
+//! libcups talks to the stand-in server via CUPS_SERVER.
+
+mod common;
+
+use common::{ipp_log_server, ipp_server};
+use uniprint::ipp::{Attribute, Group, GroupTag, Message, Operation, StatusCode, Value};
+use uniprint::{
+    cancel_job, job_status, list_jobs, printer_attr_on, CupsConnection, Encryption, JobParam,
+    JobState, PrintJob, WhichJobs,
+};
+
+fn answer(req: &Message) -> Message {
+    let mut res = Message::response(StatusCode::OK, req.request_id);
+    match req.operation() {
+        Operation::GetJobs => {
+            for id in [7, 8] {
+                res.groups.push(Group::new(GroupTag::Job));
+                res.add(GroupTag::Job, Attribute::new("job-id", Value::Integer(id)));
+                res.add(
+                    GroupTag::Job,
+                    Attribute::new("job-name", Value::Name("label".into())),
+                );
+                res.add(GroupTag::Job, Attribute::new("job-state", Value::Enum(3)));
+                res.add(
+                    GroupTag::Job,
+                    Attribute::new("job-k-octets", Value::Integer(2)),
+                );
+            }
+        }
+        Operation::CancelJob => {}
+        Operation::GetJobAttributes => {
+            res.add(GroupTag::Job, Attribute::new("job-id", Value::Integer(7)));
+            res.add(GroupTag::Job, Attribute::new("job-state", Value::Enum(9)));
+            res.add(
+                GroupTag::Job,
+                Attribute::new(
+                    "job-state-reasons",
+                    Value::Keyword("job-completed-successfully".into()),
+                ),
+            );
+            res.add(
+                GroupTag::Job,
+                Attribute::new("job-impressions-completed", Value::Integer(3)),
+            );
+        }
+        _ => {
+            // libcups connects to the printer-uri-supported of the queue.
+            let uri = req
+                .attribute(GroupTag::Operation, "printer-uri")
+                .and_then(|v| v.value())
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            res.add(
+                GroupTag::Printer,
+                Attribute::new("printer-name", Value::Name("label".into())),
+            );
+            res.add(
+                GroupTag::Printer,
+                Attribute::new("printer-uri-supported", Value::Uri(uri)),
+            );
+            res.add(
+                GroupTag::Printer,
+                Attribute::new("printer-state", Value::Enum(3)),
+            );
+        }
+    }
+    res
+}
+
+#[test]
+fn test_cups() {
+    let (uri, server) = ipp_server(6, false, answer);
+    let addr = uri
+        .strip_prefix("ipp://")
+        .and_then(|v| v.split_once('/'))
+        .map(|v| v.0)
+        .expect("addr");
+    std::env::set_var("CUPS_SERVER", addr);
+
+    let status = job_status("label", 7).expect("status");
+    assert_eq!(status.job_id, 7);
+    assert_eq!(status.state, JobState::Completed);
+    assert_eq!(status.reasons, vec!["job-completed-successfully"]);
+    assert_eq!(status.impressions_completed, 3);
+
+    let jobs = list_jobs("label", WhichJobs::Active).expect("jobs");
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[1].job_id, 8);
+    assert_eq!(jobs[1].name, "label");
+    assert_eq!(jobs[1].size, 2048);
+    assert_eq!(jobs[1].state, JobState::Pending);
+
+    cancel_job("label", 8).expect("cancel");
+
+    let requests = server.join().expect("server");
+    assert_eq!(requests[3].0.operation(), Operation::GetJobs);
+    assert_eq!(requests[5].0.operation(), Operation::CancelJob);
+    let (req, _) = &requests[1];
+    assert_eq!(req.operation(), Operation::GetJobAttributes);
+    assert_eq!(
+        req.attribute(GroupTag::Operation, "job-id")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_i32()),
+        Some(7)
+    );
+}
+
+#[test]
+fn test_cups_connection() {
+    let (uri, server) = ipp_server(1, false, answer);
+    let port = uri
+        .strip_prefix("ipp://127.0.0.1:")
+        .and_then(|v| v.split_once('/'))
+        .and_then(|v| v.0.parse::<u16>().ok())
+        .expect("port");
+    let conn = CupsConnection::new("127.0.0.1", port, Encryption::Never);
+
+    let info = printer_attr_on(&conn, "label").expect("attr");
+    assert_eq!(info.printer_name, "label");
+
+    let requests = server.join().expect("server");
+    assert_eq!(requests[0].0.operation(), Operation::GetPrinterAttributes);
+}
+
+#[test]
+fn test_cups_connection_uri() {
+    let (uri, log) = ipp_log_server(answer);
+    let port = uri
+        .strip_prefix("ipp://127.0.0.1:")
+        .and_then(|v| v.split_once('/'))
+        .and_then(|v| v.0.parse::<u16>().ok())
+        .expect("port");
+    let conn = CupsConnection::new("127.0.0.1", port, Encryption::Never);
+
+    // auto_correct asks for the capabilities first.
+    let param = JobParam {
+        auto_correct: true,
+        ..Default::default()
+    };
+    _ = PrintJob::new_on(&conn, "my label", "doc", &param);
+
+    let log = log.lock().expect("log");
+    assert_eq!(
+        log[0]
+            .attribute(GroupTag::Operation, "printer-uri")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str()),
+        Some(format!("ipp://127.0.0.1:{}/printers/my%20label", port).as_str())
+    );
+}
+
+#[test]
+fn test_cups_create_failed() {
+    let (uri, log) = ipp_log_server(|req| match req.operation() {
+        Operation::CreateJob => {
+            Message::response(StatusCode::CLIENT_ERROR_NOT_POSSIBLE, req.request_id)
+        }
+        _ => answer(req),
+    });
+    let port = uri
+        .strip_prefix("ipp://127.0.0.1:")
+        .and_then(|v| v.split_once('/'))
+        .and_then(|v| v.0.parse::<u16>().ok())
+        .expect("port");
+    let conn = CupsConnection::new("127.0.0.1", port, Encryption::Never);
+
+    assert!(PrintJob::new_on(&conn, "label", "doc", &JobParam::default()).is_err());
+
+    // no Cancel-Job for job-id 0, that would cancel the current job.
+    let log = log.lock().expect("log");
+    assert!(log.iter().all(|v| v.operation() != Operation::CancelJob));
+}
@@ -0,0 +1,216 @@
+#![cfg(all(target_os = "linux", not(feature = "cups")))]
+
+//! Without libcups the local cupsd is used via IPP.
+
+mod common;
+
+use std::io::{ErrorKind, Write};
+
+use common::ipp_server;
+use uniprint::ipp::{Attribute, GroupTag, Message, Operation, StatusCode, Value};
+use uniprint::{
+    default_printer, job_status, list_printers, list_printers_on, print_reader_progress,
+    printer_attr, printer_attr_on, CupsConnection, Encryption, Format, JobParam, JobState,
+    PrintError, PrintJob, Status,
+};
+
+fn answer(req: &Message) -> Message {
+    let mut res = Message::response(StatusCode::OK, req.request_id);
+    let name = |v: &str| Attribute::new("printer-name", Value::Name(v.into()));
+    match req.operation() {
+        Operation::CupsGetPrinters => {
+            res.add(GroupTag::Printer, name("label"));
+            res.groups
+                .push(uniprint::ipp::Group::new(GroupTag::Printer));
+            res.add(GroupTag::Printer, name("office"));
+        }
+        Operation::CupsGetDefault => {
+            res.add(GroupTag::Printer, name("office"));
+        }
+        Operation::GetPrinterAttributes => {
+            res.add(GroupTag::Printer, name("label"));
+            res.add(
+                GroupTag::Printer,
+                Attribute::new("printer-state", Value::Enum(4)),
+            );
+            res.add(
+                GroupTag::Printer,
+                Attribute::new("printer-is-accepting-jobs", Value::Boolean(true)),
+            );
+            res.add(
+                GroupTag::Printer,
+                Attribute::new("copies-default", Value::Integer(2)),
+            );
+            res.add(
+                GroupTag::Printer,
+                Attribute::with_values(
+                    "document-format-supported",
+                    vec![
+                        Value::MimeMediaType("application/pdf".into()),
+                        Value::MimeMediaType("application/vnd.cups-raw".into()),
+                    ],
+                ),
+            );
+        }
+        Operation::PrintJob | Operation::CreateJob => {
+            res.add(GroupTag::Job, Attribute::new("job-id", Value::Integer(7)));
+        }
+        Operation::GetJobAttributes => {
+            res.add(GroupTag::Job, Attribute::new("job-state", Value::Enum(5)));
+        }
+        _ => {}
+    }
+    res
+}
+
+#[test]
+fn test_cupsd() {
+    let (uri, server) = ipp_server(6, false, answer);
+    let addr = uri
+        .strip_prefix("ipp://")
+        .and_then(|v| v.split_once('/'))
+        .map(|v| v.0)
+        .expect("addr");
+    std::env::set_var("CUPS_SERVER", addr);
+    std::env::remove_var("LPDEST");
+    std::env::remove_var("PRINTER");
+
+    assert_eq!(list_printers().expect("list"), vec!["label", "office"]);
+    assert_eq!(default_printer().expect("default"), "office");
+
+    let info = printer_attr("label").expect("attr");
+    assert_eq!(info.printer_name, "label");
+    assert!(info.printer_is_accepting_jobs);
+    assert_eq!(info.copies, 2);
+    assert_eq!(info.status(), Status::Busy);
+
+    let mut pj = PrintJob::new("label", "doc").expect("job");
+    pj.write_all(b"hello").expect("write");
+    pj.close().expect("close");
+    assert_eq!(pj.job_id(), 7);
+    assert!(pj.last_error().is_none());
+    assert!(pj.write_all(b"more").is_err());
+    assert!(matches!(pj.last_error(), Some(PrintError::Closed)));
+
+    let status = job_status("label", pj.job_id()).expect("status");
+    assert_eq!(status.state, JobState::Processing);
+
+    let requests = server.join().expect("server");
+    let (req, data) = &requests[4];
+    assert_eq!(req.operation(), Operation::PrintJob);
+    assert_eq!(
+        req.attribute(GroupTag::Operation, "printer-uri")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str()),
+        Some(format!("ipp://{}/printers/label", addr).as_str())
+    );
+    assert_eq!(
+        req.attribute(GroupTag::Operation, "document-format")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str()),
+        Some("application/vnd.cups-raw")
+    );
+    assert_eq!(data.as_slice(), b"hello");
+}
+
+#[test]
+fn test_cupsd_documents() {
+    let (uri, server) = ipp_server(5, false, answer);
+
+    let mut pj = PrintJob::new_with(&uri, "packing list", &JobParam::default()).expect("job");
+    let err = pj
+        .next_document("labels", Format::Text)
+        .expect_err("single document");
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    pj.abort().expect("abort");
+
+    let param = JobParam {
+        multi_document: true,
+        ..Default::default()
+    };
+    let mut pj = PrintJob::new_with(&uri, "packing list", &param).expect("job");
+    pj.write_all(b"%PDF").expect("write");
+    pj.next_document("labels", Format::Text).expect("next");
+    pj.write_all(b"label").expect("write");
+    pj.close().expect("close");
+    assert_eq!(pj.job_id(), 7);
+
+    let requests = server.join().expect("server");
+    assert_eq!(requests[2].0.operation(), Operation::CreateJob);
+    assert_eq!(requests[3].1.as_slice(), b"%PDF");
+    assert_eq!(requests[4].1.as_slice(), b"label");
+}
+
+#[test]
+fn test_print_reader() {
+    let (uri, server) = ipp_server(2, false, answer);
+
+    let mut data = b"%PDF-1.7\n".to_vec();
+    data.resize(100_000, b' ');
+    let param = JobParam {
+        data_format: Format::Auto,
+        ..Default::default()
+    };
+    let mut progress = Vec::new();
+    let job_id = print_reader_progress(&uri, "packing list", data.as_slice(), &param, |v, _| {
+        progress.push(v)
+    })
+    .expect("print");
+    assert_eq!(job_id, 7);
+    assert_eq!(progress, vec![65536, 100_000]);
+
+    let requests = server.join().expect("server");
+    let (req, sent) = &requests[1];
+    assert_eq!(
+        req.attribute(GroupTag::Operation, "document-format")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str()),
+        Some("application/pdf")
+    );
+    assert_eq!(sent, &data);
+}
+
+#[test]
+fn test_cupsd_connection() {
+    let (uri, server) = ipp_server(5, false, answer);
+    let port = uri
+        .strip_prefix("ipp://127.0.0.1:")
+        .and_then(|v| v.split_once('/'))
+        .and_then(|v| v.0.parse::<u16>().ok())
+        .expect("port");
+    let conn = CupsConnection::new("127.0.0.1", port, Encryption::Never);
+
+    assert_eq!(
+        list_printers_on(&conn).expect("list"),
+        vec!["label", "office"]
+    );
+    assert_eq!(printer_attr_on(&conn, "label").expect("attr").copies, 2);
+    printer_attr_on(&conn, "my label").expect("attr");
+
+    let mut pj = PrintJob::new_on(&conn, "label", "doc", &JobParam::default()).expect("job");
+    pj.write_all(b"hello").expect("write");
+    pj.close().expect("close");
+
+    let requests = server.join().expect("server");
+    assert_eq!(
+        requests[2]
+            .0
+            .attribute(GroupTag::Operation, "printer-uri")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str()),
+        Some(format!("ipp://127.0.0.1:{}/printers/my%20label", port).as_str())
+    );
+    assert_eq!(requests[4].0.operation(), Operation::PrintJob);
+    assert_eq!(
+        requests[4]
+            .0
+            .attribute(GroupTag::Operation, "printer-uri")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str()),
+        Some(format!("ipp://127.0.0.1:{}/printers/label", port).as_str())
+    );
+
+    // there is no TLS without libcups.
+    let conn = CupsConnection::new("127.0.0.1", port, Encryption::Required);
+    assert!(list_printers_on(&conn).is_err());
+}
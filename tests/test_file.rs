@@ -0,0 +1,40 @@
+use uniprint::DocumentType;
+
+#[test]
+fn test_sniff() {
+    let sniff = DocumentType::sniff;
+    assert_eq!(sniff(b"%PDF-1.7\n"), Some(DocumentType::Pdf));
+    assert_eq!(sniff(b"%!PS-Adobe-3.0\n"), Some(DocumentType::Postscript));
+    assert_eq!(
+        sniff(b"\xff\xd8\xff\xe0\0\x10JFIF"),
+        Some(DocumentType::Jpeg)
+    );
+    assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0"), Some(DocumentType::Png));
+    assert_eq!(sniff(b"\x1bE\x1b&l0O"), Some(DocumentType::Pcl));
+    assert_eq!(
+        sniff(b"\x1b%-12345X@PJL ENTER LANGUAGE=PCL\r\n"),
+        Some(DocumentType::Pcl)
+    );
+    assert_eq!(sniff(b"\x02L\rH10\rE\r"), Some(DocumentType::Dpl));
+    assert_eq!(
+        sniff(b"\r\n^XA^FO50,50^FDHi^FS^XZ"),
+        Some(DocumentType::Zpl)
+    );
+    assert_eq!(sniff(b"~JA"), Some(DocumentType::Zpl));
+    assert_eq!(sniff("Grüße\r\n\x0c".as_bytes()), Some(DocumentType::Text));
+    assert_eq!(sniff(b"\0\x01\x02\x03"), None);
+    assert_eq!(sniff(b""), None);
+
+    assert_eq!(DocumentType::Pdf.mime(), "application/pdf");
+    assert_eq!(DocumentType::Zpl.mime(), "application/octet-stream");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_format() {
+    use uniprint::Format;
+
+    assert_eq!(DocumentType::Png.format(), Format::Png);
+    assert_eq!(DocumentType::Dpl.format(), Format::Raw);
+    assert_eq!(DocumentType::Text.format(), Format::Text);
+}
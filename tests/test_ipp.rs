@@ -0,0 +1,786 @@
+mod common;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use common::{answer_ok, ipp_server};
+
+use uniprint::backend::ipp::IppJob;
+use uniprint::driver::datamax::Datamax;
+use uniprint::ipp::{
+    Attribute, GroupTag, IppClient, Message, Operation, StatusCode, Value, RESOLUTION_DPI,
+};
+use uniprint::{
+    Capabilities, Driver, Duplex, Format, JobParam, JobState, Length, Lifecycle, Media, PaperSize,
+    PrintError, PrinterState, Quality, Reason, Severity, Status, WhichJobs,
+};
+
+fn op_str<'a>(req: &'a Message, name: &str) -> &'a str {
+    req.attribute(GroupTag::Operation, name)
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_str())
+        .expect(name)
+}
+
+#[test]
+fn test_codec() {
+    let mut msg = Message::request(Operation::PrintJob, 7);
+    msg.add(
+        GroupTag::Job,
+        Attribute::with_values("copies", vec![Value::Integer(2), Value::Integer(-1)]),
+    );
+    msg.add(
+        GroupTag::Job,
+        Attribute::new(
+            "media-col",
+            Value::Collection(vec![
+                Attribute::new(
+                    "media-size",
+                    Value::Collection(vec![
+                        Attribute::new("x-dimension", Value::Integer(10160)),
+                        Attribute::new("y-dimension", Value::Integer(15240)),
+                    ]),
+                ),
+                Attribute::new("media-type", Value::Keyword("labels".into())),
+            ]),
+        ),
+    );
+    msg.add(
+        GroupTag::Printer,
+        Attribute::new(
+            "printer-resolution",
+            Value::Resolution {
+                x: 203,
+                y: 203,
+                units: RESOLUTION_DPI,
+            },
+        ),
+    );
+    msg.add(
+        GroupTag::Printer,
+        Attribute::new("copies-supported", Value::Range(1, 999)),
+    );
+    msg.add(
+        GroupTag::Printer,
+        Attribute::new(
+            "printer-info",
+            Value::TextWithLanguage {
+                language: "de".into(),
+                text: "Etiketten".into(),
+            },
+        ),
+    );
+    msg.add(GroupTag::Printer, Attribute::new("x", Value::NoValue));
+
+    let mut buf = msg.encode().expect("encode");
+    assert_eq!(&buf[..8], &[2, 0, 0, 2, 0, 0, 0, 7]);
+    let len = buf.len();
+    buf.extend_from_slice(b"data");
+
+    let (decoded, n) = Message::decode(&buf).expect("decode");
+    assert_eq!(n, len);
+    assert_eq!(decoded, msg);
+    assert_eq!(decoded.groups(GroupTag::Job).count(), 1);
+
+    assert!(Message::decode(&buf[..len - 3]).is_err());
+}
+
+#[test]
+fn test_codec_depth() {
+    let nested = |depth: usize| {
+        let mut value = Value::Integer(1);
+        for _ in 0..depth {
+            value = Value::Collection(vec![Attribute::new("v", value)]);
+        }
+        let mut msg = Message::response(StatusCode::OK, 1);
+        msg.add(GroupTag::Printer, Attribute::new("media-col", value));
+        msg.encode().expect("encode")
+    };
+    assert!(Message::decode(&nested(32)).is_ok());
+    let err = Message::decode(&nested(33)).expect_err("too deep");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // begCollection without end, as deep as the data goes.
+    let mut buf = vec![2, 0, 0, 0, 0, 0, 0, 1, 0x04, 0x34, 0, 1, b'x', 0, 0];
+    for _ in 0..100_000 {
+        buf.extend_from_slice(&[0x34, 0, 0, 0, 0]);
+    }
+    let err = Message::decode(&buf).expect_err("too deep");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_ipp_job() {
+    let (uri, server) = ipp_server(2, false, answer_ok);
+
+    let param = JobParam {
+        copies: Some(3),
+        ..Default::default()
+    };
+    let job = IppJob::new_with(&uri, "label", &param).expect("job");
+    let mut pj = Datamax::with_backend(job);
+    pj.start_label().expect("label");
+    pj.end_label().expect("label");
+    pj.close().expect("close");
+
+    let requests = server.join().expect("server");
+    assert_eq!(requests[0].0.operation(), Operation::GetPrinterAttributes);
+
+    let (req, data) = &requests[1];
+    assert_eq!(req.operation(), Operation::PrintJob);
+    assert_eq!(op_str(req, "printer-uri"), uri);
+    assert_eq!(op_str(req, "job-name"), "label");
+    assert_eq!(
+        req.attribute(GroupTag::Job, "copies")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_i32()),
+        Some(3)
+    );
+    assert_eq!(data.as_slice(), b"\x02L\rE\r");
+}
+
+#[test]
+fn test_ipp_options() {
+    let (uri, server) = ipp_server(2, false, answer_ok);
+
+    let param = JobParam {
+        copies: Some(3),
+        options: vec![
+            ("page-ranges".into(), "1-4,8".into()),
+            ("copies".into(), "2".into()),
+            (
+                "media-col".into(),
+                "{media-size={x-dimension=21000 y-dimension=29700} media-source=tray-1}".into(),
+            ),
+            ("Darkness".into(), "20".into()),
+        ],
+        ..Default::default()
+    };
+    assert!(param.check_options().is_empty());
+    let mut job = IppJob::new_with(&uri, "label", &param).expect("job");
+    job.write_all(b"data").expect("write");
+    job.close().expect("close");
+
+    let requests = server.join().expect("server");
+    let req = &requests[1].0;
+    let job_attr = |name: &str| req.attribute(GroupTag::Job, name).expect(name);
+    assert_eq!(
+        job_attr("page-ranges").values,
+        vec![Value::Range(1, 4), Value::Range(8, 8)]
+    );
+    assert_eq!(job_attr("copies").values, vec![Value::Integer(2)]);
+    assert_eq!(job_attr("Darkness").values, vec![Value::Name("20".into())]);
+    let col = job_attr("media-col")
+        .value()
+        .and_then(|v| v.as_collection())
+        .expect("media-col");
+    assert_eq!(col[1].name, "media-source");
+    assert_eq!(col[1].values, vec![Value::Keyword("tray-1".into())]);
+    let size = col[0]
+        .value()
+        .and_then(|v| v.as_collection())
+        .expect("size");
+    assert_eq!(size[1].values, vec![Value::Integer(29700)]);
+
+    let (uri, server) = ipp_server(1, false, answer_ok);
+    let param = JobParam {
+        options: vec![
+            ("copies".into(), "two".into()),
+            ("print-scaling".into(), "fit".into()),
+        ],
+        ..Default::default()
+    };
+    let conflicts = param.check_options();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].to_string(), "copies=two");
+    let err = IppJob::new_with(&uri, "label", &param).expect_err("invalid");
+    assert_eq!(err.to_string(), "Unsupported job parameters: copies=two");
+    server.join().expect("server");
+}
+
+#[test]
+fn test_ipp_custom_size() {
+    let (uri, server) = ipp_server(2, false, answer_ok);
+
+    let param = JobParam {
+        paper_size: Some(PaperSize::Custom {
+            width: Length::mm(100.0),
+            height: Length::mm(50.0),
+        }),
+        ..Default::default()
+    };
+    let mut job = IppJob::new_with(&uri, "label", &param).expect("job");
+    job.write_all(b"data").expect("write");
+    job.close().expect("close");
+
+    let requests = server.join().expect("server");
+    let req = &requests[1].0;
+    // no application/vnd.cups-raw for a printer that doesn't list it.
+    assert_eq!(op_str(req, "document-format"), "application/octet-stream");
+    assert!(req.attribute(GroupTag::Job, "media").is_none());
+    let col = req
+        .attribute(GroupTag::Job, "media-col")
+        .and_then(|v| v.value())
+        .and_then(|v| v.as_collection())
+        .expect("media-col");
+    assert_eq!(col[0].name, "media-size");
+    let size = col[0]
+        .value()
+        .and_then(|v| v.as_collection())
+        .expect("size");
+    assert_eq!(size[0].values, vec![Value::Integer(10000)]);
+    assert_eq!(size[1].values, vec![Value::Integer(5000)]);
+}
+
+#[test]
+fn test_ipp_drop() {
+    let (uri, server) = ipp_server(1, false, answer_ok);
+
+    let job = IppJob::new(&uri, "label").expect("job");
+    assert_eq!(job.lifecycle(), Lifecycle::Created);
+    let mut pj = Datamax::with_backend(job);
+    pj.start_label().expect("label");
+    assert_eq!(pj.backend().lifecycle(), Lifecycle::Writing);
+    drop(pj);
+
+    // only the check for the printer, no Print-Job
+    let requests = server.join().expect("server");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].0.operation(), Operation::GetPrinterAttributes);
+
+    let (uri, server) = ipp_server(1, false, answer_ok);
+    let mut job = IppJob::new(&uri, "label").expect("job");
+    job.abort().expect("abort");
+    assert_eq!(job.lifecycle(), Lifecycle::Aborted);
+    assert!(job.close().is_err());
+    server.join().expect("server");
+}
+
+#[test]
+fn test_ipp_documents() {
+    let (uri, server) = ipp_server(4, false, answer_ok);
+
+    let mut job = IppJob::new(&uri, "packing list").expect("job");
+    job.write_all(b"%PDF").expect("write");
+    job.next_document("labels", Format::Text).expect("next");
+    job.write_all(b"label").expect("write");
+    job.close().expect("close");
+    assert_eq!(job.job_id(), 42);
+
+    let requests = server.join().expect("server");
+    assert_eq!(requests[1].0.operation(), Operation::CreateJob);
+    assert_eq!(op_str(&requests[1].0, "job-name"), "packing list");
+
+    let (req, data) = &requests[2];
+    assert_eq!(req.operation(), Operation::SendDocument);
+    assert_eq!(op_str(req, "document-name"), "packing list");
+    assert_eq!(
+        req.attribute(GroupTag::Operation, "last-document")
+            .and_then(|v| v.value()),
+        Some(&Value::Boolean(false))
+    );
+    assert_eq!(data.as_slice(), b"%PDF");
+
+    let (req, data) = &requests[3];
+    assert_eq!(op_str(req, "document-name"), "labels");
+    assert_eq!(op_str(req, "document-format"), "text/plain");
+    assert_eq!(
+        req.attribute(GroupTag::Operation, "last-document")
+            .and_then(|v| v.value()),
+        Some(&Value::Boolean(true))
+    );
+    assert_eq!(data.as_slice(), b"label");
+}
+
+#[test]
+fn test_ipp_document_error() {
+    let (uri, server) = ipp_server(5, false, |req| {
+        let failed = req
+            .attribute(GroupTag::Operation, "document-name")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str())
+            == Some("labels");
+        if failed {
+            Message::response(StatusCode::CLIENT_ERROR_NOT_POSSIBLE, req.request_id)
+        } else {
+            answer_ok(req)
+        }
+    });
+
+    let mut job = IppJob::new(&uri, "packing list").expect("job");
+    job.write_all(b"%PDF").expect("write");
+    job.next_document("labels", Format::Text).expect("next");
+    job.write_all(b"label").expect("write");
+    let err = job.close().expect_err("close");
+    match err.get_ref().and_then(|v| v.downcast_ref::<PrintError>()) {
+        Some(PrintError::Document { index, name, .. }) => {
+            assert_eq!(*index, 1);
+            assert_eq!(name, "labels");
+        }
+        e => panic!("{:?}", e),
+    }
+
+    // the job exists at the printer now and is canceled.
+    job.abort().expect("abort");
+    let requests = server.join().expect("server");
+    assert_eq!(requests[4].0.operation(), Operation::CancelJob);
+}
+
+#[test]
+fn test_ipp_wait() {
+    fn answer(state: i32) -> impl Fn(&Message) -> Message {
+        move |req| {
+            let mut res = answer_ok(req);
+            if req.operation() == Operation::GetJobAttributes {
+                res.add(
+                    GroupTag::Job,
+                    Attribute::new("job-state", Value::Enum(state)),
+                );
+                res.add(
+                    GroupTag::Job,
+                    Attribute::new(
+                        "job-state-reasons",
+                        Value::Keyword("aborted-by-system".into()),
+                    ),
+                );
+            }
+            res
+        }
+    }
+
+    let (uri, server) = ipp_server(3, false, answer(9));
+    let mut job = IppJob::new(&uri, "label").expect("job");
+    job.write_all(b"label").expect("write");
+    job.close_and_wait(Duration::from_secs(5)).expect("wait");
+    assert_eq!(job.job_id(), 42);
+    let requests = server.join().expect("server");
+    assert_eq!(requests[2].0.operation(), Operation::GetJobAttributes);
+
+    let (uri, server) = ipp_server(3, false, answer(8));
+    let mut job = IppJob::new(&uri, "label").expect("job");
+    let err = job
+        .close_and_wait(Duration::from_secs(5))
+        .expect_err("aborted");
+    match err.get_ref().and_then(|v| v.downcast_ref::<PrintError>()) {
+        Some(PrintError::JobFailed { state, reasons }) => {
+            assert_eq!(*state, JobState::Aborted);
+            assert_eq!(reasons, &vec!["aborted-by-system".to_string()]);
+        }
+        _ => panic!("{:?}", err),
+    }
+    server.join().expect("server");
+}
+
+#[test]
+fn test_ipp_client() {
+    let (uri, server) = ipp_server(5, true, answer_ok);
+
+    let client = IppClient::new(&uri).expect("client");
+    let attr = client
+        .get_printer_attributes(&["sides-supported"])
+        .expect("attr");
+    assert_eq!(attr[1].values.len(), 2);
+
+    let job_id = client.create_job("doc", &[]).expect("create");
+    assert_eq!(job_id, 42);
+    client
+        .send_document(job_id, "doc", "text/plain", true, b"hello")
+        .expect("send");
+    assert!(client.get_jobs(WhichJobs::All).expect("jobs").is_empty());
+    client.hold_job(job_id).expect("hold");
+
+    let requests = server.join().expect("server");
+    assert_eq!(op_str(&requests[3].0, "which-jobs"), "all");
+    assert_eq!(requests[4].0.operation(), Operation::HoldJob);
+    let (req, data) = &requests[2];
+    assert_eq!(req.operation(), Operation::SendDocument);
+    assert_eq!(op_str(req, "document-format"), "text/plain");
+    assert_eq!(
+        req.attribute(GroupTag::Operation, "last-document")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_bool()),
+        Some(true)
+    );
+    assert_eq!(data.as_slice(), b"hello");
+}
+
+#[test]
+fn test_ipp_error() {
+    let (uri, server) = ipp_server(2, false, |req| {
+        let mut res = Message::response(StatusCode::CLIENT_ERROR_NOT_FOUND, req.request_id);
+        res.add(
+            GroupTag::Operation,
+            Attribute::new("status-message", Value::Text("no such printer".into())),
+        );
+        res
+    });
+
+    let err = IppJob::new(&uri, "label").expect_err("not found");
+    assert!(matches!(
+        err.get_ref().and_then(|v| v.downcast_ref::<PrintError>()),
+        Some(PrintError::NotFound)
+    ));
+
+    let client = IppClient::new(&uri).expect("client");
+    let res = client
+        .send_raw(&client.request(Operation::CancelJob), &[])
+        .expect("raw");
+    assert_eq!(res.status(), StatusCode::CLIENT_ERROR_NOT_FOUND);
+    assert_eq!(res.status_message(), "no such printer");
+
+    server.join().expect("server");
+
+    assert!(IppClient::new("ipps://localhost/printers/x").is_err());
+}
+
+#[test]
+fn test_ipp_bad_length() {
+    // the announced length must not be allocated up front.
+    for head in [
+        "Content-Length: 99999999999999\r\n\r\nabc",
+        "Transfer-Encoding: chunked\r\n\r\nffffffffffff\r\nabc",
+    ] {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let uri = format!("ipp://{}/printers/x", listener.local_addr().expect("addr"));
+        let server = thread::spawn(move || {
+            let (mut conn, _) = listener.accept().expect("accept");
+            let mut buf = [0u8; 1024];
+            let _ = conn.read(&mut buf);
+            write!(conn, "HTTP/1.1 200 OK\r\n{}", head).expect("write");
+        });
+
+        let client = IppClient::new(&uri).expect("client");
+        assert!(client
+            .send_raw(&client.request(Operation::CancelJob), &[])
+            .is_err());
+        server.join().expect("server");
+    }
+}
+
+#[test]
+fn test_ipp_printer_status() {
+    let (uri, server) = ipp_server(1, false, |req| {
+        let mut res = Message::response(StatusCode::OK, req.request_id);
+        res.add(
+            GroupTag::Printer,
+            Attribute::new("printer-state", Value::Enum(5)),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::with_values(
+                "printer-state-reasons",
+                vec![
+                    Value::Keyword("media-empty-error".into()),
+                    Value::Keyword("toner-low-warning".into()),
+                ],
+            ),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::new("printer-state-message", Value::Text("Load paper".into())),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::new("printer-state-change-time", Value::Integer(1000)),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::new("printer-is-accepting-jobs", Value::Boolean(false)),
+        );
+        res
+    });
+
+    let client = IppClient::new(&uri).expect("client");
+    let status = client.printer_status().expect("status");
+    assert_eq!(status.state, PrinterState::Stopped);
+    assert_eq!(status.reasons.len(), 2);
+    assert_eq!(status.reasons[0].reason, Reason::MediaEmpty);
+    assert_eq!(status.reasons[1].severity, Severity::Warning);
+    assert_eq!(status.message, "Load paper");
+    assert_eq!(
+        status.changed,
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1000))
+    );
+    assert!(!status.accepting_jobs);
+    assert_eq!(status.status(), Status::Error);
+
+    let requests = server.join().expect("server");
+    assert_eq!(requests[0].0.operation(), Operation::GetPrinterAttributes);
+}
+
+#[test]
+fn test_ipp_capabilities() {
+    let (uri, server) = ipp_server(1, false, |req| {
+        let keywords = |v: &[&str]| v.iter().map(|v| Value::Keyword(v.to_string())).collect();
+        let dim = |name: &str, v: i32| Attribute::new(name, Value::Integer(v));
+
+        let mut res = Message::response(StatusCode::OK, req.request_id);
+        res.add(
+            GroupTag::Printer,
+            Attribute::with_values(
+                "media-supported",
+                keywords(&[
+                    "iso_a4_210x297mm",
+                    "oe_label_2x1in",
+                    "custom_min_1x1in",
+                    "custom_max_4.25x14in",
+                ]),
+            ),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::new(
+                "media-col-database",
+                Value::Collection(vec![
+                    Attribute::new(
+                        "media-size",
+                        Value::Collection(vec![
+                            dim("x-dimension", 21000),
+                            dim("y-dimension", 29700),
+                        ]),
+                    ),
+                    dim("media-bottom-margin", 300),
+                    dim("media-left-margin", 300),
+                    dim("media-right-margin", 300),
+                    dim("media-top-margin", 300),
+                    Attribute::new("media-size-name", Value::Keyword("iso_a4_210x297mm".into())),
+                ]),
+            ),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::with_values(
+                "sides-supported",
+                keywords(&["one-sided", "two-sided-long-edge"]),
+            ),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::with_values("print-quality-supported", vec![Value::Enum(4)]),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::with_values(
+                "printer-resolution-supported",
+                vec![
+                    Value::Resolution {
+                        x: 300,
+                        y: 300,
+                        units: RESOLUTION_DPI,
+                    },
+                    Value::Resolution {
+                        x: 118,
+                        y: 118,
+                        units: 4,
+                    },
+                    Value::Resolution {
+                        x: i32::MAX,
+                        y: 20_000_000,
+                        units: 4,
+                    },
+                ],
+            ),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::with_values(
+                "document-format-supported",
+                vec![Value::MimeMediaType("application/pdf".into())],
+            ),
+        );
+        res.add(
+            GroupTag::Printer,
+            Attribute::new("copies-supported", Value::Range(1, 99)),
+        );
+        res
+    });
+
+    let client = IppClient::new(&uri).expect("client");
+    let caps = client.capabilities().expect("capabilities");
+
+    assert_eq!(caps.media.len(), 2);
+    assert!(matches!(caps.media[0].paper_size, Some(PaperSize::A4)));
+    assert_eq!(
+        (caps.media[0].width, caps.media[0].length),
+        (Length::mm(210.0), Length::mm(297.0))
+    );
+    assert_eq!(caps.media[0].margins.map(|v| v.top), Some(Length::mm(3.0)));
+    assert_eq!(caps.media[1].name, "oe_label_2x1in");
+    assert_eq!(
+        (caps.media[1].width, caps.media[1].length),
+        (Length::inch(2.0), Length::inch(1.0))
+    );
+    assert!(caps.media[1].margins.is_none());
+    assert_eq!(
+        caps.custom_size,
+        Some((
+            (Length::inch(1.0), Length::inch(1.0)),
+            (Length::inch(4.25), Length::inch(14.0))
+        ))
+    );
+
+    assert!(matches!(
+        caps.sides.as_slice(),
+        [Duplex::Simplex, Duplex::TwoSidedPortrait]
+    ));
+    assert!(matches!(caps.qualities.as_slice(), [Quality::Normal]));
+    assert_eq!(
+        caps.resolutions,
+        vec![(300, 300), (300, 300), (u32::MAX, 50_800_000)]
+    );
+    assert_eq!(caps.document_formats, vec!["application/pdf".to_string()]);
+    assert_eq!(caps.copies, Some((1, 99)));
+
+    let requests = server.join().expect("server");
+    assert_eq!(requests[0].0.operation(), Operation::GetPrinterAttributes);
+}
+
+#[test]
+fn test_validate() {
+    let mut caps = Capabilities::default();
+    caps.sides = vec![Duplex::Simplex];
+    caps.copies = Some((1, 10));
+    let mut a4 = Media::default();
+    a4.paper_size = Some(PaperSize::A4);
+    a4.width = Length::mm(210.0);
+    a4.length = Length::mm(297.0);
+    let mut letter = Media::default();
+    letter.paper_size = Some(PaperSize::Letter);
+    letter.width = Length::inch(8.5);
+    letter.length = Length::inch(11.0);
+    caps.media = vec![letter, a4];
+
+    let mut param = JobParam {
+        copies: Some(20),
+        paper_size: Some(PaperSize::A3),
+        duplex: Some(Duplex::TwoSidedPortrait),
+        quality: Some(Quality::High),
+        ..Default::default()
+    };
+    let conflicts = param.validate(&caps);
+    let names = conflicts
+        .iter()
+        .map(|v| v.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["copies", "media", "sides"]);
+
+    let corrections = param.correct(&caps);
+    assert_eq!(corrections.len(), 3);
+    assert_eq!(corrections[0].name, "copies");
+    assert_eq!(corrections[0].value, "20");
+    assert_eq!(corrections[0].replacement.as_deref(), Some("10"));
+    assert_eq!(corrections[1].value, "A3");
+    assert_eq!(corrections[1].replacement.as_deref(), Some("A4"));
+    assert_eq!(param.copies, Some(10));
+    assert_eq!(param.paper_size, Some(PaperSize::A4));
+    assert_eq!(param.duplex, Some(Duplex::Simplex));
+    assert_eq!(param.quality, Some(Quality::High));
+    assert!(param.validate(&caps).is_empty());
+
+    // custom sizes need the range, or a media of the same size.
+    let custom = PaperSize::Custom {
+        width: Length::mm(100.0),
+        height: Length::mm(50.0),
+    };
+    param.paper_size = Some(custom.clone());
+    assert_eq!(param.validate(&caps).len(), 1);
+    caps.custom_size = Some((
+        (Length::inch(1.0), Length::inch(1.0)),
+        (Length::inch(8.5), Length::inch(14.0)),
+    ));
+    assert!(param.validate(&caps).is_empty());
+    param.paper_size = Some(PaperSize::Custom {
+        width: Length::mm(210.0),
+        height: Length::mm(297.0),
+    });
+    caps.custom_size = None;
+    assert!(param.validate(&caps).is_empty());
+
+    // a custom size without any named media to replace it is cleared.
+    caps.media.iter_mut().for_each(|v| v.paper_size = None);
+    param.paper_size = Some(custom);
+    let corrections = param.correct(&caps);
+    assert_eq!(corrections.len(), 1);
+    assert_eq!(corrections[0].name, "media");
+    assert_eq!(corrections[0].replacement, None);
+    assert_eq!(param.paper_size, None);
+}
+
+#[test]
+fn test_ipp_validate() {
+    let (uri, server) = ipp_server(1, false, |req| {
+        let mut res = Message::response(
+            StatusCode::CLIENT_ERROR_ATTRIBUTES_OR_VALUES_NOT_SUPPORTED,
+            req.request_id,
+        );
+        res.add(
+            GroupTag::Unsupported,
+            Attribute::new("sides", Value::Keyword("two-sided-short-edge".into())),
+        );
+        res
+    });
+
+    let client = IppClient::new(&uri).expect("client");
+    let sides = Attribute::new("sides", Value::Keyword("two-sided-short-edge".into()));
+    let conflicts = client
+        .validate_job("label", "application/pdf", &[sides])
+        .expect("validate");
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].name, "sides");
+    assert_eq!(conflicts[0].value, "two-sided-short-edge");
+
+    let requests = server.join().expect("server");
+    assert_eq!(requests[0].0.operation(), Operation::ValidateJob);
+}
+
+#[test]
+fn test_ipp_auto_correct() {
+    let (uri, server) = ipp_server(3, false, answer_ok);
+
+    let param = JobParam {
+        duplex: Some(Duplex::TwoSidedLandscape),
+        auto_correct: true,
+        ..Default::default()
+    };
+    let mut job = IppJob::new_with(&uri, "label", &param).expect("job");
+    job.write_all(b"data").expect("write");
+    job.close().expect("close");
+
+    let requests = server.join().expect("server");
+    let req = &requests[2].0;
+    assert_eq!(req.operation(), Operation::PrintJob);
+    assert_eq!(
+        req.attribute(GroupTag::Job, "sides")
+            .and_then(|v| v.value())
+            .and_then(|v| v.as_str()),
+        Some("one-sided")
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_ipp_auto_correct_cleared() {
+    let (uri, server) = ipp_server(2, false, |req| {
+        let mut res = answer_ok(req);
+        res.add(
+            GroupTag::Printer,
+            Attribute::with_values("finishings-supported", vec![Value::Enum(3)]),
+        );
+        res
+    });
+
+    // nothing to replace the staple with, the job is refused.
+    let param = JobParam {
+        finishings: Some(uniprint::Finishings::Staple),
+        auto_correct: true,
+        ..Default::default()
+    };
+    let err = IppJob::new_with(&uri, "label", &param).expect_err("cleared");
+    match err.get_ref().and_then(|v| v.downcast_ref::<PrintError>()) {
+        Some(PrintError::InvalidParam(v)) => assert_eq!(v[0].name, "finishings"),
+        v => panic!("{:?}", v),
+    }
+    server.join().expect("server");
+}
@@ -0,0 +1,38 @@
+use uniprint::Length;
+
+#[test]
+fn test_length() {
+    assert_eq!(Length::inch(1.0), Length::mm(25.4));
+    assert_eq!(Length::inch(1.0), Length::points(72.0));
+    assert_eq!(Length::inch(1.0), Length::dots(300, 300));
+    assert_eq!(Length::mm(0.01) * 2540, Length::inch(1.0));
+
+    assert_eq!(Length::mm(210.0).as_mm(), 210.0);
+    assert_eq!(Length::inch(8.5).as_inch(), 8.5);
+    assert_eq!(Length::inch(0.5).as_points(), 36.0);
+    assert_eq!(Length::mm(25.4).to_dots(600), Some(600));
+    // 203dpi is rounded to the nearest dot.
+    assert_eq!(Length::mm(10.0).to_dots(203), Some(80));
+    assert_eq!(Length::inch(8.5).count(Length::mm(0.01)), Some(21590));
+    assert_eq!(Length::mm(-1.25).count(Length::mm(0.1)), Some(-13));
+
+    assert_eq!(Length::mm(12.5).to_string(), "12.5mm");
+    assert_eq!(Length::inch(1.0).to_string(), "25.4mm");
+    assert_eq!((-Length::mm(3.0)).to_string(), "-3mm");
+
+    let sum: Length = [Length::mm(1.0), Length::inch(1.0)].into_iter().sum();
+    assert_eq!(sum - Length::mm(1.0), Length::inch(1.0));
+    assert_eq!(Length::mm(10.0) / 4, Length::mm(2.5));
+}
+
+#[test]
+fn test_length_range() {
+    assert_eq!(Length::mm(1.0).to_dots(0), None);
+    assert_eq!(Length::mm(1.0).count(Length::ZERO), None);
+    assert_eq!(Length::inch(1e11).to_dots(600), Some(60_000_000_000_000));
+    assert_eq!(Length::inch(1e13).to_dots(u32::MAX), None);
+    assert_eq!(
+        Length::inch(1e13).count(Length::inch(1.0)),
+        Some(10_000_000_000_000)
+    );
+}
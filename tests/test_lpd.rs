@@ -0,0 +1,132 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use uniprint::backend::lpd::LpdJob;
+use uniprint::driver::datamax::Datamax;
+use uniprint::{Driver, JobParam, Lifecycle};
+
+/// Received files: (name, content)
+type Files = Vec<(String, Vec<u8>)>;
+
+/// Accepts one job. Answers with ack for the first n commands.
+fn lpd_server(n_ok: usize) -> (String, thread::JoinHandle<(String, Files)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr").to_string();
+
+    let server = thread::spawn(move || {
+        let (conn, _) = listener.accept().expect("accept");
+        let mut wr = conn.try_clone().expect("clone");
+        let mut rd = BufReader::new(conn);
+        let mut n_cmd = 0;
+
+        let mut line = Vec::new();
+        rd.read_until(b'\n', &mut line).expect("read");
+        let queue = String::from_utf8_lossy(&line[1..line.len() - 1]).to_string();
+        n_cmd += 1;
+        if n_cmd > n_ok {
+            wr.write_all(&[1]).expect("nack");
+            return (queue, Vec::new());
+        }
+        wr.write_all(&[0]).expect("ack");
+
+        let mut files = Vec::new();
+        loop {
+            let mut line = Vec::new();
+            if rd.read_until(b'\n', &mut line).expect("read") == 0 {
+                break;
+            }
+            let cmd = String::from_utf8_lossy(&line[1..line.len() - 1]).to_string();
+            let (len, name) = cmd.split_once(' ').expect("subcommand");
+            let len: usize = len.parse().expect("len");
+
+            n_cmd += 1;
+            if n_cmd > n_ok {
+                wr.write_all(&[1]).expect("nack");
+                break;
+            }
+            wr.write_all(&[0]).expect("ack");
+
+            let mut buf = vec![0u8; len + 1];
+            rd.read_exact(&mut buf).expect("file");
+            assert_eq!(buf.pop(), Some(0));
+            wr.write_all(&[0]).expect("ack");
+
+            files.push((name.to_string(), buf));
+        }
+        (queue, files)
+    });
+
+    (addr, server)
+}
+
+#[test]
+fn test_lpd() -> std::io::Result<()> {
+    let (addr, server) = lpd_server(usize::MAX);
+
+    let param = JobParam {
+        copies: Some(2),
+        ..Default::default()
+    };
+    let mut dm = Datamax::with_backend(LpdJob::new_with(&addr, "labels", "Label 1", &param)?);
+    dm.start_label()?;
+    dm.end_label()?;
+    dm.close()?;
+
+    let (queue, files) = server.join().expect("join");
+    assert_eq!(queue, "labels");
+    assert_eq!(files.len(), 2);
+
+    let (df_name, data) = &files[0];
+    assert!(df_name.starts_with("dfA"));
+    assert_eq!(data, b"\x02L\rE\r");
+
+    let (cf_name, control) = &files[1];
+    assert!(cf_name.starts_with("cfA"));
+    assert_eq!(cf_name[3..], df_name[3..]);
+    let control = String::from_utf8_lossy(control);
+    let lines = control.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"JLabel 1"));
+    assert!(lines.contains(&"NLabel 1"));
+    assert!(lines.iter().any(|v| v.starts_with('H')));
+    assert!(lines.iter().any(|v| v.starts_with('P')));
+    let print_lines = lines
+        .iter()
+        .filter(|v| **v == format!("l{}", df_name))
+        .count();
+    assert_eq!(print_lines, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_lpd_refused() -> std::io::Result<()> {
+    let (addr, server) = lpd_server(0);
+
+    let mut job = LpdJob::new(&addr, "missing", "Doc")?;
+    job.write_all(b"test0")?;
+    assert!(job.close().is_err());
+    assert_eq!(job.lifecycle(), Lifecycle::Writing);
+
+    let (queue, files) = server.join().expect("join");
+    assert_eq!(queue, "missing");
+    assert!(files.is_empty());
+
+    // the retry doesn't pretend success, the server is gone.
+    assert!(job.close().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_lpd_abort() -> std::io::Result<()> {
+    let mut job = LpdJob::new("127.0.0.1:1", "queue", "Doc")?;
+    job.write_all(b"test0")?;
+    job.abort()?;
+    // nothing is sent, so the bogus address is never used.
+    assert!(job.close().is_err());
+    assert_eq!(job.lifecycle(), Lifecycle::Aborted);
+    assert!(job.write_all(b"more").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use uniprint::media::{self, MEDIA_SIZES};
+use uniprint::{Length, PaperSize};
+
+#[test]
+fn test_media() {
+    let a4 = media::by_name("iso_a4_210x297mm").expect("a4");
+    assert_eq!(
+        (a4.width, a4.length),
+        (Length::mm(210.0), Length::mm(297.0))
+    );
+    assert_eq!(a4.short_name(), "iso_a4");
+    assert_eq!(media::by_name("iso_a4"), Some(a4));
+    assert_eq!(media::by_alias("a4"), Some(a4));
+    assert_eq!(media::by_dmpaper(9), Some(a4));
+    assert!(media::by_name("iso_a4_mm").is_none());
+
+    let env10 = media::by_alias("Env10").expect("env10");
+    assert_eq!(env10.name, "na_number-10_4.125x9.5in");
+    assert_eq!(
+        (env10.width, env10.length),
+        (Length::inch(4.125), Length::inch(9.5))
+    );
+
+    let (w, l) = (Length::inch(8.5), Length::inch(11.0));
+    assert_eq!(media::nearest(w, l).name, "na_letter_8.5x11in");
+    assert_eq!(media::nearest(l, w).name, "na_letter_8.5x11in");
+    let (w, l) = (Length::mm(211.0), Length::mm(296.0));
+    assert_eq!(media::nearest(w, l).name, "iso_a4_210x297mm");
+    assert_eq!(
+        media::by_size(Length::mm(210.5), Length::mm(296.5)),
+        Some(a4)
+    );
+    assert!(media::by_size(Length::mm(213.0), Length::mm(297.0)).is_none());
+}
+
+#[test]
+fn test_media_table() {
+    for (i, media) in MEDIA_SIZES.iter().enumerate() {
+        assert!(media.width <= media.length, "{}", media.name);
+        assert!(
+            MEDIA_SIZES[i + 1..].iter().all(|v| v.name != media.name),
+            "{}",
+            media.name
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_paper_size() {
+    let a4 = media::by_name("iso_a4").expect("a4");
+    assert_eq!(PaperSize::A4.media_size(), Some(a4));
+    assert_eq!(PaperSize::from(a4), PaperSize::A4);
+    assert_eq!(PaperSize::Named("A4".into()).media_size(), Some(a4));
+    assert_eq!(
+        PaperSize::Custom {
+            width: Length::mm(210.0),
+            height: Length::mm(297.0),
+        }
+        .media_size(),
+        Some(a4)
+    );
+
+    let b5 = media::by_name("iso_b5").expect("b5");
+    assert_eq!(
+        PaperSize::from(b5),
+        PaperSize::Named("iso_b5_176x250mm".into())
+    );
+    assert_eq!(PaperSize::from(b5).media_size(), Some(b5));
+}
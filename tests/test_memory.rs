@@ -0,0 +1,95 @@
+use uniprint::backend::memory::{Event, MemoryJob};
+use uniprint::driver::datamax::{Datamax, ScaleSize, TextScale, TextSys};
+use uniprint::{Driver, Length};
+
+#[test]
+fn test_datamax_stream() -> std::io::Result<()> {
+    let mut dm = Datamax::with_backend(MemoryJob::new());
+
+    dm.start_page()?;
+    dm.start_label()?;
+    dm.print_density(10)?;
+    dm.text_scale(
+        TextScale::new().size(ScaleSize::S12),
+        Length::inch(1.0),
+        Length::inch(2.0),
+        "Hi",
+    )?;
+    dm.text_sys(
+        TextSys::new().font(2).hor_expand(12),
+        Length::inch(0.05),
+        Length::inch(0.1),
+        "ä",
+    )?;
+    dm.end_label()?;
+    dm.end_page()?;
+    dm.close()?;
+
+    assert_eq!(
+        dm.print.data(),
+        b"\x02L\rH10\r1911A1201000200Hi\r12L100000050010\x84\rE\r"
+    );
+    assert_eq!(
+        dm.print.events(),
+        &[Event::StartPage(0), Event::EndPage(44), Event::Close(44)]
+    );
+    assert!(dm.print.is_closed());
+    assert!(!dm.print.is_aborted());
+
+    Ok(())
+}
+
+#[test]
+fn test_datamax_metric() -> std::io::Result<()> {
+    let mut dm = Datamax::with_backend(MemoryJob::new());
+
+    dm.start_label()?;
+    dm.metric()?;
+    dm.offset_x(Length::mm(2.0))?;
+    dm.text_sys(TextSys::new(), Length::mm(12.5), Length::inch(1.0), "A")?;
+
+    assert_eq!(dm.print.data(), b"\x02L\rm\rC0020\r101100001250254A\r");
+
+    Ok(())
+}
+
+#[test]
+fn test_datamax_range() -> std::io::Result<()> {
+    let mut dm = Datamax::with_backend(MemoryJob::new());
+
+    dm.metric()?;
+    dm.text_sys(TextSys::new(), Length::mm(999.9), Length::mm(0.0), "A")?;
+    let len = dm.print.data().len();
+
+    for v in [Length::mm(-1.0), Length::mm(1000.0)] {
+        let e = dm.offset_x(v).expect_err("range");
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+        let e = dm
+            .text_sys(TextSys::new(), v, Length::mm(0.0), "A")
+            .expect_err("range");
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    }
+    // nothing of the rejected records was written.
+    assert_eq!(dm.print.data().len(), len);
+
+    Ok(())
+}
+
+#[test]
+fn test_pages() -> std::io::Result<()> {
+    let mut dm = Datamax::with_backend(MemoryJob::new());
+
+    dm.start_page()?;
+    dm.copies(2)?;
+    dm.end_page()?;
+    dm.start_page()?;
+    dm.copies(3)?;
+    dm.abort()?;
+
+    let pages = dm.print.pages();
+    assert_eq!(pages, vec![&b"Q0002\r"[..], &b"Q0003\r"[..]]);
+    assert!(dm.print.is_aborted());
+    assert!(!dm.print.is_closed());
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+#![cfg(target_os = "linux")]
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+
+use uniprint::ipp::Value;
+use uniprint::{
+    ColorMode, Duplex, Finishings, Length, Orientation, PaperSize, PaperSource, PaperType, Quality,
+};
+
+fn round_trip<T: PartialEq + Debug>(
+    values: &[T],
+    keyword: fn(&T) -> Cow<'_, str>,
+    from_keyword: fn(&str) -> T,
+    ipp_value: fn(&T) -> Value,
+    from_ipp_value: fn(&Value) -> Option<T>,
+) {
+    for v in values {
+        assert_eq!(&from_keyword(&keyword(v)), v);
+        assert_eq!(from_ipp_value(&ipp_value(v)).as_ref(), Some(v));
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    round_trip(
+        &[
+            Finishings::None,
+            Finishings::Staple,
+            Finishings::Punch,
+            Finishings::Cover,
+            Finishings::Bind,
+            Finishings::Fold,
+            Finishings::Trim,
+            Finishings::Named("saddle-stitch".into()),
+        ],
+        Finishings::keyword,
+        Finishings::from_keyword,
+        Finishings::ipp_value,
+        Finishings::from_ipp_value,
+    );
+    round_trip(
+        &[
+            PaperSize::Size3x5,
+            PaperSize::Size4x6,
+            PaperSize::Size5x7,
+            PaperSize::Size8x10,
+            PaperSize::A3,
+            PaperSize::A4,
+            PaperSize::A5,
+            PaperSize::A6,
+            PaperSize::Env10,
+            PaperSize::EnvDl,
+            PaperSize::Legal,
+            PaperSize::Letter,
+            PaperSize::PhotoL,
+            PaperSize::SuperBA3,
+            PaperSize::Tabloid,
+            PaperSize::Named("iso_b5_176x250mm".into()),
+            PaperSize::Custom {
+                width: Length::mm(100.0),
+                height: Length::inch(2.0),
+            },
+        ],
+        PaperSize::keyword,
+        PaperSize::from_keyword,
+        PaperSize::ipp_value,
+        PaperSize::from_ipp_value,
+    );
+    round_trip(
+        &[
+            PaperSource::Auto,
+            PaperSource::Manual,
+            PaperSource::Named("tray-1".into()),
+        ],
+        PaperSource::keyword,
+        PaperSource::from_keyword,
+        PaperSource::ipp_value,
+        PaperSource::from_ipp_value,
+    );
+    round_trip(
+        &[
+            PaperType::Auto,
+            PaperType::Envelope,
+            PaperType::Labels,
+            PaperType::Letterhead,
+            PaperType::Photo,
+            PaperType::PhotoGlossy,
+            PaperType::PhotoMatte,
+            PaperType::Plain,
+            PaperType::Transparency,
+            PaperType::Named("cardstock".into()),
+        ],
+        PaperType::keyword,
+        PaperType::from_keyword,
+        PaperType::ipp_value,
+        PaperType::from_ipp_value,
+    );
+    round_trip(
+        &[
+            Orientation::Portrait,
+            Orientation::Landscape,
+            Orientation::Named("reverse-landscape".into()),
+        ],
+        Orientation::keyword,
+        Orientation::from_keyword,
+        Orientation::ipp_value,
+        Orientation::from_ipp_value,
+    );
+    round_trip(
+        &[
+            ColorMode::Auto,
+            ColorMode::Monochrome,
+            ColorMode::Color,
+            ColorMode::Named("bi-level".into()),
+        ],
+        ColorMode::keyword,
+        ColorMode::from_keyword,
+        ColorMode::ipp_value,
+        ColorMode::from_ipp_value,
+    );
+    round_trip(
+        &[
+            Quality::Draft,
+            Quality::Normal,
+            Quality::High,
+            Quality::Named("best".into()),
+        ],
+        Quality::keyword,
+        Quality::from_keyword,
+        Quality::ipp_value,
+        Quality::from_ipp_value,
+    );
+    round_trip(
+        &[
+            Duplex::Simplex,
+            Duplex::TwoSidedPortrait,
+            Duplex::TwoSidedLandscape,
+            Duplex::Named("two-sided".into()),
+        ],
+        Duplex::keyword,
+        Duplex::from_keyword,
+        Duplex::ipp_value,
+        Duplex::from_ipp_value,
+    );
+}
+
+#[test]
+fn test_values() {
+    assert_eq!(Orientation::Portrait.ipp_value(), Value::Enum(3));
+    assert_eq!(Orientation::Landscape.ipp_value(), Value::Enum(4));
+    assert_eq!(Orientation::from_keyword("3"), Orientation::Portrait);
+    assert_eq!(
+        ColorMode::Monochrome.ipp_value(),
+        Value::Keyword("monochrome".into())
+    );
+    assert_eq!(ColorMode::Color.keyword(), "color");
+    assert_eq!(Quality::High.keyword(), "high");
+    assert_eq!(Finishings::from_keyword("7"), Finishings::Bind);
+    assert_eq!(Finishings::Named("20".into()).ipp_value(), Value::Enum(20));
+    assert_eq!(Duplex::from_keyword(""), Duplex::Simplex);
+    assert_eq!(
+        PaperSize::Custom {
+            width: Length::mm(100.0),
+            height: Length::inch(2.0),
+        }
+        .keyword(),
+        "custom_100x50.8mm"
+    );
+    assert_eq!(
+        PaperSize::from_keyword("custom_4x6in"),
+        PaperSize::Custom {
+            width: Length::inch(4.0),
+            height: Length::inch(6.0),
+        }
+    );
+}
@@ -1,3 +1,6 @@
+// Kept as written, newer clippy lints are allowed here.
+#![allow(clippy::field_reassign_with_default, clippy::unused_io_amount)]
+
 use std::io::Write;
 
 use uniprint::*;
@@ -0,0 +1,54 @@
+use uniprint::{Reason, Severity, StateReason};
+
+#[test]
+fn test_reason() {
+    let r = StateReason::parse("media-low");
+    assert_eq!(r.reason, Reason::MediaLow);
+    assert_eq!(r.severity, Severity::Warning);
+
+    let r = StateReason::parse("media-empty-report");
+    assert_eq!(r.keyword, "media-empty");
+    assert_eq!(r.reason, Reason::MediaEmpty);
+    assert_eq!(r.severity, Severity::Report);
+
+    let r = StateReason::parse("cups-missing-filter-warning");
+    assert_eq!(r.keyword, "cups-missing-filter");
+    assert_eq!(r.reason, Reason::MissingFilter);
+    assert_eq!(r.severity, Severity::Warning);
+    assert_eq!(r.to_string(), "cups-missing-filter-warning");
+
+    let r = StateReason::parse("com.example-smoke-error");
+    assert_eq!(r.keyword, "com.example-smoke");
+    assert_eq!(r.reason, Reason::Unknown);
+    assert_eq!(r.severity, Severity::Error);
+}
+
+#[test]
+fn test_reason_list() {
+    let l = StateReason::parse_list("media-low-warning,toner-low-report,,offline-report");
+    assert_eq!(l.len(), 3);
+    assert_eq!(l[0].reason, Reason::MediaLow);
+    assert_eq!(l[1].reason, Reason::TonerLow);
+    assert_eq!(l[1].severity, Severity::Report);
+    assert_eq!(l[2].reason, Reason::Offline);
+
+    assert!(StateReason::parse_list("none").is_empty());
+    assert!(StateReason::parse_list("").is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_reason_status() {
+    use uniprint::{Info, PrinterState, Status};
+
+    let mut info = Info::default();
+    info.printer_state = PrinterState::Stopped;
+    info.state_reasons = StateReason::parse_list("connecting-to-device");
+    assert_eq!(info.status(), Status::Error);
+
+    info.state_reasons = StateReason::parse_list("media-low");
+    assert_eq!(info.status(), Status::Warn);
+
+    info.state_reasons = StateReason::parse_list("paused");
+    assert_eq!(info.status(), Status::Stopped);
+}
@@ -0,0 +1,72 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+use uniprint::backend::socket::SocketJob;
+use uniprint::driver::datamax::Datamax;
+use uniprint::{Driver, PrintError};
+
+fn listen() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr").to_string();
+    (listener, addr)
+}
+
+#[test]
+fn test_socket() -> std::io::Result<()> {
+    let (listener, addr) = listen();
+    let server = thread::spawn(move || {
+        let (mut conn, _) = listener.accept().expect("accept");
+        let mut buf = Vec::new();
+        conn.read_to_end(&mut buf).expect("read");
+        buf
+    });
+
+    let mut job = SocketJob::new_with(
+        &addr,
+        Some(Duration::from_secs(5)),
+        Some(Duration::from_secs(5)),
+    )?;
+    job.write_all(b"test0")?;
+    job.close()?;
+
+    assert_eq!(server.join().expect("join"), b"test0");
+
+    assert!(job.write_all(b"more").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_socket_datamax() -> std::io::Result<()> {
+    let (listener, addr) = listen();
+    let server = thread::spawn(move || {
+        let (mut conn, _) = listener.accept().expect("accept");
+        let mut buf = Vec::new();
+        conn.read_to_end(&mut buf).expect("read");
+        buf
+    });
+
+    let mut dm = Datamax::with_backend(SocketJob::new(&addr)?);
+    dm.start_label()?;
+    dm.end_label()?;
+    dm.close()?;
+
+    assert_eq!(server.join().expect("join"), b"\x02L\rE\r");
+
+    Ok(())
+}
+
+#[test]
+fn test_socket_refused() {
+    let (listener, addr) = listen();
+    drop(listener);
+
+    let err = SocketJob::new(&addr).expect_err("refused");
+    let err = err
+        .get_ref()
+        .and_then(|v| v.downcast_ref::<PrintError>())
+        .expect("print error");
+    assert!(matches!(err, PrintError::Network(_)));
+}